@@ -0,0 +1,569 @@
+use bitcoin_hashes::Hash;
+use codec::alloc::string::String;
+use codec::{Decode, Encode};
+use primitive_types::{H160, H256};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_std::prelude::*;
+use x_core::Error;
+
+use crate::network::Network;
+use crate::types::{OpCode, Script, HASH160_SIZE_HEX, P2PKH_SCRIPT_SIZE, P2SH_SCRIPT_SIZE};
+use crate::{base58, bech32};
+
+/// Size of a P2WPKH (v0) output script: 0x00 - 0x14 - <20 bytes pubkey hash>
+pub const P2WPKH_V0_SCRIPT_SIZE: u32 = 22;
+/// Size of a P2WSH (v0) or P2TR (v1) output script: <version> - 0x20 - <32 bytes hash>
+pub const P2WSH_V0_SCRIPT_SIZE: u32 = 34;
+pub const P2TR_SCRIPT_SIZE: u32 = 34;
+pub const HASH256_SIZE_HEX: u8 = 0x20;
+/// Size of a Base58Check payload: <1 byte version> - <20 bytes hash>
+const BASE58_PAYLOAD_SIZE: usize = 21;
+
+/// A Bitcoin address, i.e. the type of the output script together with
+/// the hash (or witness program) it commits to
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum BtcAddress {
+    /// Pay to public key hash
+    P2PKH(H160),
+    /// Pay to script hash
+    P2SH(H160),
+    /// Pay to witness public key hash (SegWit v0)
+    P2WPKHv0(H160),
+    /// Pay to witness script hash (SegWit v0)
+    P2WSHv0(H256),
+    /// Pay to taproot (SegWit v1)
+    P2TR(H256),
+}
+
+impl Default for BtcAddress {
+    fn default() -> Self {
+        BtcAddress::P2PKH(H160::zero())
+    }
+}
+
+impl BtcAddress {
+    /// Returns the hash or witness program committed to by the address
+    pub fn hash_bytes(&self) -> &[u8] {
+        match self {
+            BtcAddress::P2PKH(hash) | BtcAddress::P2SH(hash) | BtcAddress::P2WPKHv0(hash) => {
+                hash.as_bytes()
+            }
+            BtcAddress::P2WSHv0(hash) | BtcAddress::P2TR(hash) => hash.as_bytes(),
+        }
+    }
+
+    /// Parses the address paid to by an output script (`scriptPubKey`)
+    pub fn from_script_pubkey(script: &[u8]) -> Result<BtcAddress, Error> {
+        let script_len = script.len() as u32;
+        if script_len == 0 {
+            return Err(Error::UnsupportedOutputFormat);
+        }
+
+        // Witness v0
+        // Format:
+        // 0x00 (OP_0) - 0x14 (20 bytes len) - <20 bytes pubkey hash>
+        // 0x00 (OP_0) - 0x20 (32 bytes len) - <32 bytes script hash>
+        if script[0] == OpCode::Op0 as u8 {
+            if script_len < 2 || script[1] as u32 != script_len - 2 {
+                return Err(Error::MalformedWitnessOutput);
+            }
+            return match script_len {
+                P2WPKH_V0_SCRIPT_SIZE => Ok(BtcAddress::P2WPKHv0(H160::from_slice(&script[2..]))),
+                P2WSH_V0_SCRIPT_SIZE => Ok(BtcAddress::P2WSHv0(H256::from_slice(&script[2..]))),
+                _ => Err(Error::MalformedWitnessOutput),
+            };
+        }
+
+        // Witness v1
+        // Format:
+        // 0x51 (OP_1) - 0x20 (32 bytes len) - <32 bytes tweaked public key>
+        if script[0] == OpCode::Op1 as u8 {
+            if script_len != P2TR_SCRIPT_SIZE || script[1] != HASH256_SIZE_HEX {
+                return Err(Error::MalformedWitnessOutput);
+            }
+            return Ok(BtcAddress::P2TR(H256::from_slice(&script[2..])));
+        }
+
+        // P2PKH
+        // Format:
+        // 0x76 (OP_DUP) - 0xa9 (OP_HASH160) - 0x14 (20 bytes len) - <20 bytes pubkey hash> - 0x88 (OP_EQUALVERIFY) - 0xac (OP_CHECKSIG)
+        if script_len == P2PKH_SCRIPT_SIZE
+            && script[0..=2]
+                == [
+                    OpCode::OpDup as u8,
+                    OpCode::OpHash160 as u8,
+                    HASH160_SIZE_HEX,
+                ]
+        {
+            let len = script.len();
+            if script[len - 2..] != [OpCode::OpEqualVerify as u8, OpCode::OpCheckSig as u8] {
+                return Err(Error::MalformedP2PKHOutput);
+            }
+            return Ok(BtcAddress::P2PKH(H160::from_slice(&script[3..len - 2])));
+        }
+
+        // P2SH
+        // Format:
+        // 0xa9 (OP_HASH160) - 0x14 (20 bytes hash) - <20 bytes script hash> - 0x87 (OP_EQUAL)
+        if script_len == P2SH_SCRIPT_SIZE
+            && script[0..=1] == [OpCode::OpHash160 as u8, HASH160_SIZE_HEX]
+        {
+            let len = script.len();
+            if script[len - 1] != OpCode::OpEqual as u8 {
+                return Err(Error::MalformedP2SHOutput);
+            }
+            return Ok(BtcAddress::P2SH(H160::from_slice(&script[2..len - 1])));
+        }
+
+        Err(Error::UnsupportedOutputFormat)
+    }
+
+    /// Returns the output script (`scriptPubKey`) paying to this address
+    pub fn to_script_pubkey(&self) -> Script {
+        let mut script = Script::new();
+        match self {
+            BtcAddress::P2PKH(hash) => {
+                script.append(OpCode::OpDup);
+                script.append(OpCode::OpHash160);
+                script.append(HASH160_SIZE_HEX);
+                script.append(hash.as_bytes());
+                script.append(OpCode::OpEqualVerify);
+                script.append(OpCode::OpCheckSig);
+            }
+            BtcAddress::P2SH(hash) => {
+                script.append(OpCode::OpHash160);
+                script.append(HASH160_SIZE_HEX);
+                script.append(hash.as_bytes());
+                script.append(OpCode::OpEqual);
+            }
+            BtcAddress::P2WPKHv0(hash) => {
+                script.append(OpCode::Op0);
+                script.append(HASH160_SIZE_HEX);
+                script.append(hash.as_bytes());
+            }
+            BtcAddress::P2WSHv0(hash) => {
+                script.append(OpCode::Op0);
+                script.append(HASH256_SIZE_HEX);
+                script.append(hash.as_bytes());
+            }
+            BtcAddress::P2TR(hash) => {
+                script.append(OpCode::Op1);
+                script.append(HASH256_SIZE_HEX);
+                script.append(hash.as_bytes());
+            }
+        }
+        script
+    }
+
+    /// Returns the address spent from by an input, given its `scriptSig` and witness.
+    /// Native SegWit inputs are only recognised as P2WPKH (`<signature> <pubkey>`)
+    /// or as P2WSH (`<args>... <witness script>`). Other witnesses, such as
+    /// Taproot key and script path spends, fail with `UnsupportedInputFormat`
+    /// since the spent output cannot be derived from them.
+    pub fn from_input(script_sig: &[u8], witness: &[Vec<u8>]) -> Result<BtcAddress, Error> {
+        if !witness.is_empty() {
+            // nested SegWit: the scriptSig only pushes the witness program,
+            // which is the redeem script of the P2SH output
+            if !script_sig.is_empty() {
+                let redeem_script = script_sig
+                    .get(1..)
+                    .filter(|redeem_script| redeem_script.len() == script_sig[0] as usize)
+                    .ok_or(Error::UnsupportedInputFormat)?;
+                return Ok(BtcAddress::P2SH(hash160(redeem_script)));
+            }
+
+            // native SegWit: the witness is either <signature> <pubkey> or
+            // ends with the witness script
+            let last = witness.last().ok_or(Error::UnsupportedInputFormat)?;
+            if witness.len() == 2 && is_compressed_pubkey(last) {
+                return Ok(BtcAddress::P2WPKHv0(hash160(last)));
+            }
+            // a single element is a Taproot key path spend (or a witness script
+            // without arguments, which cannot be told apart), the last element
+            // of a Taproot script path spend is the control block or the annex
+            if witness.len() < 2 || is_taproot_control_block(last) || is_taproot_annex(last) {
+                return Err(Error::UnsupportedInputFormat);
+            }
+            let script_hash = bitcoin_hashes::sha256::Hash::hash(last);
+            return Ok(BtcAddress::P2WSHv0(H256::from(script_hash.into_inner())));
+        }
+
+        crate::parser::extract_address_scriptsig(script_sig)
+    }
+
+    /// Encodes the address as a string for the given network, i.e.
+    /// Base58Check for P2PKH and P2SH, Bech32 for SegWit v0 and
+    /// Bech32m for Taproot
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - Bitcoin network the address is used on
+    pub fn encode_str(&self, network: Network) -> Result<String, Error> {
+        let params = network.params();
+        match self {
+            BtcAddress::P2PKH(hash) => Ok(encode_base58(params.p2pkh_prefix, hash)),
+            BtcAddress::P2SH(hash) => Ok(encode_base58(params.p2sh_prefix, hash)),
+            BtcAddress::P2WPKHv0(hash) => {
+                bech32::encode_segwit(params.bech32_hrp, 0, hash.as_bytes())
+            }
+            BtcAddress::P2WSHv0(hash) => {
+                bech32::encode_segwit(params.bech32_hrp, 0, hash.as_bytes())
+            }
+            BtcAddress::P2TR(hash) => bech32::encode_segwit(params.bech32_hrp, 1, hash.as_bytes()),
+        }
+    }
+
+    /// Decodes a Base58Check (`1...`, `3...`) or Bech32/Bech32m
+    /// (`bc1q...`, `bc1p...`) address string of the given network
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - address string
+    /// * `network` - Bitcoin network the address must belong to
+    pub fn decode_str(address: &str, network: Network) -> Result<BtcAddress, Error> {
+        let params = network.params();
+
+        if let Some(hrp) = segwit_hrp(address) {
+            if hrp != params.bech32_hrp {
+                return Err(Error::AddressNetworkMismatch);
+            }
+            let (version, program) = bech32::decode_segwit(hrp, address)?;
+            return match (version, program.len()) {
+                (0, 20) => Ok(BtcAddress::P2WPKHv0(H160::from_slice(&program))),
+                (0, 32) => Ok(BtcAddress::P2WSHv0(H256::from_slice(&program))),
+                (1, 32) => Ok(BtcAddress::P2TR(H256::from_slice(&program))),
+                _ => Err(Error::InvalidWitnessProgram),
+            };
+        }
+
+        let payload = base58::check_decode(address)?;
+        if payload.len() != BASE58_PAYLOAD_SIZE {
+            return Err(Error::MalformedAddress);
+        }
+        let hash = H160::from_slice(&payload[1..]);
+        match payload[0] {
+            prefix if prefix == params.p2pkh_prefix => Ok(BtcAddress::P2PKH(hash)),
+            prefix if prefix == params.p2sh_prefix => Ok(BtcAddress::P2SH(hash)),
+            _ => Err(Error::AddressNetworkMismatch),
+        }
+    }
+}
+
+fn encode_base58(prefix: u8, hash: &H160) -> String {
+    let mut payload = Vec::with_capacity(BASE58_PAYLOAD_SIZE);
+    payload.push(prefix);
+    payload.extend_from_slice(hash.as_bytes());
+    base58::check_encode(&payload)
+}
+
+/// Returns the human-readable part of the network whose SegWit
+/// addresses start like the given address, if any
+fn segwit_hrp(address: &str) -> Option<&'static str> {
+    let address = address.as_bytes();
+    [Network::Mainnet, Network::Testnet, Network::Regtest]
+        .iter()
+        .map(|network| network.params().bech32_hrp)
+        .find(|hrp| {
+            address.len() > hrp.len()
+                && address[..hrp.len()].eq_ignore_ascii_case(hrp.as_bytes())
+                && address[hrp.len()] == b'1'
+        })
+}
+
+pub(crate) fn hash160(bytes: &[u8]) -> H160 {
+    H160::from(bitcoin_hashes::hash160::Hash::hash(bytes).into_inner())
+}
+
+/// Checks if the witness element is a 33 byte compressed public key
+fn is_compressed_pubkey(element: &[u8]) -> bool {
+    element.len() == 33 && (element[0] == 0x02 || element[0] == 0x03)
+}
+
+/// Checks if the witness element is a Taproot control block, i.e. the leaf
+/// version and internal key followed by up to 128 merkle path hashes (BIP 341)
+fn is_taproot_control_block(element: &[u8]) -> bool {
+    element.len() >= 33
+        && element.len() <= 33 + 32 * 128
+        && (element.len() - 33) % 32 == 0
+        && element[0] & 0xfe == 0xc0
+}
+
+/// Checks if the witness element is a Taproot annex (BIP 341)
+fn is_taproot_annex(element: &[u8]) -> bool {
+    element.first() == Some(&0x50)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_script_roundtrip(address: BtcAddress, script_hex: &str) {
+        let script = address.to_script_pubkey();
+        assert_eq!(script.as_hex(), script_hex);
+        assert_eq!(
+            BtcAddress::from_script_pubkey(script.as_bytes()),
+            Ok(address)
+        );
+    }
+
+    #[test]
+    fn test_p2pkh_script_roundtrip() {
+        assert_script_roundtrip(
+            BtcAddress::P2PKH(H160::from_slice(
+                &hex::decode("66c7060feb882664ae62ffad0051fe843e318e85").unwrap(),
+            )),
+            "76a91466c7060feb882664ae62ffad0051fe843e318e8588ac",
+        );
+    }
+
+    #[test]
+    fn test_p2sh_script_roundtrip() {
+        assert_script_roundtrip(
+            BtcAddress::P2SH(H160::from_slice(
+                &hex::decode("4a1154d50b03292b3024370901711946cb7cccc3").unwrap(),
+            )),
+            "a9144a1154d50b03292b3024370901711946cb7cccc387",
+        );
+    }
+
+    #[test]
+    fn test_p2wpkh_script_roundtrip() {
+        assert_script_roundtrip(
+            BtcAddress::P2WPKHv0(H160::from_slice(
+                &hex::decode("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap(),
+            )),
+            "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+        );
+    }
+
+    #[test]
+    fn test_p2wsh_script_roundtrip() {
+        assert_script_roundtrip(
+            BtcAddress::P2WSHv0(H256::from_slice(
+                &hex::decode("1863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262")
+                    .unwrap(),
+            )),
+            "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+        );
+    }
+
+    #[test]
+    fn test_p2tr_script_roundtrip() {
+        assert_script_roundtrip(
+            BtcAddress::P2TR(H256::from_slice(
+                &hex::decode("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                    .unwrap(),
+            )),
+            "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        );
+    }
+
+    #[test]
+    fn test_same_hash_different_type_not_equal() {
+        let hash = H160::from_slice(&[1; 20]);
+        assert_ne!(BtcAddress::P2PKH(hash), BtcAddress::P2SH(hash));
+        assert_ne!(BtcAddress::P2PKH(hash), BtcAddress::P2WPKHv0(hash));
+    }
+
+    #[test]
+    fn test_from_input_p2wpkh() {
+        let pubkey =
+            hex::decode("02b4632d08485ff1df2db55b9dafd23347d1c47a457072a1e87be26896549a8737")
+                .unwrap();
+        assert_eq!(
+            BtcAddress::from_input(&[], &[vec![0x30; 71], pubkey.clone()]),
+            Ok(BtcAddress::P2WPKHv0(hash160(&pubkey)))
+        );
+    }
+
+    #[test]
+    fn test_from_input_p2wsh() {
+        // <sig> <2-of-2 multisig witness script>
+        let witness_script = hex::decode(
+            "522102b4632d08485ff1df2db55b9dafd23347d1c47a457072a1e87be26896549a8737\
+             2102b4632d08485ff1df2db55b9dafd23347d1c47a457072a1e87be26896549a873752ae",
+        )
+        .unwrap();
+        let script_hash = bitcoin_hashes::sha256::Hash::hash(&witness_script);
+        assert_eq!(
+            BtcAddress::from_input(&[], &[vec![], vec![0x30; 71], witness_script]),
+            Ok(BtcAddress::P2WSHv0(H256::from(script_hash.into_inner())))
+        );
+    }
+
+    #[test]
+    fn test_from_input_taproot_key_path_fails() {
+        // a single 64 byte Schnorr signature
+        assert_eq!(
+            BtcAddress::from_input(&[], &[vec![0x01; 64]]),
+            Err(Error::UnsupportedInputFormat)
+        );
+        // with a sighash type
+        assert_eq!(
+            BtcAddress::from_input(&[], &[vec![0x01; 65]]),
+            Err(Error::UnsupportedInputFormat)
+        );
+    }
+
+    #[test]
+    fn test_from_input_taproot_script_path_fails() {
+        let mut control_block = vec![0xc0];
+        control_block.extend_from_slice(&[0x01; 32 + 32]);
+        assert_eq!(
+            BtcAddress::from_input(&[], &[vec![0x01; 64], vec![0x51], control_block.clone()]),
+            Err(Error::UnsupportedInputFormat)
+        );
+        // with an annex
+        assert_eq!(
+            BtcAddress::from_input(
+                &[],
+                &[vec![0x01; 64], vec![0x51], control_block, vec![0x50, 0x01]]
+            ),
+            Err(Error::UnsupportedInputFormat)
+        );
+    }
+
+    #[test]
+    fn test_malformed_witness_script_fails() {
+        let script = hex::decode("0015751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        assert_eq!(
+            BtcAddress::from_script_pubkey(&script),
+            Err(Error::MalformedWitnessOutput)
+        );
+    }
+
+    #[test]
+    fn test_scale_encoding_roundtrip() {
+        let address = BtcAddress::P2TR(H256::from_slice(&[2; 32]));
+        let encoded = address.encode();
+        assert_eq!(encoded[0], 4);
+        assert_eq!(BtcAddress::decode(&mut &encoded[..]).ok(), Some(address));
+    }
+
+    fn assert_str_roundtrip(address: BtcAddress, network: Network, address_str: &str) {
+        assert_eq!(address.encode_str(network).unwrap(), address_str);
+        assert_eq!(BtcAddress::decode_str(address_str, network), Ok(address));
+    }
+
+    fn h160(hex_str: &str) -> H160 {
+        H160::from_slice(&hex::decode(hex_str).unwrap())
+    }
+
+    fn h256(hex_str: &str) -> H256 {
+        H256::from_slice(&hex::decode(hex_str).unwrap())
+    }
+
+    #[test]
+    fn test_mainnet_str_roundtrip() {
+        assert_str_roundtrip(
+            BtcAddress::P2PKH(h160("77bff20c60e522dfaa3350c39b030a5d004e839a")),
+            Network::Mainnet,
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+        );
+        assert_str_roundtrip(
+            BtcAddress::P2SH(h160("b472a266d0bd89c13706a4132ccfb16f7c3b9fcb")),
+            Network::Mainnet,
+            "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+        );
+        assert_str_roundtrip(
+            BtcAddress::P2WPKHv0(h160("751e76e8199196d454941c45d1b3a323f1433bd6")),
+            Network::Mainnet,
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+        );
+        assert_str_roundtrip(
+            BtcAddress::P2TR(h256(
+                "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            )),
+            Network::Mainnet,
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+        );
+    }
+
+    #[test]
+    fn test_testnet_and_regtest_str_roundtrip() {
+        assert_str_roundtrip(
+            BtcAddress::P2PKH(h160("243f1394f44554f4ce3fd68649c19adc483ce924")),
+            Network::Testnet,
+            "mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn",
+        );
+        assert_str_roundtrip(
+            BtcAddress::P2SH(h160("4e9f39ca4688ff102128ea4ccda34105324305b0")),
+            Network::Regtest,
+            "2MzQwSSnBHWHqSAqtTVQ6v47XtaisrJa1Vc",
+        );
+        assert_str_roundtrip(
+            BtcAddress::P2WSHv0(h256(
+                "1863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+            )),
+            Network::Testnet,
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+        );
+        assert_str_roundtrip(
+            BtcAddress::P2WPKHv0(h160("751e76e8199196d454941c45d1b3a323f1433bd6")),
+            Network::Regtest,
+            "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080",
+        );
+    }
+
+    #[test]
+    fn test_decode_uppercase_segwit_address() {
+        assert_eq!(
+            BtcAddress::decode_str(
+                "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+                Network::Mainnet
+            ),
+            Ok(BtcAddress::P2WPKHv0(h160(
+                "751e76e8199196d454941c45d1b3a323f1433bd6"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_decode_wrong_network_fails() {
+        assert_eq!(
+            BtcAddress::decode_str("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2", Network::Testnet),
+            Err(Error::AddressNetworkMismatch)
+        );
+        assert_eq!(
+            BtcAddress::decode_str(
+                "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+                Network::Regtest
+            ),
+            Err(Error::AddressNetworkMismatch)
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_checksum_fails() {
+        assert_eq!(
+            BtcAddress::decode_str("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLz", Network::Mainnet),
+            Err(Error::InvalidBase58Checksum)
+        );
+        assert_eq!(
+            BtcAddress::decode_str(
+                "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5",
+                Network::Mainnet
+            ),
+            Err(Error::InvalidBech32Checksum)
+        );
+    }
+
+    #[test]
+    fn test_decode_unsupported_witness_program_fails() {
+        // witness v2
+        assert_eq!(
+            BtcAddress::decode_str("bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs", Network::Mainnet),
+            Err(Error::InvalidWitnessProgram)
+        );
+        // witness v1 with a 20 bytes program
+        assert_eq!(
+            BtcAddress::decode_str(
+                "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kj9wkru",
+                Network::Mainnet
+            ),
+            Err(Error::InvalidWitnessProgram)
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use codec::alloc::string::String;
+use sp_std::{prelude::*, vec};
+use x_core::Error;
+
+use crate::utils::sha256d;
+
+/// Base58 alphabet used by Bitcoin (no `0`, `O`, `I` and `l`)
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Size of the checksum appended to Base58Check payloads
+const CHECKSUM_SIZE: usize = 4;
+
+/// Encodes bytes as a Base58 string
+///
+/// # Arguments
+///
+/// * `data` - bytes to encode
+pub fn encode(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|byte| **byte == 0).count();
+
+    // base58 digits, least significant first
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for byte in &data[zeros..] {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut result = String::with_capacity(zeros + digits.len());
+    for _ in 0..zeros {
+        result.push(ALPHABET[0] as char);
+    }
+    for digit in digits.iter().rev() {
+        result.push(ALPHABET[*digit as usize] as char);
+    }
+    result
+}
+
+/// Decodes a Base58 string into bytes
+///
+/// # Arguments
+///
+/// * `input` - Base58 string to decode
+pub fn decode(input: &str) -> Result<Vec<u8>, Error> {
+    let zeros = input.bytes().take_while(|c| *c == ALPHABET[0]).count();
+
+    // decoded bytes, least significant first
+    let mut bytes: Vec<u8> = Vec::with_capacity(input.len() * 733 / 1000 + 1);
+    for c in input.bytes() {
+        let mut carry = ALPHABET
+            .iter()
+            .position(|a| *a == c)
+            .ok_or(Error::InvalidBase58Character)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut result = vec![0u8; zeros];
+    result.extend(bytes.iter().rev());
+    Ok(result)
+}
+
+/// Encodes a payload as a Base58Check string, i.e. with the first
+/// four bytes of its double SHA256 appended
+///
+/// # Arguments
+///
+/// * `payload` - version byte(s) followed by the data to encode
+pub fn check_encode(payload: &[u8]) -> String {
+    let mut data = payload.to_vec();
+    data.extend_from_slice(&sha256d(payload)[..CHECKSUM_SIZE]);
+    encode(&data)
+}
+
+/// Decodes a Base58Check string and verifies its checksum,
+/// returning the payload without the checksum
+///
+/// # Arguments
+///
+/// * `input` - Base58Check string to decode
+pub fn check_decode(input: &str) -> Result<Vec<u8>, Error> {
+    let mut data = decode(input)?;
+    if data.len() < CHECKSUM_SIZE {
+        return Err(Error::InvalidBase58Checksum);
+    }
+    let checksum = data.split_off(data.len() - CHECKSUM_SIZE);
+    if sha256d(&data)[..CHECKSUM_SIZE] != checksum[..] {
+        return Err(Error::InvalidBase58Checksum);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let data = hex::decode("00000077bff20c60e522dfaa3350c39b030a5d004e839a").unwrap();
+        let encoded = encode(&data);
+        assert!(encoded.starts_with("11"));
+        assert_eq!(decode(&encoded), Ok(data));
+        assert_eq!(encode(&[]), "");
+        assert_eq!(decode(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_check_decode_p2pkh_address() {
+        assert_eq!(
+            check_decode("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2"),
+            Ok(hex::decode("0077bff20c60e522dfaa3350c39b030a5d004e839a").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_check_encode_p2sh_address() {
+        let payload = hex::decode("05b472a266d0bd89c13706a4132ccfb16f7c3b9fcb").unwrap();
+        assert_eq!(check_encode(&payload), "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy");
+    }
+
+    #[test]
+    fn test_check_decode_invalid_checksum_fails() {
+        assert_eq!(
+            check_decode("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3"),
+            Err(Error::InvalidBase58Checksum)
+        );
+        assert_eq!(check_decode("1"), Err(Error::InvalidBase58Checksum));
+    }
+
+    #[test]
+    fn test_decode_invalid_character_fails() {
+        assert_eq!(
+            decode("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNV0"),
+            Err(Error::InvalidBase58Character)
+        );
+    }
+}
@@ -0,0 +1,329 @@
+use codec::alloc::string::String;
+use sp_std::{prelude::*, vec};
+use x_core::Error;
+
+/// Bech32 alphabet, indexed by 5-bit value
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Separator between the human-readable part and the data part
+const SEPARATOR: u8 = b'1';
+
+/// Number of 5-bit values in the checksum
+const CHECKSUM_SIZE: usize = 6;
+
+/// Maximum length of a Bech32 string (BIP173)
+const MAX_LENGTH: usize = 90;
+
+const GENERATOR: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+
+/// Checksum variant of a Bech32 string
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Variant {
+    /// Original checksum (BIP173), used by SegWit v0 addresses
+    Bech32,
+    /// Modified checksum (BIP350), used by SegWit v1+ addresses
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2bc8_30a3,
+        }
+    }
+
+    fn from_constant(constant: u32) -> Option<Variant> {
+        match constant {
+            c if c == Variant::Bech32.constant() => Some(Variant::Bech32),
+            c if c == Variant::Bech32m.constant() => Some(Variant::Bech32m),
+            _ => None,
+        }
+    }
+
+    /// Returns the checksum variant required for the given witness version
+    pub fn for_witness_version(version: u8) -> Variant {
+        if version == 0 {
+            Variant::Bech32
+        } else {
+            Variant::Bech32m
+        }
+    }
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut checksum: u32 = 1;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x01ff_ffff) << 5) ^ *value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn hrp_expand(hrp: &[u8]) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.iter().map(|c| c >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.iter().map(|c| c & 0x1f));
+    expanded
+}
+
+/// Encodes 5-bit values as a lowercase Bech32 string
+///
+/// # Arguments
+///
+/// * `hrp` - lowercase human-readable part
+/// * `data` - 5-bit values to encode
+/// * `variant` - checksum variant
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> String {
+    let mut values = hrp_expand(hrp.as_bytes());
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0; CHECKSUM_SIZE]);
+    let checksum = polymod(&values) ^ variant.constant();
+
+    let mut result = String::with_capacity(hrp.len() + 1 + data.len() + CHECKSUM_SIZE);
+    result.push_str(hrp);
+    result.push(SEPARATOR as char);
+    for value in data {
+        result.push(CHARSET[*value as usize] as char);
+    }
+    for i in 0..CHECKSUM_SIZE {
+        let value = (checksum >> (5 * (CHECKSUM_SIZE - 1 - i))) & 0x1f;
+        result.push(CHARSET[value as usize] as char);
+    }
+    result
+}
+
+/// Decodes a Bech32 or Bech32m string, returning the lowercase
+/// human-readable part, the 5-bit data values (without checksum)
+/// and the checksum variant
+///
+/// # Arguments
+///
+/// * `input` - Bech32 string to decode
+pub fn decode(input: &str) -> Result<(String, Vec<u8>, Variant), Error> {
+    let bytes = input.as_bytes();
+    if bytes.len() > MAX_LENGTH {
+        return Err(Error::MalformedAddress);
+    }
+    let has_lower = bytes.iter().any(|c| c.is_ascii_lowercase());
+    let has_upper = bytes.iter().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err(Error::MalformedAddress);
+    }
+
+    let separator = bytes
+        .iter()
+        .rposition(|c| *c == SEPARATOR)
+        .ok_or(Error::MalformedAddress)?;
+    if separator == 0 || separator + 1 + CHECKSUM_SIZE > bytes.len() {
+        return Err(Error::MalformedAddress);
+    }
+
+    let (hrp, data_part) = (&bytes[..separator], &bytes[separator + 1..]);
+    if hrp.iter().any(|c| *c < 33 || *c > 126) {
+        return Err(Error::InvalidBech32Character);
+    }
+    let hrp: Vec<u8> = hrp.iter().map(|c| c.to_ascii_lowercase()).collect();
+
+    let mut data = data_part
+        .iter()
+        .map(|c| {
+            CHARSET
+                .iter()
+                .position(|a| *a == c.to_ascii_lowercase())
+                .map(|value| value as u8)
+                .ok_or(Error::InvalidBech32Character)
+        })
+        .collect::<Result<Vec<u8>, Error>>()?;
+
+    let mut values = hrp_expand(&hrp);
+    values.extend_from_slice(&data);
+    let variant = Variant::from_constant(polymod(&values)).ok_or(Error::InvalidBech32Checksum)?;
+
+    data.truncate(data.len() - CHECKSUM_SIZE);
+    // hrp only contains printable ASCII characters
+    let hrp = String::from_utf8(hrp).map_err(|_| Error::InvalidBech32Character)?;
+    Ok((hrp, data, variant))
+}
+
+/// Regroups a sequence of `from`-bit values into `to`-bit values
+///
+/// # Arguments
+///
+/// * `data` - values of at most `from` bits each
+/// * `from` - number of bits per input value
+/// * `to` - number of bits per output value
+/// * `pad` - if set, the remaining bits are zero-padded, otherwise
+///   they must be zero and fewer than `from`
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, Error> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max_value: u32 = (1 << to) - 1;
+    let max_acc: u32 = (1 << (from + to - 1)) - 1;
+    let mut result = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+
+    for value in data {
+        let value = *value as u32;
+        if value >> from != 0 {
+            return Err(Error::MalformedAddress);
+        }
+        acc = ((acc << from) | value) & max_acc;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((acc >> bits) & max_value) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            result.push(((acc << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max_value) != 0 {
+        return Err(Error::MalformedAddress);
+    }
+    Ok(result)
+}
+
+/// Encodes a witness program as a SegWit address (BIP173, BIP350)
+///
+/// # Arguments
+///
+/// * `hrp` - human-readable part of the network
+/// * `version` - witness version
+/// * `program` - witness program
+pub fn encode_segwit(hrp: &str, version: u8, program: &[u8]) -> Result<String, Error> {
+    if version > 16 {
+        return Err(Error::InvalidWitnessProgram);
+    }
+    let mut data = vec![version];
+    data.extend(convert_bits(program, 8, 5, true)?);
+    Ok(encode(hrp, &data, Variant::for_witness_version(version)))
+}
+
+/// Decodes a SegWit address (BIP173, BIP350), returning the witness
+/// version and program
+///
+/// # Arguments
+///
+/// * `hrp` - expected human-readable part of the network
+/// * `address` - SegWit address to decode
+pub fn decode_segwit(hrp: &str, address: &str) -> Result<(u8, Vec<u8>), Error> {
+    let (address_hrp, data, variant) = decode(address)?;
+    if address_hrp != hrp {
+        return Err(Error::AddressNetworkMismatch);
+    }
+
+    let (version, program) = data.split_first().ok_or(Error::InvalidWitnessProgram)?;
+    if *version > 16 {
+        return Err(Error::InvalidWitnessProgram);
+    }
+    if variant != Variant::for_witness_version(*version) {
+        return Err(Error::InvalidBech32Checksum);
+    }
+
+    let program = convert_bits(program, 5, 8, false)?;
+    if program.len() < 2 || program.len() > 40 {
+        return Err(Error::InvalidWitnessProgram);
+    }
+    if *version == 0 && program.len() != 20 && program.len() != 32 {
+        return Err(Error::InvalidWitnessProgram);
+    }
+    Ok((*version, program))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_segwit_v0_uppercase() {
+        assert_eq!(
+            decode_segwit("bc", "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4"),
+            Ok((
+                0,
+                hex::decode("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_segwit_v1_uses_bech32m() {
+        let program =
+            hex::decode("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+        let address = encode_segwit("bc", 1, &program).unwrap();
+        assert_eq!(
+            address,
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+        );
+        assert_eq!(decode_segwit("bc", &address), Ok((1, program)));
+    }
+
+    #[test]
+    fn test_decode_invalid_checksum_fails() {
+        assert_eq!(
+            decode("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5"),
+            Err(Error::InvalidBech32Checksum)
+        );
+    }
+
+    #[test]
+    fn test_decode_segwit_wrong_variant_fails() {
+        // v0 program with a Bech32m checksum
+        assert_eq!(
+            decode_segwit("bc", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh"),
+            Err(Error::InvalidBech32Checksum)
+        );
+        // v1 program with a Bech32 checksum
+        assert_eq!(
+            decode_segwit(
+                "bc",
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd"
+            ),
+            Err(Error::InvalidBech32Checksum)
+        );
+    }
+
+    #[test]
+    fn test_decode_mixed_case_fails() {
+        assert_eq!(
+            decode("tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sL5k7"),
+            Err(Error::MalformedAddress)
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_character_fails() {
+        assert_eq!(
+            decode("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3tb"),
+            Err(Error::InvalidBech32Character)
+        );
+    }
+
+    #[test]
+    fn test_decode_segwit_wrong_hrp_fails() {
+        assert_eq!(
+            decode_segwit("tb", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+            Err(Error::AddressNetworkMismatch)
+        );
+    }
+
+    #[test]
+    fn test_convert_bits_roundtrip() {
+        let data = vec![0xff, 0x00, 0xab, 0xcd, 0x12];
+        let converted = convert_bits(&data, 8, 5, true).unwrap();
+        assert_eq!(convert_bits(&converted, 5, 8, false), Ok(data));
+    }
+}
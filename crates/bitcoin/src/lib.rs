@@ -5,8 +5,14 @@
 #[cfg(test)]
 extern crate mocktopus;
 
+pub mod address;
+pub mod base58;
+pub mod bech32;
+
 pub mod merkle;
 
+pub mod network;
+
 pub mod types;
 
 pub mod formatter;
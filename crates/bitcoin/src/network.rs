@@ -0,0 +1,174 @@
+use codec::{Decode, Encode};
+use primitive_types::U256;
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{compact_to_target, target_to_compact};
+
+/// Bitcoin network whose consensus rules are followed
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Regtest,
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        Network::Mainnet
+    }
+}
+
+/// Consensus parameters of a Bitcoin network required to verify block headers
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NetworkParams {
+    /// Number of blocks between two difficulty retargets
+    pub difficulty_adjustment_interval: u32,
+    /// Expected duration of a retarget interval in seconds
+    pub target_timespan: u32,
+    /// Expected time between two blocks in seconds
+    pub target_spacing: u32,
+    /// Easiest allowed (unrounded) target, i.e. the proof-of-work limit
+    pub max_target: U256,
+    /// If set, blocks may use the easiest target when no block was
+    /// found for twice the target spacing (testnet, regtest)
+    pub allow_min_difficulty_blocks: bool,
+    /// If set, the target is never adjusted (regtest)
+    pub no_retargeting: bool,
+    /// Height from which blocks must have at least version 2 (BIP34)
+    pub bip34_height: u32,
+    /// Height from which blocks must have at least version 3 (BIP66)
+    pub bip66_height: u32,
+    /// Height from which blocks must have at least version 4 (BIP65)
+    pub bip65_height: u32,
+    /// Version byte of Base58Check encoded P2PKH addresses
+    pub p2pkh_prefix: u8,
+    /// Version byte of Base58Check encoded P2SH addresses
+    pub p2sh_prefix: u8,
+    /// Human-readable part of Bech32 encoded SegWit addresses (BIP173)
+    pub bech32_hrp: &'static str,
+}
+
+/// Unrounded Maximum Target of mainnet and testnet
+/// 0x00000000FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF
+pub const UNROUNDED_MAX_TARGET: U256 = U256([
+    <u64>::max_value(),
+    <u64>::max_value(),
+    <u64>::max_value(),
+    0x0000_0000_ffff_ffffu64,
+]);
+
+/// Unrounded Maximum Target of regtest
+/// 0x7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF
+pub const REGTEST_UNROUNDED_MAX_TARGET: U256 = U256([
+    <u64>::max_value(),
+    <u64>::max_value(),
+    <u64>::max_value(),
+    0x7fff_ffff_ffff_ffffu64,
+]);
+
+impl Network {
+    /// Returns the consensus parameters of the network
+    pub fn params(&self) -> NetworkParams {
+        match self {
+            Network::Mainnet => NetworkParams {
+                difficulty_adjustment_interval: 2016,
+                target_timespan: 1_209_600,
+                target_spacing: 600,
+                max_target: UNROUNDED_MAX_TARGET,
+                allow_min_difficulty_blocks: false,
+                no_retargeting: false,
+                bip34_height: 227_931,
+                bip66_height: 363_725,
+                bip65_height: 388_381,
+                p2pkh_prefix: 0x00,
+                p2sh_prefix: 0x05,
+                bech32_hrp: "bc",
+            },
+            Network::Testnet => NetworkParams {
+                difficulty_adjustment_interval: 2016,
+                target_timespan: 1_209_600,
+                target_spacing: 600,
+                max_target: UNROUNDED_MAX_TARGET,
+                allow_min_difficulty_blocks: true,
+                no_retargeting: false,
+                bip34_height: 21_111,
+                bip66_height: 330_776,
+                bip65_height: 581_885,
+                p2pkh_prefix: 0x6f,
+                p2sh_prefix: 0xc4,
+                bech32_hrp: "tb",
+            },
+            Network::Regtest => NetworkParams {
+                difficulty_adjustment_interval: 2016,
+                target_timespan: 1_209_600,
+                target_spacing: 600,
+                max_target: REGTEST_UNROUNDED_MAX_TARGET,
+                allow_min_difficulty_blocks: true,
+                no_retargeting: true,
+                bip34_height: 500,
+                bip66_height: 1_251,
+                bip65_height: 1_351,
+                p2pkh_prefix: 0x6f,
+                p2sh_prefix: 0xc4,
+                bech32_hrp: "bcrt",
+            },
+        }
+    }
+}
+
+impl NetworkParams {
+    /// Returns the easiest target as encoded in a block header, i.e.
+    /// the maximum target rounded to its compact representation
+    pub fn min_difficulty_target(&self) -> U256 {
+        compact_to_target(target_to_compact(&self.max_target))
+    }
+
+    /// Returns the minimum block version required at the given height
+    /// by the BIP34, BIP66 and BIP65 soft forks
+    ///
+    /// # Arguments
+    /// * `block_height` - height of the block
+    pub fn min_block_version(&self, block_height: u32) -> i32 {
+        if block_height >= self.bip65_height {
+            4
+        } else if block_height >= self.bip66_height {
+            3
+        } else if block_height >= self.bip34_height {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_difficulty_target() {
+        // nBits 0x1d00ffff
+        assert_eq!(
+            Network::Mainnet.params().min_difficulty_target(),
+            U256::from(0xffff) << 208
+        );
+        // nBits 0x207fffff
+        assert_eq!(
+            Network::Regtest.params().min_difficulty_target(),
+            U256::from(0x7fffff) << 232
+        );
+    }
+
+    #[test]
+    fn test_min_block_version() {
+        let params = Network::Mainnet.params();
+        assert_eq!(params.min_block_version(227_930), 1);
+        assert_eq!(params.min_block_version(227_931), 2);
+        assert_eq!(params.min_block_version(363_725), 3);
+        assert_eq!(params.min_block_version(388_381), 4);
+        assert_eq!(params.min_block_version(633_040), 4);
+    }
+}
@@ -1,10 +1,6 @@
 #[cfg(test)]
 extern crate mocktopus;
 
-extern crate bitcoin_hashes;
-
-use bitcoin_hashes::Hash;
-
 #[cfg(test)]
 use mocktopus::macros::mockable;
 
@@ -12,6 +8,7 @@ use primitive_types::U256;
 use sp_std::prelude::*;
 use x_core::Error;
 
+use crate::address::{hash160, BtcAddress};
 use crate::types::*;
 
 /// Type to be parsed from a bytes array
@@ -391,7 +388,7 @@ fn parse_transaction_output(raw_output: &[u8]) -> Result<(TransactionOutput, usi
     ))
 }
 
-pub(crate) fn extract_address_hash_scriptsig(input_script: &[u8]) -> Result<Vec<u8>, Error> {
+pub(crate) fn extract_address_scriptsig(input_script: &[u8]) -> Result<BtcAddress, Error> {
     let mut parser = BytesParser::new(input_script);
     let mut p2pkh = true;
 
@@ -411,60 +408,16 @@ pub(crate) fn extract_address_hash_scriptsig(input_script: &[u8]) -> Result<Vec<
         return Err(Error::UnsupportedInputFormat);
     }
     let redeem_script = parser.read(redeem_script_size as usize)?;
-    return Ok(bitcoin_hashes::hash160::Hash::hash(&redeem_script).to_vec());
-}
-
-pub(crate) fn extract_address_hash_scriptpubkey(output_script: &[u8]) -> Result<Vec<u8>, Error> {
-    let script_len = output_script.len();
-    // Witness
-    if output_script[0] == 0 {
-        if script_len < 2 {
-            return Err(Error::MalformedWitnessOutput);
-        }
-        if output_script[1] == (script_len - 2) as u8 {
-            return Ok(output_script[2..].to_vec());
-        } else {
-            return Err(Error::MalformedWitnessOutput);
-        }
-    }
-
-    // P2PKH
-    // 25 bytes
-    // Format:
-    // 0x76 (OP_DUP) - 0xa9 (OP_HASH160) - 0x14 (20 bytes len) - <20 bytes pubkey hash> - 0x88 (OP_EQUALVERIFY) - 0xac (OP_CHECKSIG)
-    if script_len as u32 == P2PKH_SCRIPT_SIZE
-        && output_script[0..=2]
-            == [
-                OpCode::OpDup as u8,
-                OpCode::OpHash160 as u8,
-                HASH160_SIZE_HEX,
-            ]
-    {
-        if output_script[script_len - 2..]
-            != [OpCode::OpEqualVerify as u8, OpCode::OpCheckSig as u8]
-        {
-            return Err(Error::MalformedP2PKHOutput);
-        }
-        return Ok(output_script[3..script_len - 2].to_vec());
-    }
-
-    // P2SH
-    // 23 bytes
-    // Format:
-    // 0xa9 (OP_HASH160) - 0x14 (20 bytes hash) - <20 bytes script hash> - 0x87 (OP_EQUAL)
-    if script_len as u32 == P2SH_SCRIPT_SIZE
-        && output_script[0..=1] == [OpCode::OpHash160 as u8, HASH160_SIZE_HEX]
-    {
-        if output_script[script_len - 1] != OpCode::OpEqual as u8 {
-            return Err(Error::MalformedP2SHOutput);
-        }
-        return Ok(output_script[2..(script_len - 1)].to_vec());
+    let hash = hash160(&redeem_script);
+    if p2pkh {
+        Ok(BtcAddress::P2PKH(hash))
+    } else {
+        Ok(BtcAddress::P2SH(hash))
     }
-    Err(Error::UnsupportedOutputFormat)
 }
 
 pub(crate) fn extract_op_return_data(output_script: &[u8]) -> Result<Vec<u8>, Error> {
-    if output_script[0] != OpCode::OpReturn as u8 {
+    if output_script.len() < 2 || output_script[0] != OpCode::OpReturn as u8 {
         return Err(Error::MalformedOpReturnOutput);
     }
     // Check for max OP_RETURN size
@@ -485,6 +438,7 @@ pub(crate) fn extract_op_return_data(output_script: &[u8]) -> Result<Vec<u8>, Er
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
+    use primitive_types::H160;
 
     // examples from https://bitcoin.org/en/developer-reference#block-headers
 
@@ -706,22 +660,22 @@ pub(crate) mod tests {
     fn test_extract_address_hash_valid_p2pkh() {
         let p2pkh_script = hex::decode(&sample_valid_p2pkh()).unwrap();
 
-        let p2pkh_address: [u8; 20] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let p2pkh_address = BtcAddress::P2PKH(H160::zero());
 
-        let extr_p2pkh = extract_address_hash_scriptpubkey(&p2pkh_script).unwrap();
+        let extr_p2pkh = BtcAddress::from_script_pubkey(&p2pkh_script).unwrap();
 
-        assert_eq!(&extr_p2pkh, &p2pkh_address);
+        assert_eq!(extr_p2pkh, p2pkh_address);
     }
 
     #[test]
     fn test_extract_address_hash_valid_p2sh() {
         let p2sh_script = hex::decode(&sample_valid_p2sh()).unwrap();
 
-        let p2sh_address: [u8; 20] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let p2sh_address = BtcAddress::P2SH(H160::zero());
 
-        let extr_p2sh = extract_address_hash_scriptpubkey(&p2sh_script).unwrap();
+        let extr_p2sh = BtcAddress::from_script_pubkey(&p2sh_script).unwrap();
 
-        assert_eq!(&extr_p2sh, &p2sh_address);
+        assert_eq!(extr_p2sh, p2sh_address);
     }
 
     #[test]
@@ -730,13 +684,13 @@ pub(crate) mod tests {
         let tx_bytes = hex::decode(&raw_tx).unwrap();
         let transaction = parse_transaction(&tx_bytes).unwrap();
 
-        let address: [u8; 20] = [
+        let address = BtcAddress::P2PKH(H160::from([
             126, 125, 148, 208, 221, 194, 29, 131, 191, 188, 252, 119, 152, 228, 84, 126, 223, 8,
             50, 170,
-        ];
-        let extr_address = extract_address_hash_scriptsig(&transaction.inputs[0].script).unwrap();
+        ]));
+        let extr_address = extract_address_scriptsig(&transaction.inputs[0].script).unwrap();
 
-        assert_eq!(&extr_address, &address);
+        assert_eq!(extr_address, address);
     }
 
     #[test]
@@ -745,13 +699,21 @@ pub(crate) mod tests {
         let tx_bytes = hex::decode(&raw_tx).unwrap();
         let transaction = parse_transaction(&tx_bytes).unwrap();
 
-        let address: [u8; 20] = [
+        let address = BtcAddress::P2SH(H160::from([
             233, 195, 221, 12, 7, 170, 199, 97, 121, 235, 199, 106, 108, 120, 212, 214, 124, 108,
             22, 10,
-        ];
-        let extr_address = extract_address_hash_scriptsig(&transaction.inputs[0].script).unwrap();
+        ]));
+        let extr_address = extract_address_scriptsig(&transaction.inputs[0].script).unwrap();
 
-        assert_eq!(&extr_address, &address);
+        assert_eq!(extr_address, address);
+    }
+
+    #[test]
+    fn test_extract_op_return_data_without_push_fails() {
+        assert_eq!(
+            extract_op_return_data(&[OpCode::OpReturn as u8]),
+            Err(Error::MalformedOpReturnOutput)
+        );
     }
 
     /*
@@ -759,7 +721,7 @@ pub(crate) mod tests {
     fn test_extract_address_invalid_p2pkh_fails() {
         let p2pkh_script = hex::decode(&sample_malformed_p2pkh_output()).unwrap();
 
-        assert_eq!(extract_address_hash_scriptpubkey(&p2pkh_script).err(), Some(Error::MalformedP2PKHOutput));
+        assert_eq!(BtcAddress::from_script_pubkey(&p2pkh_script).err(), Some(Error::MalformedP2PKHOutput));
     }
     */
 }
@@ -3,22 +3,20 @@ extern crate hex;
 #[cfg(test)]
 use mocktopus::macros::mockable;
 
-use bitcoin_hashes::Hash;
-
 use codec::alloc::string::String;
 use codec::{Decode, Encode};
 use primitive_types::{H256, U256};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sp_std::collections::btree_set::BTreeSet;
 use sp_std::convert::TryFrom;
 use sp_std::prelude::*;
 use x_core::Error;
 
+use crate::address::BtcAddress;
 use crate::formatter::Formattable;
 use crate::merkle::MerkleProof;
-use crate::parser::{
-    extract_address_hash_scriptpubkey, extract_address_hash_scriptsig, extract_op_return_data,
-    FromLeBytes,
-};
+use crate::parser::{extract_op_return_data, FromLeBytes};
 use crate::utils::{hash256_merkle_step, log2, reverse_endianness, sha256d_le};
 
 pub(crate) const SERIALIZE_TRANSACTION_NO_WITNESS: i32 = 0x4000_0000;
@@ -277,7 +275,7 @@ pub const MAX_OPRETURN_SIZE: usize = 83;
 /// Bitcoin Basic Block Headers
 // TODO: Figure out how to set a pointer to the ChainIndex mapping instead
 #[derive(Encode, Decode, Default, Copy, Clone, PartialEq, Debug)]
-//#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct BlockHeader {
     pub merkle_root: H256Le,
     pub target: U256,
@@ -291,6 +289,14 @@ impl BlockHeader {
     pub fn hash(&self) -> H256Le {
         sha256d_le(&self.format())
     }
+
+    /// Returns the expected number of hashes required to produce a block
+    /// meeting the header's target, computed as 2**256 / (target + 1)
+    pub fn work(&self) -> U256 {
+        // 2**256 does not fit into a U256, but 2**256 / (target + 1) is equal
+        // to (~target / (target + 1)) + 1
+        (!self.target / self.target.saturating_add(U256::one())).saturating_add(U256::one())
+    }
 }
 
 /// Bitcoin transaction input
@@ -310,14 +316,8 @@ impl TransactionInput {
         self.witness = witness;
     }
 
-    pub fn extract_address(&self) -> Result<Vec<u8>, Error> {
-        // Witness
-        if !self.witness.is_empty() {
-            return Ok(bitcoin_hashes::hash160::Hash::hash(&self.witness[1]).to_vec());
-        }
-
-        // P2PKH
-        extract_address_hash_scriptsig(&self.script)
+    pub fn extract_address(&self) -> Result<BtcAddress, Error> {
+        BtcAddress::from_input(&self.script, &self.witness)
     }
 }
 
@@ -382,6 +382,10 @@ impl Script {
         self.bytes.extend(&value.format())
     }
 
+    pub fn is_op_return(&self) -> bool {
+        self.bytes.first() == Some(&(OpCode::OpReturn as u8))
+    }
+
     pub fn extract_op_return_data(&self) -> Result<Vec<u8>, Error> {
         extract_op_return_data(&self.bytes)
     }
@@ -440,6 +444,13 @@ impl TransactionOutput {
         }
     }
 
+    pub fn payment(value: i64, address: &BtcAddress) -> TransactionOutput {
+        TransactionOutput {
+            value,
+            script: address.to_script_pubkey(),
+        }
+    }
+
     pub fn op_return(value: i64, return_content: &[u8]) -> TransactionOutput {
         TransactionOutput {
             value,
@@ -447,8 +458,8 @@ impl TransactionOutput {
         }
     }
 
-    pub fn extract_address(&self) -> Result<Vec<u8>, Error> {
-        extract_address_hash_scriptpubkey(&self.script.bytes)
+    pub fn extract_address(&self) -> Result<BtcAddress, Error> {
+        BtcAddress::from_script_pubkey(&self.script.bytes)
     }
 }
 
@@ -475,25 +486,32 @@ impl Transaction {
 
 /// Bitcoin Enriched Block Headers
 #[derive(Encode, Decode, Default, Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct RichBlockHeader {
     pub block_hash: H256Le,
     pub block_header: BlockHeader,
     pub block_height: u32,
     pub chain_ref: u32,
+    /// Cumulative work of the chain up to and including this block.
+    /// Adding this field changed the storage encoding (see the README).
+    pub chain_work: U256,
 }
 
 impl RichBlockHeader {
     // Creates a RichBlockHeader given a RawBlockHeader, Blockchain identifier and block height
+    // The cumulative chain work is initialized with the work of the block itself
     pub fn construct(
         raw_block_header: RawBlockHeader,
         chain_ref: u32,
         block_height: u32,
     ) -> Result<RichBlockHeader, Error> {
+        let block_header = BlockHeader::from_le_bytes(raw_block_header.as_bytes())?;
         Ok(RichBlockHeader {
             block_hash: raw_block_header.hash(),
-            block_header: BlockHeader::from_le_bytes(raw_block_header.as_bytes())?,
+            block_header,
             block_height,
             chain_ref,
+            chain_work: block_header.work(),
         })
     }
 }
@@ -648,10 +666,14 @@ impl BlockBuilder {
 
 /// Representation of a Bitcoin blockchain
 #[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct BlockChain {
     pub chain_id: u32,
     pub start_height: u32,
     pub max_height: u32,
+    /// Cumulative work of the chain up to and including its tip.
+    /// Adding this field changed the storage encoding (see the README).
+    pub chain_work: U256,
     pub no_data: BTreeSet<u32>,
     pub invalid: BTreeSet<u32>,
 }
@@ -736,6 +758,27 @@ impl H256Le {
     }
 }
 
+/// Serializes the hash as big endian hex string, i.e. as displayed by block explorers
+#[cfg(feature = "std")]
+impl Serialize for H256Le {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex_be())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de> Deserialize<'de> for H256Le {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex_string = String::deserialize(deserializer)?;
+        let bytes =
+            hex::decode(hex_string.trim_start_matches("0x")).map_err(serde::de::Error::custom)?;
+        if bytes.len() != 32 {
+            return Err(serde::de::Error::custom("expected a 32 bytes hash"));
+        }
+        Ok(H256Le::from_bytes_be(&bytes))
+    }
+}
+
 macro_rules! impl_h256le_from_integer {
     ($type:ty) => {
         impl From<$type> for H256Le {
@@ -923,6 +966,7 @@ mod tests {
     use mocktopus::mocking::*;
 
     use super::*;
+    use primitive_types::H160;
     use sp_std::convert::TryInto;
 
     use crate::parser::parse_transaction;
@@ -964,6 +1008,42 @@ mod tests {
         assert_eq!(h256_le, h256);
     }
 
+    #[test]
+    fn test_h256le_serde() {
+        let hash =
+            H256Le::from_hex_be("00000000000000000017306835000000000000000000000000000000000000ff");
+        let json = serde_json::to_string(&hash).unwrap();
+        assert_eq!(
+            json,
+            "\"00000000000000000017306835000000000000000000000000000000000000ff\""
+        );
+        assert_eq!(serde_json::from_str::<H256Le>(&json).unwrap(), hash);
+        assert!(serde_json::from_str::<H256Le>("\"00ff\"").is_err());
+    }
+
+    #[test]
+    fn test_block_header_work() {
+        // the genesis block (nBits 0x1d00ffff) accounts for 0x100010001 hashes
+        let header = BlockHeader {
+            target: U256::from(0xffff) << 208,
+            ..Default::default()
+        };
+        assert_eq!(header.work(), U256::from(0x1_0001_0001u64));
+
+        // halving the target doubles the work
+        let header = BlockHeader {
+            target: (U256::from(0xffff) << 208) >> 1,
+            ..Default::default()
+        };
+        assert_eq!(header.work(), U256::from(0x2_0002_0002u64));
+
+        let header = BlockHeader {
+            target: U256::max_value(),
+            ..Default::default()
+        };
+        assert_eq!(header.work(), U256::one());
+    }
+
     #[test]
     fn test_transaction_hash() {
         let raw_tx = hex::decode(&sample_example_real_rawtx()).unwrap();
@@ -1016,7 +1096,7 @@ mod tests {
         assert_eq!(transaction.outputs[0].value, 100);
         assert_eq!(
             transaction.outputs[0].extract_address().unwrap(),
-            address.as_bytes()
+            BtcAddress::P2PKH(H160::from_slice(address.as_bytes()))
         );
         assert_eq!(transaction.outputs[1].value, 0);
         assert_eq!(
@@ -1152,33 +1232,51 @@ mod tests {
 
     #[test]
     fn extract_witness_address_p2wpkh() {
+        let pubkey =
+            hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+        let input = TransactionInputBuilder::new()
+            .with_coinbase(false)
+            .add_witness(&[0u8; 71])
+            .add_witness(&pubkey)
+            .build();
+
+        let address =
+            H160::from_slice(&hex::decode("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap());
+
+        assert_eq!(input.extract_address(), Ok(BtcAddress::P2WPKHv0(address)));
+    }
+
+    #[test]
+    fn extract_nested_witness_address_p2sh_p2wpkh() {
         let raw_tx = "0200000000010140d43a99926d43eb0e619bf0b3d83b4a31f60c176beecfb9d35bf45e54d0f7420100000017160014a4b4ca48de0b3fffc15404a1acdc8dbaae226955ffffffff0100e1f5050000000017a9144a1154d50b03292b3024370901711946cb7cccc387024830450221008604ef8f6d8afa892dee0f31259b6ce02dd70c545cfcfed8148179971876c54a022076d771d6e91bed212783c9b06e0de600fab2d518fad6f15a2b191d7fbd262a3e0121039d25ab79f41f75ceaf882411fd41fa670a4c672c23ffaf0e361a969cde0692e800000000";
         let tx_bytes = hex::decode(&raw_tx).unwrap();
         let transaction = parse_transaction(&tx_bytes).unwrap();
 
-        let address: [u8; 20] = [
-            164, 180, 202, 72, 222, 11, 63, 255, 193, 84, 4, 161, 172, 220, 141, 186, 174, 34, 105,
-            85,
-        ];
+        // hash160 of the witness program 0x0014a4b4ca48de0b3fffc15404a1acdc8dbaae226955
+        let address = BtcAddress::P2SH(H160::from([
+            41, 40, 244, 58, 241, 141, 45, 96, 232, 168, 67, 84, 13, 128, 134, 179, 5, 52, 19, 57,
+        ]));
 
         let extr_address = transaction.inputs[0].extract_address().unwrap();
 
-        assert_eq!(&extr_address, &address);
+        assert_eq!(extr_address, address);
     }
 
     #[test]
-    fn extract_witness_address_p2wsh() {
+    fn extract_nested_witness_address_with_multiple_inputs() {
         let raw_tx = "020000000001027113554199c88273f7f04d18a0dca69145ea863f31519a790b346579b9b55f090100000017160014d6ad6711da30f4349a0d8c387a515bff10ecd507fdffffff90a9eb7550a8308c629014f3f685d2d72e9e7de6bd199c3a9615b567206889430100000017160014cce6d8dffda77f56e237389f48417f10659c2e42fdffffff0228641c000000000017a914d980c4240e77b76d48051c791f68831d23ad3e8687400d03000000000017a914e9c3dd0c07aac76179ebc76a6c78d4d67c6c160a870248304502210088b0fb4b40af9620f785f265c2e2f7436018391d9db34eee3bc1ebd796fbce96022015151182eaa595e090c8030d9f979b920aae276c385dfc66ac2d77160a27453b01210266dd88be116711227e2e953daa008cca45ce5cc0aa4b584c20ae6ddb9ce0212d0247304402204b2fdd767ab93b30a43042c3287ae78d06d1418084fe88350b0aaf06bebe02fe02202d850fc5887d948307fdade871de3714d867610643f6507e511d14dad86fe3ce012102593012612326b4c07e6f0234bac5ff62b5ed12afe77e2900474ca36b3bfa528075f50700";
         let tx_bytes = hex::decode(&raw_tx).unwrap();
         let transaction = parse_transaction(&tx_bytes).unwrap();
 
-        let address: [u8; 20] = [
-            214, 173, 103, 17, 218, 48, 244, 52, 154, 13, 140, 56, 122, 81, 91, 255, 16, 236, 213,
-            7,
-        ];
+        // hash160 of the witness program 0x0014d6ad6711da30f4349a0d8c387a515bff10ecd507
+        let address = BtcAddress::P2SH(H160::from([
+            26, 213, 153, 191, 208, 237, 65, 126, 50, 238, 133, 163, 77, 172, 220, 49, 89, 116, 75,
+            4,
+        ]));
 
         let extr_address = transaction.inputs[0].extract_address().unwrap();
 
-        assert_eq!(&extr_address, &address);
+        assert_eq!(extr_address, address);
     }
 }
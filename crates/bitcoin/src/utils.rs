@@ -94,6 +94,40 @@ pub fn sha256d_le(bytes: &[u8]) -> H256Le {
     H256Le::from_bytes_le(&sha256d(bytes))
 }
 
+/// Encodes a target into Bitcoin's compact "nBits" representation,
+/// truncating the mantissa to three bytes as done by Bitcoin Core
+///
+/// # Arguments
+/// * `target` - the target to encode
+pub fn target_to_compact(target: &U256) -> u32 {
+    let mut size = (target.bits() + 7) / 8;
+    let mut compact = if size <= 3 {
+        target.low_u64() << (8 * (3 - size))
+    } else {
+        (*target >> (8 * (size - 3))).low_u64()
+    };
+    // the mantissa is signed, so avoid setting the sign bit
+    if compact & 0x0080_0000 != 0 {
+        compact >>= 8;
+        size += 1;
+    }
+    (compact as u32) | (size as u32) << 24
+}
+
+/// Decodes a target from Bitcoin's compact "nBits" representation
+///
+/// # Arguments
+/// * `compact` - the compact encoded target
+pub fn compact_to_target(compact: u32) -> U256 {
+    let size = compact >> 24;
+    let mantissa = U256::from(compact & 0x007f_ffff);
+    if size <= 3 {
+        mantissa >> (8 * (3 - size) as usize)
+    } else {
+        mantissa << (8 * (size - 3) as usize)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,4 +139,21 @@ mod tests {
         let result = log256(&value);
         assert_eq!(result, 24);
     }
+
+    #[test]
+    fn test_target_compact_roundtrip() {
+        let target = U256::from(0xffff) << 208;
+        assert_eq!(target_to_compact(&target), 0x1d00ffff);
+        assert_eq!(compact_to_target(0x1d00ffff), target);
+
+        // sign bit of the mantissa is never set
+        let target = U256::from(0x80) << 200;
+        assert_eq!(target_to_compact(&target), 0x1b008000);
+        assert_eq!(compact_to_target(0x1b008000), target);
+
+        // mantissa is truncated to three bytes
+        let target = U256::from(0x1234_5678);
+        assert_eq!(target_to_compact(&target), 0x04123456);
+        assert_eq!(compact_to_target(0x04123456), U256::from(0x1234_5600));
+    }
 }
@@ -0,0 +1,29 @@
+//! Runtime API definition for the BTC-Relay module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use bitcoin::types::{BlockChain, H256Le, RichBlockHeader};
+use sp_std::prelude::*;
+use x_core::Error;
+
+sp_api::decl_runtime_apis! {
+    pub trait BtcRelayApi {
+        /// Get the hash of the best block
+        fn get_best_block() -> H256Le;
+
+        /// Get the height of the best block
+        fn get_best_block_height() -> u32;
+
+        /// Get a block header from its hash, on any chain
+        fn get_block_header(block_hash: H256Le) -> Option<RichBlockHeader>;
+
+        /// Get the block header at the given height of the main chain
+        fn get_block_header_at_height(block_height: u32) -> Option<RichBlockHeader>;
+
+        /// Get all tracked chains, starting with the main chain
+        fn get_forks() -> Result<Vec<BlockChain>, Error>;
+
+        /// Get the number of confirmations of the main chain block at the given height
+        fn get_confirmations(block_height: u32) -> Option<u32>;
+    }
+}
@@ -0,0 +1,140 @@
+//! RPC interface for the BTC-Relay module.
+
+use bitcoin::types::{BlockChain, H256Le, RichBlockHeader};
+pub use btc_relay_rpc_runtime_api::BtcRelayApi as BtcRelayRuntimeApi;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::sync::Arc;
+
+#[rpc]
+pub trait BtcRelayApi<BlockHash> {
+    #[rpc(name = "btcRelay_getBestBlock")]
+    fn get_best_block(&self, at: Option<BlockHash>) -> Result<H256Le>;
+
+    #[rpc(name = "btcRelay_getBestBlockHeight")]
+    fn get_best_block_height(&self, at: Option<BlockHash>) -> Result<u32>;
+
+    #[rpc(name = "btcRelay_getBlockHeader")]
+    fn get_block_header(
+        &self,
+        block_hash: H256Le,
+        at: Option<BlockHash>,
+    ) -> Result<Option<RichBlockHeader>>;
+
+    #[rpc(name = "btcRelay_getBlockHeaderAtHeight")]
+    fn get_block_header_at_height(
+        &self,
+        block_height: u32,
+        at: Option<BlockHash>,
+    ) -> Result<Option<RichBlockHeader>>;
+
+    #[rpc(name = "btcRelay_getForks")]
+    fn get_forks(&self, at: Option<BlockHash>) -> Result<Vec<BlockChain>>;
+
+    #[rpc(name = "btcRelay_getConfirmations")]
+    fn get_confirmations(&self, block_height: u32, at: Option<BlockHash>) -> Result<Option<u32>>;
+}
+
+/// A struct that implements the [`BtcRelayApi`].
+pub struct BtcRelay<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> BtcRelay<C, B> {
+    /// Create new `BtcRelay` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        BtcRelay {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+/// Error code returned when the runtime API call fails.
+const RUNTIME_ERROR: i64 = 1;
+/// Error code returned when the BTC-Relay storage cannot be read.
+const STORAGE_ERROR: i64 = 2;
+
+fn runtime_error<E: std::fmt::Debug>(message: &str, error: E) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(RUNTIME_ERROR),
+        message: message.into(),
+        data: Some(format!("{:?}", error).into()),
+    }
+}
+
+impl<C, Block> BtcRelay<C, Block>
+where
+    Block: BlockT,
+    C: HeaderBackend<Block>,
+{
+    /// Returns the given block or, if not specified, the best block
+    fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId<Block> {
+        // if the block hash is not supplied assume the best block
+        BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash))
+    }
+}
+
+impl<C, Block> BtcRelayApi<<Block as BlockT>::Hash> for BtcRelay<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: BtcRelayRuntimeApi<Block>,
+{
+    fn get_best_block(&self, at: Option<<Block as BlockT>::Hash>) -> Result<H256Le> {
+        let api = self.client.runtime_api();
+        api.get_best_block(&self.block_id(at))
+            .map_err(|e| runtime_error("Unable to query the best block.", e))
+    }
+
+    fn get_best_block_height(&self, at: Option<<Block as BlockT>::Hash>) -> Result<u32> {
+        let api = self.client.runtime_api();
+        api.get_best_block_height(&self.block_id(at))
+            .map_err(|e| runtime_error("Unable to query the best block height.", e))
+    }
+
+    fn get_block_header(
+        &self,
+        block_hash: H256Le,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<RichBlockHeader>> {
+        let api = self.client.runtime_api();
+        api.get_block_header(&self.block_id(at), block_hash)
+            .map_err(|e| runtime_error("Unable to query the block header.", e))
+    }
+
+    fn get_block_header_at_height(
+        &self,
+        block_height: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<RichBlockHeader>> {
+        let api = self.client.runtime_api();
+        api.get_block_header_at_height(&self.block_id(at), block_height)
+            .map_err(|e| runtime_error("Unable to query the block header.", e))
+    }
+
+    fn get_forks(&self, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<BlockChain>> {
+        let api = self.client.runtime_api();
+        api.get_forks(&self.block_id(at))
+            .map_err(|e| runtime_error("Unable to query the forks.", e))?
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(STORAGE_ERROR),
+                message: e.message().into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn get_confirmations(
+        &self,
+        block_height: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<u32>> {
+        let api = self.client.runtime_api();
+        api.get_confirmations(&self.block_id(at), block_height)
+            .map_err(|e| runtime_error("Unable to query the confirmations.", e))
+    }
+}
@@ -22,21 +22,25 @@ use mocktopus::macros::mockable;
 
 use frame_support::debug;
 use frame_support::{
-    decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure, IterableStorageMap,
+    decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure, weights::Weight,
+    IterableStorageMap,
 };
 use primitive_types::U256;
-use sp_core::H160;
 use sp_std::collections::btree_set::BTreeSet;
+use sp_std::convert::TryInto;
 use sp_std::prelude::*;
 use system::ensure_signed;
 
 // Crates
 pub use bitcoin;
+use bitcoin::address::BtcAddress;
 use bitcoin::merkle::{MerkleProof, ProofResult};
+use bitcoin::network::{Network, NetworkParams};
 use bitcoin::parser::{parse_block_header, parse_transaction};
 use bitcoin::types::{
     BlockChain, BlockHeader, H256Le, RawBlockHeader, RichBlockHeader, Transaction,
 };
+use bitcoin::utils::{compact_to_target, sha256d_le, target_to_compact};
 use security::types::ErrorCode;
 use x_core::{Error, UnitResult};
 
@@ -44,31 +48,42 @@ use x_core::{Error, UnitResult};
 /// The pallet's configuration trait.
 /// For further reference, see:
 /// https://interlay.gitlab.io/polkabtc-spec/btcrelay-spec/spec/data-model.html
-pub trait Trait: system::Trait + security::Trait {
+pub trait Trait: system::Trait + security::Trait + timestamp::Trait {
     /// The overarching event type.
     type Event: From<Event> + Into<<Self as system::Trait>::Event>;
 }
 
-/// Difficulty Adjustment Interval
-pub const DIFFICULTY_ADJUSTMENT_INTERVAL: u32 = 2016;
-
-/// Target Timespan
-pub const TARGET_TIMESPAN: u32 = 1_209_600;
-
 // Used in Bitcoin's retarget algorithm
 pub const TARGET_TIMESPAN_DIVISOR: u32 = 4;
 
 // Accepted minimum number of transaction outputs for validation
 pub const ACCEPTED_MIN_TRANSACTION_OUTPUTS: u32 = 2;
 
-/// Unrounded Maximum Target
-/// 0x00000000FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF
-pub const UNROUNDED_MAX_TARGET: U256 = U256([
-    <u64>::max_value(),
-    <u64>::max_value(),
-    <u64>::max_value(),
-    0x0000_0000_ffff_ffffu64,
-]);
+/// Number of previous blocks used to compute the median time past
+pub const MEDIAN_TIME_SPAN: u32 = 11;
+
+/// Maximum time (in seconds) a block timestamp may be ahead of the
+/// parachain time
+pub const MAX_FUTURE_BLOCK_TIME: u64 = 2 * 60 * 60;
+
+/// Maximum depth of the merkle tree of request ids committed to by a
+/// transaction paying several requests
+pub const MAX_BATCH_COMMITMENT_DEPTH: usize = 16;
+
+/// Prefix of the hashed leaves of a batch commitment
+pub const BATCH_COMMITMENT_LEAF_PREFIX: u8 = 0x00;
+
+/// Prefix of the hashed nodes of a batch commitment
+pub const BATCH_COMMITMENT_NODE_PREFIX: u8 = 0x01;
+
+/// Maximum number of block headers submitted in a single batch
+pub const MAX_HEADER_BATCH_SIZE: usize = 100;
+
+/// Weight of storing a single block header
+pub const STORE_BLOCK_HEADER_WEIGHT: Weight = 1000;
+
+/// Weight of removing a single block header while pruning
+pub const PRUNE_BLOCK_HEADER_WEIGHT: Weight = 1000;
 
 /// Main chain id
 pub const MAIN_CHAIN_ID: u32 = 0;
@@ -77,6 +92,16 @@ pub const MAIN_CHAIN_ID: u32 = 0;
 /// See: https://interlay.gitlab.io/polkabtc-spec/btcrelay-spec/intro/accepted-format.html
 pub const ACCEPTED_NO_TRANSACTION_OUTPUTS: u32 = 2;
 
+/// A fork selected for pruning together with the block headers to remove
+struct StaleFork {
+    /// The fork with its updated max height and chain work
+    fork: BlockChain,
+    /// Heights and hashes of the block headers to remove
+    headers: Vec<(u32, H256Le)>,
+    /// Whether all block headers of the fork are removed
+    removed: bool,
+}
+
 // This pallet's storage items.
 decl_storage! {
     trait Store for Module<T: Trait> as BTCRelay {
@@ -104,6 +129,16 @@ decl_storage! {
 
         /// Global security parameter k for stable transactions
         StableTransactionConfirmations get(fn confirmations) config(): u32;
+
+        /// Bitcoin network whose consensus rules are used to verify headers
+        BitcoinNetwork get(fn bitcoin_network) config(): Network;
+
+        /// Number of main chain block headers below the best block which are
+        /// kept in storage, pruning is disabled if set to zero
+        PruningDepth get(fn pruning_depth) config(): u32;
+
+        /// Maximum number of block headers pruned per parachain block
+        MaxPrunedPerBlock get(fn max_pruned_per_block) config(): u32;
     }
 }
 
@@ -137,15 +172,20 @@ decl_module! {
             let basic_block_header = parse_block_header(&raw_block_header)?;
             let block_header_hash = raw_block_header.hash();
 
+            // The chain work is counted from the initial block header
+            // since all forks branch off the main chain after that block
+            let chain_work = basic_block_header.work();
+
             // construct the BlockChain struct
             let blockchain = Self::initialize_blockchain(
-                    block_height, block_header_hash);
+                    block_height, block_header_hash, chain_work);
             // Create rich block header
             let block_header = RichBlockHeader {
                 block_hash: block_header_hash,
                 block_header: basic_block_header,
                 block_height: block_height,
-                chain_ref: blockchain.chain_id
+                chain_ref: blockchain.chain_id,
+                chain_work,
             };
 
             // Store a new BlockHeader struct in BlockHeaders
@@ -184,101 +224,49 @@ decl_module! {
             // Make sure Parachain is not shutdown
             ext::security::ensure_parachain_status_not_shutdown::<T>()?;
 
-            // Parse the block header bytes to extract the required info
-            let basic_block_header = Self::verify_block_header(&raw_block_header)?;
-            let block_header_hash = raw_block_header.hash();
-
-            let prev_header = Self::get_block_header_from_hash(
-                basic_block_header.hash_prev_block
-            )?;
-
-            // get the block chain of the previous header
-            let prev_blockchain = Self::get_block_chain_from_id(
-                prev_header.chain_ref
-            )?;
-
-            // Update the current block header
-            // check if the prev block is the highest block in the chain
-            // load the previous block header block height
-            let prev_block_height = prev_header.block_height;
-
-            // update the current block header with height and chain ref
-            // Set the height of the block header
-            let current_block_height = prev_block_height + 1;
-
-            // Update the blockchain
-            // check if we create a new blockchain or extend the existing one
-            debug::print!("Prev max height: {:?}\n", prev_blockchain.max_height);
-            debug::print!("Prev block height: {:?}\n", prev_block_height);
-            let is_fork = prev_blockchain.max_height != prev_block_height;
-            debug::print!("Fork detected: {:?}\n", is_fork);
-
-            let blockchain = if is_fork {
-                // create new blockchain element
-                Self::create_blockchain(current_block_height, block_header_hash)
-            } else {
-                // extend the current chain
-                Self::extend_blockchain(
-                    current_block_height, &block_header_hash, prev_blockchain)?
-            };
-
-            // Create rich block header
-            let block_header = RichBlockHeader {
-                block_hash: block_header_hash,
-                block_header: basic_block_header,
-                block_height: current_block_height,
-                chain_ref: blockchain.chain_id
-            };
-
+            Self::_store_block_header(raw_block_header)?;
+            Ok(())
+        }
 
-            // Store a new BlockHeader struct in BlockHeaders
-            Self::set_block_header_from_hash(block_header_hash, &block_header);
+        /// Stores a contiguous run of new block headers, where each header
+        /// must reference the previous one in the batch. Processing stops at
+        /// the first header that fails verification, in which case a
+        /// `StoreBlockHeadersFailed` event with the index of the rejected
+        /// header is emitted. Headers before that index remain stored: this
+        /// partial application is intended, as each of them was fully
+        /// verified on its own, and lets relayers resubmit the batch starting
+        /// from the rejected header.
+        ///
+        /// # Arguments
+        ///
+        /// * `raw_block_headers` - 80 byte raw Bitcoin block headers, ordered
+        /// by ascending block height. At most `MAX_HEADER_BATCH_SIZE` headers
+        /// are accepted per batch.
+        #[weight = STORE_BLOCK_HEADER_WEIGHT.saturating_mul(raw_block_headers.len() as Weight)]
+        fn store_block_headers(
+            origin, raw_block_headers: Vec<RawBlockHeader>
+        ) -> DispatchResult {
+            let _ = ensure_signed(origin)?;
+            // Make sure Parachain is not shutdown
+            ext::security::ensure_parachain_status_not_shutdown::<T>()?;
 
-            // Storing the blockchain depends if we extend or create a new chain
-            if is_fork {
-                // create a new chain
-                // Store a pointer to BlockChain in ChainsIndex
-                Self::set_block_chain_from_id(blockchain.chain_id, &blockchain);
-                // Store the reference to the blockchain in Chains
-                Self::insert_sorted(&blockchain)?;
-            } else {
-                // extended the chain
-                // Update the pointer to BlockChain in ChainsIndex
-                <ChainsIndex>::mutate(blockchain.chain_id, |_b| &blockchain);
+            ensure!(
+                !raw_block_headers.is_empty() && raw_block_headers.len() <= MAX_HEADER_BATCH_SIZE,
+                Error::InvalidHeaderBatch
+            );
 
-                // check if ordering of Chains needs updating
-                Self::check_and_do_reorg(&blockchain)?;
+            let mut prev_block_hash: Option<H256Le> = None;
+            for (index, raw_block_header) in raw_block_headers.into_iter().enumerate() {
+                let result = Self::ensure_header_follows(&raw_block_header, prev_block_hash)
+                    .and_then(|_| Self::_store_block_header(raw_block_header));
 
-                if blockchain.chain_id == MAIN_CHAIN_ID {
-                    Self::set_best_block(block_header_hash);
-                    Self::set_best_block_height(current_block_height)
+                // the headers stored so far are kept, see above
+                if let Err(err) = result {
+                    Self::deposit_event(Event::StoreBlockHeadersFailed(index as u32));
+                    return Err(err.into());
                 }
-            };
-
-            // Determine if this block extends the main chain or a fork
-            let current_best_block = Self::get_best_block();
-
-            // print!("Best block hash: {:?} \n", current_best_block);
-            // print!("Current block hash: {:?} \n", block_header_hash);
-            if current_best_block == block_header_hash {
-                // extends the main chain
-                Self::deposit_event(
-                    Event::StoreMainChainHeader(
-                        current_block_height,
-                        block_header_hash
-                    )
-                );
-            } else {
-            // created a new fork or updated an existing one
-                Self::deposit_event(
-                    Event::StoreForkHeader(
-                        blockchain.chain_id,
-                        current_block_height,
-                        block_header_hash
-                    )
-                );
-            };
-
+                prev_block_hash = Some(raw_block_header.hash());
+            }
 
             Ok(())
         }
@@ -298,10 +286,10 @@ decl_module! {
         /// the proof
         /// * `insecure` - determines if checks against recommended global transaction confirmation are to be executed. Recommended: set to `true`
         /// * `raw_tx` - raw Bitcoin transaction
-        /// * `paymentValue` - value of BTC sent in the 1st /
-        /// payment UTXO of the transaction
-        /// * `recipientBtcAddress` - 20 byte Bitcoin address of recipient
-        /// of the BTC in the 1st  / payment UTXO
+        /// * `paymentValue` - value of BTC sent in the payment UTXO of
+        /// the transaction, which may be at any position
+        /// * `recipientBtcAddress` - Bitcoin address (script type and hash) of
+        /// the recipient of the BTC in the payment UTXO
         /// * `op_return_id` - 32 byte hash identifier expected in
         /// OP_RETURN (replay protection)
         #[weight = 1000]
@@ -314,7 +302,7 @@ decl_module! {
             insecure: bool,
             raw_tx: Vec<u8>,
             payment_value: i64,
-            recipient_btc_address: Vec<u8>,
+            recipient_btc_address: BtcAddress,
             op_return_id: Vec<u8>)
         -> DispatchResult {
             let _ = ensure_signed(origin)?;
@@ -373,10 +361,10 @@ decl_module! {
         ///
         /// # Arguments
         /// * `raw_tx` - raw Bitcoin transaction
-        /// * `paymentValue` - value of BTC sent in the 1st /
-        /// payment UTXO of the transaction
-        /// * `recipientBtcAddress` - 20 byte Bitcoin address of recipient
-        /// of the BTC in the 1st  / payment UTXO
+        /// * `paymentValue` - value of BTC sent in the payment UTXO of
+        /// the transaction, which may be at any position
+        /// * `recipientBtcAddress` - Bitcoin address (script type and hash) of
+        /// the recipient of the BTC in the payment UTXO
         /// * `op_return_id` - 32 byte hash identifier expected in
         /// OP_RETURN (replay protection)
         #[weight = 1000]
@@ -384,13 +372,28 @@ decl_module! {
             origin,
             raw_tx: Vec<u8>,
             payment_value: i64,
-            recipient_btc_address: Vec<u8>,
+            recipient_btc_address: BtcAddress,
             op_return_id: Vec<u8>
         ) -> DispatchResult {
             let _ = ensure_signed(origin)?;
             Self::_validate_transaction(raw_tx, payment_value, recipient_btc_address, op_return_id)?;
             Ok(())
         }
+
+        // Pruning runs here rather than in `on_finalize`: only `on_initialize` can
+        // report the weight it consumed, which is needed to charge the (capped)
+        // number of pruned headers against the block weight.
+        fn on_initialize(_n: T::BlockNumber) -> Weight {
+            match Self::prune_block_headers() {
+                Ok(pruned) => PRUNE_BLOCK_HEADER_WEIGHT.saturating_mul(pruned as Weight),
+                Err(_) => {
+                    Self::deposit_event(Event::PruneBlockHeadersFailed(
+                        Self::get_best_block_height(),
+                    ));
+                    0
+                }
+            }
+        }
     }
 }
 
@@ -429,12 +432,17 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Checks that the transaction pays at least `payment_value` to the
+    /// recipient and contains the expected OP_RETURN id. The payment and
+    /// OP_RETURN outputs may be at any position, but each must be unique.
+    /// Returns the value of the payment UTXO, which may exceed
+    /// `payment_value`.
     pub fn _validate_transaction(
         raw_tx: Vec<u8>,
         payment_value: i64,
-        recipient_btc_address: Vec<u8>,
+        recipient_btc_address: BtcAddress,
         op_return_id: Vec<u8>,
-    ) -> Result<(), Error> {
+    ) -> Result<i64, Error> {
         let transaction = Self::parse_transaction(&raw_tx)?;
 
         ensure!(
@@ -442,32 +450,282 @@ impl<T: Trait> Module<T> {
             Error::MalformedTransaction
         );
 
-        // Check if 1st / payment UTXO transfers sufficient value
-        let extr_payment_value = transaction.outputs[0].value;
+        // Check if the payment UTXO transfers sufficient value to the
+        // correct address, comparing both the script type and the hash
+        let extr_payment_value = Self::find_payment_output(&transaction, recipient_btc_address)?;
         ensure!(
             extr_payment_value >= payment_value,
             Error::InsufficientValue
         );
 
-        // Check if 1st / payment UTXO sends to correct address
-        let extr_recipient_address = transaction.outputs[0].extract_address()?;
+        // Check if the data UTXO has correct OP_RETURN value
+        let extr_op_return_value = Self::extract_op_return_data(&transaction)?;
+        ensure!(extr_op_return_value == op_return_id, Error::InvalidOpreturn);
+
+        Ok(extr_payment_value)
+    }
+
+    /// Returns the value of the only output of the transaction paying the
+    /// recipient, failing if no or several outputs pay the recipient.
+    fn find_payment_output(
+        transaction: &Transaction,
+        recipient_btc_address: BtcAddress,
+    ) -> Result<i64, Error> {
+        let mut payment_outputs = transaction.outputs.iter().filter(|output| {
+            output
+                .extract_address()
+                .map_or(false, |address| address == recipient_btc_address)
+        });
+        let payment_output = payment_outputs.next().ok_or(Error::WrongRecipient)?;
         ensure!(
-            extr_recipient_address == recipient_btc_address,
+            payment_outputs.next().is_none(),
+            Error::AmbiguousTransactionOutputs
+        );
+        Ok(payment_output.value)
+    }
+
+    /// Returns the data of the only OP_RETURN output of the transaction,
+    /// failing if it contains no or several OP_RETURN outputs.
+    fn extract_op_return_data(transaction: &Transaction) -> Result<Vec<u8>, Error> {
+        let mut op_return_outputs = transaction
+            .outputs
+            .iter()
+            .filter(|output| output.script.is_op_return());
+        let op_return_output = op_return_outputs.next().ok_or(Error::NotOpReturn)?;
+        ensure!(
+            op_return_outputs.next().is_none(),
+            Error::AmbiguousTransactionOutputs
+        );
+        op_return_output.script.extract_op_return_data()
+    }
+
+    /// Checks that the output at `output_index` of a transaction paying
+    /// several requests at once pays at least `payment_value` to the
+    /// recipient, and that the OP_RETURN output commits to `op_return_id`.
+    /// Returns the value of the payment output, which may exceed
+    /// `payment_value`.
+    ///
+    /// The OP_RETURN output contains the depth of the merkle tree of the ids
+    /// of all requests paid by the transaction as a single byte, followed by
+    /// its root. The id at leaf `output_index` is paid by the output at the
+    /// same index, and all leaves are at the committed depth. Leaves are hashed
+    /// as `sha256d(0x00 || id)` and nodes as `sha256d(0x01 || left || right)`,
+    /// so that neither a node nor a path of another depth can be passed off
+    /// as a leaf. A transaction paying a single request commits to a tree of
+    /// depth 0, with an empty `commitment_path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_tx` - raw Bitcoin transaction
+    /// * `output_index` - index of the payment UTXO and of the id in the merkle tree
+    /// * `payment_value` - value of BTC sent in the payment UTXO
+    /// * `recipient_btc_address` - Bitcoin address (script type and hash) of
+    /// the recipient of the BTC in the payment UTXO
+    /// * `op_return_id` - 32 byte hash identifier of the paid request
+    /// * `commitment_path` - sibling hashes from the id up to the merkle root
+    pub fn _validate_batch_transaction(
+        raw_tx: Vec<u8>,
+        output_index: u32,
+        payment_value: i64,
+        recipient_btc_address: BtcAddress,
+        op_return_id: Vec<u8>,
+        commitment_path: Vec<H256Le>,
+    ) -> Result<i64, Error> {
+        let commitment =
+            Self::compute_batch_commitment(op_return_id, output_index, &commitment_path)?;
+        let transaction = Self::parse_transaction(&raw_tx)?;
+
+        let payment_output = transaction
+            .outputs
+            .get(output_index as usize)
+            .ok_or(Error::MalformedTransaction)?;
+        ensure!(
+            payment_output.value >= payment_value,
+            Error::InsufficientValue
+        );
+        ensure!(
+            payment_output.extract_address()? == recipient_btc_address,
             Error::WrongRecipient
         );
 
-        // Check if 2nd / data UTXO has correct OP_RETURN value
-        let extr_op_return_value = transaction.outputs[1].script.extract_op_return_data()?;
-        ensure!(extr_op_return_value == op_return_id, Error::InvalidOpreturn);
+        let extr_op_return_value = Self::extract_op_return_data(&transaction)?;
+        ensure!(extr_op_return_value == commitment, Error::InvalidOpreturn);
+
+        Ok(payment_output.value)
+    }
+
+    /// Computes the batch commitment, i.e. the depth of the path followed by
+    /// the merkle root, from the id at leaf `index` and the sibling hashes on
+    /// the path to the root. The index must fit into the depth of the path,
+    /// so each leaf has exactly one valid index.
+    fn compute_batch_commitment(
+        id: Vec<u8>,
+        index: u32,
+        path: &[H256Le],
+    ) -> Result<Vec<u8>, Error> {
+        ensure!(
+            path.len() <= MAX_BATCH_COMMITMENT_DEPTH && (index as u64) >> path.len() == 0,
+            Error::InvalidBatchCommitment
+        );
+
+        let mut node = Self::batch_commitment_leaf(&id);
+        for (level, sibling) in path.iter().enumerate() {
+            node = if (index >> level) & 1 == 0 {
+                Self::batch_commitment_node(&node, sibling)
+            } else {
+                Self::batch_commitment_node(sibling, &node)
+            };
+        }
+
+        let mut commitment = vec![path.len() as u8];
+        commitment.extend_from_slice(&node.to_bytes_le());
+        Ok(commitment)
+    }
+
+    /// Hashes a request id into a leaf of a batch commitment
+    fn batch_commitment_leaf(id: &[u8]) -> H256Le {
+        let mut bytes = vec![BATCH_COMMITMENT_LEAF_PREFIX];
+        bytes.extend_from_slice(id);
+        sha256d_le(&bytes)
+    }
+
+    /// Hashes two children into a node of a batch commitment
+    fn batch_commitment_node(left: &H256Le, right: &H256Le) -> H256Le {
+        let mut bytes = vec![BATCH_COMMITMENT_NODE_PREFIX];
+        bytes.extend_from_slice(&left.to_bytes_le());
+        bytes.extend_from_slice(&right.to_bytes_le());
+        sha256d_le(&bytes)
+    }
+
+    /// Verifies and stores a single block header, extending an existing
+    /// chain or creating a new fork and reorganizing Chains if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_block_header` - 80 byte raw Bitcoin block header.
+    fn _store_block_header(raw_block_header: RawBlockHeader) -> Result<(), Error> {
+        // Parse the block header bytes to extract the required info
+        let basic_block_header = Self::verify_block_header(&raw_block_header)?;
+        let block_header_hash = raw_block_header.hash();
+
+        let prev_header = Self::get_block_header_from_hash(basic_block_header.hash_prev_block)?;
+
+        // get the block chain of the previous header
+        let prev_blockchain = Self::get_block_chain_from_id(prev_header.chain_ref)?;
+
+        // Update the current block header
+        // check if the prev block is the highest block in the chain
+        // load the previous block header block height
+        let prev_block_height = prev_header.block_height;
+
+        // update the current block header with height and chain ref
+        // Set the height of the block header
+        let current_block_height = prev_block_height + 1;
+
+        // accumulate the work of the chain up to the current block header
+        let chain_work = prev_header
+            .chain_work
+            .saturating_add(basic_block_header.work());
+
+        // Update the blockchain
+        // check if we create a new blockchain or extend the existing one
+        let is_fork = prev_blockchain.max_height != prev_block_height;
+
+        let blockchain = if is_fork {
+            // create new blockchain element
+            Self::create_blockchain(current_block_height, block_header_hash, chain_work)
+        } else {
+            // extend the current chain
+            Self::extend_blockchain(
+                current_block_height,
+                &block_header_hash,
+                chain_work,
+                prev_blockchain,
+            )?
+        };
+
+        // Create rich block header
+        let block_header = RichBlockHeader {
+            block_hash: block_header_hash,
+            block_header: basic_block_header,
+            block_height: current_block_height,
+            chain_ref: blockchain.chain_id,
+            chain_work,
+        };
+
+        // Store a new BlockHeader struct in BlockHeaders
+        Self::set_block_header_from_hash(block_header_hash, &block_header);
+
+        // Storing the blockchain depends if we extend or create a new chain
+        if is_fork {
+            // create a new chain
+            // Store a pointer to BlockChain in ChainsIndex
+            Self::set_block_chain_from_id(blockchain.chain_id, &blockchain);
+            // Store the reference to the blockchain in Chains
+            Self::insert_sorted(&blockchain)?;
+        } else {
+            // extended the chain
+            // Update the pointer to BlockChain in ChainsIndex
+            <ChainsIndex>::mutate(blockchain.chain_id, |_b| &blockchain);
+
+            // check if ordering of Chains needs updating
+            Self::check_and_do_reorg(&blockchain)?;
+
+            if blockchain.chain_id == MAIN_CHAIN_ID {
+                Self::set_best_block(block_header_hash);
+                Self::set_best_block_height(current_block_height)
+            }
+        };
+
+        // Determine if this block extends the main chain or a fork
+        let current_best_block = Self::get_best_block();
+
+        // print!("Best block hash: {:?} \n", current_best_block);
+        // print!("Current block hash: {:?} \n", block_header_hash);
+        if current_best_block == block_header_hash {
+            // extends the main chain
+            Self::deposit_event(Event::StoreMainChainHeader(
+                current_block_height,
+                block_header_hash,
+            ));
+        } else {
+            // created a new fork or updated an existing one
+            Self::deposit_event(Event::StoreForkHeader(
+                blockchain.chain_id,
+                current_block_height,
+                block_header_hash,
+            ));
+        };
 
         Ok(())
     }
 
+    /// Checks that a header of a batch references the header preceding it
+    /// in the batch, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_block_header` - 80 byte raw Bitcoin block header.
+    /// * `prev_block_hash` - hash of the preceding header in the batch
+    fn ensure_header_follows(
+        raw_block_header: &RawBlockHeader,
+        prev_block_hash: Option<H256Le>,
+    ) -> UnitResult {
+        if let Some(prev_block_hash) = prev_block_hash {
+            let basic_block_header = parse_block_header(raw_block_header)?;
+            ensure!(
+                basic_block_header.hash_prev_block == prev_block_hash,
+                Error::InvalidHeaderBatch
+            );
+        }
+        Ok(())
+    }
+
     // ********************************
     // START: Storage getter functions
     // ********************************
 
-    /// Get chain id from position (sorted by max block height)
+    /// Get chain id from position (sorted by chain work)
     fn get_chain_id_from_position(position: u32) -> Result<u32, Error> {
         <Chains>::get(position).ok_or(Error::InvalidChainID)
     }
@@ -513,7 +771,7 @@ impl<T: Trait> Module<T> {
     }
 
     /// Get a block header from its hash
-    fn get_block_header_from_hash(block_hash: H256Le) -> Result<RichBlockHeader, Error> {
+    pub fn get_block_header_from_hash(block_hash: H256Le) -> Result<RichBlockHeader, Error> {
         if <BlockHeaders>::contains_key(block_hash) {
             return Ok(<BlockHeaders>::get(block_hash));
         }
@@ -531,6 +789,29 @@ impl<T: Trait> Module<T> {
         let block_hash = Self::get_block_hash(blockchain.chain_id, block_height)?;
         Self::get_block_header_from_hash(block_hash)
     }
+    /// Get the main chain block header at the given height
+    pub fn get_main_chain_block_header(block_height: u32) -> Result<RichBlockHeader, Error> {
+        let main_chain = Self::get_block_chain_from_id(MAIN_CHAIN_ID)?;
+        Self::get_block_header_from_height(&main_chain, block_height)
+    }
+    /// Get all blockchains in the order of Chains, starting with the main chain
+    pub fn get_chains() -> Result<Vec<BlockChain>, Error> {
+        let mut chains = <Chains>::iter().collect::<Vec<(u32, u32)>>();
+        chains.sort_by_key(|k| k.0);
+        chains
+            .into_iter()
+            .map(|(_, chain_id)| Self::get_block_chain_from_id(chain_id))
+            .collect()
+    }
+    /// Get the number of confirmations of the main chain block at the given
+    /// height, counted the same way as in `check_confirmations`
+    pub fn get_block_confirmations(block_height: u32) -> Result<u32, Error> {
+        let best_block_height = Self::get_best_block_height();
+        if block_height > best_block_height || !Self::block_exists(MAIN_CHAIN_ID, block_height) {
+            return Err(Error::MissingBlockHeight);
+        }
+        Ok(best_block_height - block_height)
+    }
     /// Storage setter functions
     /// Set a new chain with position and id
     fn set_chain_from_position_and_id(position: u32, id: u32) {
@@ -589,22 +870,31 @@ impl<T: Trait> Module<T> {
         new_counter
     }
     /// Initialize the new main blockchain with a single block
-    fn initialize_blockchain(block_height: u32, block_hash: H256Le) -> BlockChain {
+    fn initialize_blockchain(
+        block_height: u32,
+        block_hash: H256Le,
+        chain_work: U256,
+    ) -> BlockChain {
         let chain_id = MAIN_CHAIN_ID;
 
         // generate an empty blockchain
-        Self::generate_blockchain(chain_id, block_height, block_hash)
+        Self::generate_blockchain(chain_id, block_height, block_hash, chain_work)
     }
     /// Create a new blockchain element with a new chain id
-    fn create_blockchain(block_height: u32, block_hash: H256Le) -> BlockChain {
+    fn create_blockchain(block_height: u32, block_hash: H256Le, chain_work: U256) -> BlockChain {
         // get a new chain id
         let chain_id: u32 = Self::increment_chain_counter();
 
         // generate an empty blockchain
-        Self::generate_blockchain(chain_id, block_height, block_hash)
+        Self::generate_blockchain(chain_id, block_height, block_hash, chain_work)
     }
     /// Generate the raw blockchain from a chain Id and with a single block
-    fn generate_blockchain(chain_id: u32, block_height: u32, block_hash: H256Le) -> BlockChain {
+    fn generate_blockchain(
+        chain_id: u32,
+        block_height: u32,
+        block_hash: H256Le,
+        chain_work: U256,
+    ) -> BlockChain {
         // initialize an empty chain
 
         Self::insert_block_hash(chain_id, block_height, block_hash);
@@ -613,6 +903,7 @@ impl<T: Trait> Module<T> {
             chain_id,
             start_height: block_height,
             max_height: block_height,
+            chain_work,
             no_data: BTreeSet::new(),
             invalid: BTreeSet::new(),
         }
@@ -638,6 +929,7 @@ impl<T: Trait> Module<T> {
     fn extend_blockchain(
         block_height: u32,
         block_hash: &H256Le,
+        chain_work: U256,
         prev_blockchain: BlockChain,
     ) -> Result<BlockChain, Error> {
         let mut blockchain = prev_blockchain;
@@ -648,15 +940,33 @@ impl<T: Trait> Module<T> {
         Self::insert_block_hash(blockchain.chain_id, block_height, *block_hash);
 
         blockchain.max_height = block_height;
+        blockchain.chain_work = chain_work;
         Self::set_block_chain_from_id(blockchain.chain_id, &blockchain);
 
         Ok(blockchain)
     }
 
+    /// Remove a block header
+    fn remove_block_header(hash: H256Le) {
+        <BlockHeaders>::remove(hash);
+    }
+
+    /// Get the work of the best block header
+    fn get_best_block_work() -> Result<U256, Error> {
+        Ok(Self::get_block_header_from_hash(Self::get_best_block())?
+            .block_header
+            .work())
+    }
+
     // Get require conformations for stable transactions
     fn get_stable_transaction_confirmations() -> u32 {
         Self::confirmations()
     }
+
+    /// Get the consensus parameters of the configured Bitcoin network
+    fn get_network_params() -> NetworkParams {
+        Self::bitcoin_network().params()
+    }
     // *********************************
     // END: Storage getter functions
     // *********************************
@@ -702,22 +1012,84 @@ impl<T: Trait> Module<T> {
 
         // Check that the diff. target is indeed correctly set in the block header, i.e., check for re-target.
         let block_height = prev_block_header.block_height + 1;
+        let params = Self::get_network_params();
 
-        let expected_target =
-            if block_height >= 2016 && block_height % DIFFICULTY_ADJUSTMENT_INTERVAL == 0 {
-                Self::compute_new_target(&prev_block_header, block_height)?
-            } else {
-                prev_block_header.block_header.target
-            };
+        let expected_target = if block_height >= params.difficulty_adjustment_interval
+            && block_height % params.difficulty_adjustment_interval == 0
+        {
+            Self::compute_new_target(&prev_block_header, block_height)?
+        } else if params.allow_min_difficulty_blocks {
+            Self::get_min_difficulty_target(&prev_block_header, basic_block_header.timestamp)?
+        } else {
+            prev_block_header.block_header.target
+        };
 
         ensure!(
             basic_block_header.target == expected_target,
             Error::DiffTargetHeader
         );
 
+        // Check that the block version is not obsoleted by a soft fork (BIP34, BIP66, BIP65)
+        ensure!(
+            basic_block_header.version >= params.min_block_version(block_height),
+            Error::ObsoleteBlockVersion
+        );
+
+        // Check that the timestamp is greater than the median time of the previous blocks
+        ensure!(
+            basic_block_header.timestamp > Self::get_median_time_past(&prev_block_header),
+            Error::TimestampBelowMedianTimePast
+        );
+
+        // Check that the timestamp is not too far ahead of the parachain time
+        let current_time = Self::get_current_time_in_seconds()?;
+        ensure!(
+            basic_block_header.timestamp as u64 <= current_time + MAX_FUTURE_BLOCK_TIME,
+            Error::TimestampTooFarInFuture
+        );
+
         Ok(basic_block_header)
     }
 
+    /// Returns the median timestamp of the given block header and its
+    /// predecessors, considering at most MEDIAN_TIME_SPAN block headers.
+    ///
+    /// # Arguments
+    /// * `prev_block_header` - the latest block header to consider
+    fn get_median_time_past(prev_block_header: &RichBlockHeader) -> u32 {
+        let mut timestamps = Vec::with_capacity(MEDIAN_TIME_SPAN as usize);
+        let mut header = *prev_block_header;
+        timestamps.push(header.block_header.timestamp);
+
+        while timestamps.len() < MEDIAN_TIME_SPAN as usize {
+            match Self::get_block_header_from_hash(header.block_header.hash_prev_block) {
+                Ok(prev_header) => {
+                    header = prev_header;
+                    timestamps.push(header.block_header.timestamp);
+                }
+                // the relay was initialized after this block
+                Err(_) => break,
+            }
+        }
+
+        timestamps.sort();
+        timestamps[timestamps.len() / 2]
+    }
+
+    /// Returns the current parachain time in seconds
+    fn get_current_time_in_seconds() -> Result<u64, Error> {
+        // the timestamp pallet tracks milliseconds since the unix epoch
+        let now: u64 = Self::get_current_time()
+            .try_into()
+            .map_err(|_e| Error::RuntimeError)?;
+        Ok(now / 1000)
+    }
+
+    /// Returns the current timestamp
+    fn get_current_time() -> T::Moment {
+        <timestamp::Module<T>>::get()
+    }
+
     /// Computes Bitcoin's PoW retarget algorithm for a given block height
     /// # Arguments
     ///  * `prev_block_header`: previous block header
@@ -726,28 +1098,72 @@ impl<T: Trait> Module<T> {
         prev_block_header: &RichBlockHeader,
         block_height: u32,
     ) -> Result<U256, Error> {
+        let params = Self::get_network_params();
+        // regtest never adjusts the target
+        if params.no_retargeting {
+            return Ok(prev_block_header.block_header.target);
+        }
+
         // get time of last retarget
         let last_retarget_time =
             Self::get_last_retarget_time(prev_block_header.chain_ref, block_height)?;
-        // Compute new target
-        let actual_timespan = if ((prev_block_header.block_header.timestamp as u64
-            - last_retarget_time) as u32)
-            < (TARGET_TIMESPAN / TARGET_TIMESPAN_DIVISOR)
-        {
-            TARGET_TIMESPAN / TARGET_TIMESPAN_DIVISOR
-        } else {
-            TARGET_TIMESPAN * TARGET_TIMESPAN_DIVISOR
-        };
+        // Compute the actual timespan of the last interval and
+        // limit the adjustment step to [target_timespan / 4, target_timespan * 4]
+        let min_timespan = (params.target_timespan / TARGET_TIMESPAN_DIVISOR) as u64;
+        let max_timespan = (params.target_timespan * TARGET_TIMESPAN_DIVISOR) as u64;
+        let actual_timespan = (prev_block_header.block_header.timestamp as u64)
+            .saturating_sub(last_retarget_time)
+            .max(min_timespan)
+            .min(max_timespan);
 
         let new_target = U256::from(actual_timespan) * prev_block_header.block_header.target
-            / U256::from(TARGET_TIMESPAN);
+            / U256::from(params.target_timespan);
 
         // ensure target does not exceed max. target
-        Ok(if new_target > UNROUNDED_MAX_TARGET {
-            UNROUNDED_MAX_TARGET
+        let new_target = if new_target > params.max_target {
+            params.max_target
         } else {
             new_target
-        })
+        };
+
+        // the target is stored in its compact representation in the header
+        Ok(compact_to_target(target_to_compact(&new_target)))
+    }
+
+    /// Returns the expected target of a block that is not at a retarget
+    /// height on networks allowing min. difficulty blocks (testnet rule):
+    /// if the block is more than twice the target spacing younger than its
+    /// predecessor, the easiest target is allowed. Otherwise, the target of
+    /// the last block not mined at min. difficulty applies.
+    ///
+    /// # Arguments
+    /// * `prev_block_header` - previous block header
+    /// * `timestamp` - timestamp of the new block header
+    fn get_min_difficulty_target(
+        prev_block_header: &RichBlockHeader,
+        timestamp: u32,
+    ) -> Result<U256, Error> {
+        let params = Self::get_network_params();
+        let min_difficulty_target = params.min_difficulty_target();
+
+        if timestamp as u64
+            > prev_block_header.block_header.timestamp as u64 + 2 * params.target_spacing as u64
+        {
+            return Ok(min_difficulty_target);
+        }
+
+        // walk back to the last block not mined at min. difficulty
+        let mut header = *prev_block_header;
+        while header.block_height % params.difficulty_adjustment_interval != 0
+            && header.block_header.target == min_difficulty_target
+        {
+            match Self::get_block_header_from_hash(header.block_header.hash_prev_block) {
+                Ok(prev_header) => header = prev_header,
+                // the relay was initialized after this block
+                Err(_) => break,
+            }
+        }
+        Ok(header.block_header.target)
     }
 
     /// Returns the timestamp of the last difficulty retarget on the specified BlockChain, given the current block height
@@ -759,7 +1175,7 @@ impl<T: Trait> Module<T> {
         let block_chain = Self::get_block_chain_from_id(chain_ref)?;
         let last_retarget_header = Self::get_block_header_from_height(
             &block_chain,
-            block_height - DIFFICULTY_ADJUSTMENT_INTERVAL,
+            block_height - Self::get_network_params().difficulty_adjustment_interval,
         )?;
         Ok(last_retarget_header.block_header.timestamp as u64)
     }
@@ -817,11 +1233,13 @@ impl<T: Trait> Module<T> {
             chain_id,
             start_height,
             max_height: main_chain.max_height,
+            chain_work: main_chain.chain_work,
             no_data,
             invalid,
         };
 
         main_chain.max_height = fork.max_height;
+        main_chain.chain_work = fork.chain_work;
         main_chain.no_data.append(&mut fork.no_data.clone());
         main_chain.invalid.append(&mut fork.invalid.clone());
 
@@ -870,8 +1288,11 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
     /// Checks if a newly inserted fork results in an update to the sorted
-    /// Chains mapping. This happens when the max height of the fork is greater
-    /// than the max height of the previous element in the Chains mapping.
+    /// Chains mapping. This happens when the chain work of the fork is greater
+    /// than the chain work of the previous element in the Chains mapping.
+    /// The fork becomes the main chain if it has accumulated more work than
+    /// STABLE_TRANSACTION_CONFIRMATIONS blocks at the difficulty of the best
+    /// block on top of the work of the main chain.
     ///
     /// # Arguments
     ///
@@ -888,47 +1309,47 @@ impl<T: Trait> Module<T> {
         // print!("fork position {:?}\n", fork_position);
         // check if the previous element in Chains has a lower block_height
         let mut current_position = fork_position;
-        let mut current_height = fork.max_height;
+        let mut current_work = fork.chain_work;
 
-        // swap elements as long as previous block height is smaller
+        // swap elements as long as previous chain work is smaller
         while current_position > 0 {
             // get the previous position
             let prev_position = current_position - 1;
             // get the blockchain id
             let prev_blockchain_id = Self::get_chain_id_from_position(prev_position)?;
-            // get the previous blockchain height
-            let prev_height = Self::get_block_chain_from_id(prev_blockchain_id)?.max_height;
-            // swap elements if block height is greater
-            // print!("curr height {:?}\n", current_height);
-            // print!("prev height {:?}\n", prev_height);
-            if prev_height < current_height {
+            // get the previous blockchain
+            let prev_blockchain = Self::get_block_chain_from_id(prev_blockchain_id)?;
+            let prev_work = prev_blockchain.chain_work;
+            // swap elements if chain work is greater
+            if prev_work < current_work {
                 // Check if swap occurs on the main chain element
-                // print!("prev chain id {:?}\n", prev_blockchain_id);
                 if prev_blockchain_id == MAIN_CHAIN_ID {
                     // if the previous position is the top element
-                    // and the current height is more than the
-                    // STABLE_TRANSACTION_CONFIRMATIONS ahead
+                    // and the current work is more than the work of
+                    // STABLE_TRANSACTION_CONFIRMATIONS blocks ahead
                     // we are swapping the main chain
-                    if prev_height + Self::confirmations() < current_height {
+                    let confirmations_work = Self::get_best_block_work()?
+                        .saturating_mul(U256::from(Self::confirmations()));
+                    if prev_work.saturating_add(confirmations_work) < current_work {
                         Self::swap_main_blockchain(&fork)?;
 
                         // announce the new main chain
                         let new_chain_tip = <BestBlock>::get();
                         let block_height = <BestBlockHeight>::get();
                         let fork_depth = fork.max_height - fork.start_height;
-                        // print!("tip {:?}\n", new_chain_tip);
-                        // print!("block height {:?}\n", block_height);
-                        // print!("depth {:?}\n", fork_depth);
                         Self::deposit_event(Event::ChainReorg(
                             new_chain_tip,
                             block_height,
                             fork_depth,
+                            fork.chain_work,
                         ));
                     } else {
                         Self::deposit_event(Event::ForkAheadOfMainChain(
-                            prev_height,     // main chain height
-                            fork.max_height, // fork height
-                            fork.chain_id,   // fork id
+                            prev_blockchain.max_height, // main chain height
+                            fork.max_height,            // fork height
+                            fork.chain_id,              // fork id
+                            prev_work,                  // main chain work
+                            fork.chain_work,            // fork work
                         ));
                     }
                     // break the while loop
@@ -940,7 +1361,7 @@ impl<T: Trait> Module<T> {
 
                 // update the current chain to the previous one
                 current_position = prev_position;
-                current_height = prev_height;
+                current_work = prev_work;
             } else {
                 break;
             }
@@ -948,7 +1369,7 @@ impl<T: Trait> Module<T> {
 
         Ok(())
     }
-    /// Insert a new fork into the Chains mapping sorted by its max height
+    /// Insert a new fork into the Chains mapping sorted by its chain work
     ///
     /// # Arguments
     ///
@@ -966,16 +1387,16 @@ impl<T: Trait> Module<T> {
         let mut position_blockchain = max_chain_element;
 
         // Starting from the second highest element, find where to insert the new fork
-        // the previous element's block height should be higher or equal
-        // the next element's block height should be lower or equal
+        // the previous element's chain work should be higher or equal
+        // the next element's chain work should be lower or equal
         // NOTE: we never want to insert a new main chain through this function
         for (curr_position, curr_chain_id) in chains.iter().skip(1) {
-            // get the height of the current chain_id
-            let curr_height = Self::get_block_chain_from_id(*curr_chain_id)?.max_height;
+            // get the chain work of the current chain_id
+            let curr_work = Self::get_block_chain_from_id(*curr_chain_id)?.chain_work;
 
-            // if the height of the current blockchain is lower than
+            // if the chain work of the current blockchain is lower than
             // the new blockchain, it should be inserted at that position
-            if curr_height <= blockchain.max_height {
+            if curr_work <= blockchain.chain_work {
                 position_blockchain = *curr_position;
                 break;
             };
@@ -1156,6 +1577,163 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Prunes main chain block headers which are more than the pruning depth
+    /// below the best block, except for the block headers at retarget
+    /// heights, and garbage collects forks whose tip is below that height.
+    /// At most `MaxPrunedPerBlock` block headers are removed per call and
+    /// the start height of the main chain serves as the pruning cursor.
+    /// All reads which can fail happen before the first write.
+    ///
+    /// Returns the number of removed block headers.
+    fn prune_block_headers() -> Result<u32, Error> {
+        let pruning_depth = Self::pruning_depth();
+        if pruning_depth == 0 || !Self::best_block_exists() {
+            return Ok(0);
+        }
+
+        let params = Self::get_network_params();
+        // keep the headers required for retargeting, the median time past
+        // and the testnet min. difficulty rule
+        let pruning_depth = pruning_depth.max(params.difficulty_adjustment_interval);
+        let prune_below = match Self::get_best_block_height().checked_sub(pruning_depth) {
+            Some(height) => height,
+            None => return Ok(0),
+        };
+
+        let max_pruned = Self::max_pruned_per_block();
+        let mut budget = max_pruned;
+
+        // collect the main chain block headers to prune
+        let mut main_chain = Self::get_block_chain_from_id(MAIN_CHAIN_ID)?;
+        let mut main_chain_headers = Vec::new();
+        let mut height = main_chain.start_height;
+        while height < prune_below && budget > 0 {
+            // retarget anchors are never pruned
+            if height % params.difficulty_adjustment_interval != 0 {
+                if let Ok(block_hash) = Self::get_block_hash(MAIN_CHAIN_ID, height) {
+                    main_chain_headers.push((height, block_hash));
+                }
+                budget -= 1;
+            }
+            height += 1;
+        }
+
+        let stale_forks = Self::get_stale_forks(prune_below, &mut budget)?;
+
+        // prune the main chain
+        for (height, block_hash) in main_chain_headers {
+            Self::remove_block_header(block_hash);
+            Self::remove_block_hash(MAIN_CHAIN_ID, height);
+        }
+        if height > main_chain.start_height {
+            main_chain.start_height = height;
+            Self::set_block_chain_from_id(MAIN_CHAIN_ID, &main_chain);
+        }
+
+        if let Some(stale_forks) = stale_forks {
+            Self::prune_stale_forks(stale_forks);
+        }
+
+        Ok(max_pruned - budget)
+    }
+
+    /// Selects the forks whose tip is below the given height in a single
+    /// pass over Chains. Since Chains is sorted by chain work, stale forks
+    /// can be at any position. A fork is pruned from its tip downwards.
+    ///
+    /// Returns `None` if there is nothing to prune, otherwise all forks with
+    /// the block headers to remove and their updated chain work.
+    ///
+    /// # Arguments
+    ///
+    /// * `prune_below` - forks with a lower max height are pruned
+    /// * `budget` - maximum number of block headers to remove, decreased by
+    /// the number of selected block headers
+    fn get_stale_forks(
+        prune_below: u32,
+        budget: &mut u32,
+    ) -> Result<Option<Vec<StaleFork>>, Error> {
+        if *budget == 0 {
+            return Ok(None);
+        }
+
+        let mut chains = <Chains>::iter().collect::<Vec<(u32, u32)>>();
+        chains.sort_by_key(|k| k.0);
+
+        let mut forks = Vec::new();
+        let mut selected = false;
+        // never prune the main chain
+        for (_, chain_id) in chains.into_iter().skip(1) {
+            let mut fork = Self::get_block_chain_from_id(chain_id)?;
+            let mut headers = Vec::new();
+            let mut removed = false;
+
+            if fork.max_height < prune_below {
+                while *budget > 0 {
+                    let block_hash = Self::get_block_hash(fork.chain_id, fork.max_height)?;
+                    headers.push((fork.max_height, block_hash));
+                    *budget -= 1;
+
+                    if fork.max_height == fork.start_height {
+                        removed = true;
+                        break;
+                    }
+                    fork.max_height -= 1;
+                }
+            }
+
+            if !headers.is_empty() && !removed {
+                let tip = Self::get_block_hash(fork.chain_id, fork.max_height)?;
+                fork.chain_work = Self::get_block_header_from_hash(tip)?.chain_work;
+            }
+            selected |= !headers.is_empty();
+            forks.push(StaleFork {
+                fork,
+                headers,
+                removed,
+            });
+        }
+
+        Ok(if selected { Some(forks) } else { None })
+    }
+
+    /// Removes the selected block headers of the forks and rewrites Chains
+    /// such that the remaining forks are contiguous and sorted by chain work.
+    ///
+    /// # Arguments
+    ///
+    /// * `forks` - all forks in the order of Chains, see `get_stale_forks`
+    fn prune_stale_forks(forks: Vec<StaleFork>) {
+        let chain_count = forks.len() as u32 + 1;
+        let mut remaining = Vec::new();
+
+        for stale_fork in forks {
+            for (height, block_hash) in stale_fork.headers.iter() {
+                Self::remove_block_header(*block_hash);
+                Self::remove_block_hash(stale_fork.fork.chain_id, *height);
+            }
+
+            if stale_fork.removed {
+                // all block headers of the fork are removed
+                Self::remove_blockchain_from_chainindex(stale_fork.fork.chain_id);
+            } else {
+                if !stale_fork.headers.is_empty() {
+                    Self::set_block_chain_from_id(stale_fork.fork.chain_id, &stale_fork.fork);
+                }
+                remaining.push(stale_fork.fork);
+            }
+        }
+
+        // the sort is stable, forks with equal chain work keep their order
+        remaining.sort_by(|a, b| b.chain_work.cmp(&a.chain_work));
+        for (position, fork) in remaining.iter().enumerate() {
+            Self::set_chain_from_position_and_id(position as u32 + 1, fork.chain_id);
+        }
+        for position in remaining.len() as u32 + 1..chain_count {
+            <Chains>::remove(position);
+        }
+    }
+
     fn recover_if_needed() -> Result<(), Error> {
         if ext::security::_is_parachain_error_invalid_btcrelay::<T>()
             || ext::security::_is_parachain_error_no_data_btcrelay::<T>()
@@ -1172,10 +1750,12 @@ decl_event! {
         Initialized(u32, H256Le),
         StoreMainChainHeader(u32, H256Le),
         StoreForkHeader(u32, u32, H256Le),
-        ChainReorg(H256Le, u32, u32),
-        ForkAheadOfMainChain(u32, u32, u32),
+        StoreBlockHeadersFailed(u32),
+        PruneBlockHeadersFailed(u32),
+        ChainReorg(H256Le, u32, u32, U256),
+        ForkAheadOfMainChain(u32, u32, u32, U256, U256),
         VerifyTransaction(H256Le, u32, u32),
-        ValidateTransaction(H256Le, u32, H160, H256Le),
+        ValidateTransaction(H256Le, u32, BtcAddress, H256Le),
         FlagBlockError(H256Le, u32, ErrorCode),
         ClearBlockError(H256Le, u32, ErrorCode),
     }
@@ -1,5 +1,6 @@
 /// Mocking the test environment
 use crate::{GenesisConfig, Module, Trait};
+use bitcoin::network::Network;
 use frame_support::{
     impl_outer_event, impl_outer_origin, parameter_types,
     weights::{
@@ -35,6 +36,8 @@ impl_outer_event! {
 pub type AccountId = u64;
 pub const CONFIRMATIONS: u32 = 6;
 pub type BlockNumber = u64;
+/// Parachain time in milliseconds used in tests (2020-09-13)
+pub const CURRENT_TIME: u64 = 1_600_000_000_000;
 
 // For testing the pallet, we construct most of a mock runtime. This means
 // first constructing a configuration type (`Test`) which `impl`s each of the
@@ -82,8 +85,19 @@ impl security::Trait for Test {
     type Event = TestEvent;
 }
 
+parameter_types! {
+    pub const MinimumPeriod: u64 = 5;
+}
+
+impl timestamp::Trait for Test {
+    type Moment = u64;
+    type OnTimestampSet = ();
+    type MinimumPeriod = MinimumPeriod;
+}
+
 pub type System = system::Module<Test>;
 pub type BTCRelay = Module<Test>;
+pub type Timestamp = timestamp::Module<Test>;
 
 pub struct ExtBuilder;
 
@@ -95,6 +109,9 @@ impl ExtBuilder {
 
         GenesisConfig {
             confirmations: CONFIRMATIONS,
+            bitcoin_network: Network::Mainnet,
+            pruning_depth: 0,
+            max_pruned_per_block: 0,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
@@ -110,6 +127,7 @@ where
     clear_mocks();
     ExtBuilder::build().execute_with(|| {
         System::set_block_number(1);
+        Timestamp::set_timestamp(CURRENT_TIME);
         test();
     });
 }
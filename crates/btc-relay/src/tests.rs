@@ -1,16 +1,21 @@
 /// Tests for BTC-Relay
-use primitive_types::U256;
+use primitive_types::{H160, U256};
 
 use crate::ext;
 use crate::mock::{run_test, BTCRelay, Origin, System, Test, TestEvent};
 use crate::Event;
 
+use bitcoin::address::BtcAddress;
 use bitcoin::formatter::Formattable;
 use bitcoin::merkle::*;
+use bitcoin::network::{Network, NetworkParams};
 use bitcoin::parser::*;
 use bitcoin::types::*;
-use frame_support::{assert_err, assert_ok};
+use frame_support::{
+    assert_err, assert_ok, traits::OnInitialize, weights::GetDispatchInfo, StorageValue,
+};
 use security::{ErrorCode, StatusCode};
+use serde::Deserialize;
 use sp_std::collections::btree_set::BTreeSet;
 use sp_std::convert::{TryFrom, TryInto};
 use x_core::Error;
@@ -53,6 +58,7 @@ fn get_block_header_from_hash_succeeds() {
             block_header: BlockHeader::from_le_bytes(&block_header).unwrap(),
             block_height: block_height,
             chain_ref: chain_ref,
+            chain_work: U256::zero(),
         };
 
         BTCRelay::set_block_header_from_hash(rich_header.block_hash, &rich_header);
@@ -158,6 +164,7 @@ fn store_block_header_on_mainchain_succeeds() {
             block_header: parse_block_header(&block_header).unwrap(),
             block_height: block_height,
             chain_ref: chain_ref,
+            chain_work: U256::zero(),
         };
         BTCRelay::get_block_header_from_hash
             .mock_safe(move |_| MockResult::Return(Ok(rich_header)));
@@ -197,6 +204,7 @@ fn store_block_header_on_fork_succeeds() {
             block_header: parse_block_header(&block_header).unwrap(),
             block_height: block_height - 1,
             chain_ref: chain_ref,
+            chain_work: U256::zero(),
         };
         BTCRelay::get_block_header_from_hash
             .mock_safe(move |_| MockResult::Return(Ok(rich_header)));
@@ -364,6 +372,7 @@ fn check_and_do_reorg_new_fork_is_main_chain() {
         assert_eq!(current_position, fork_position);
 
         BTCRelay::swap_main_blockchain.mock_safe(|_| MockResult::Return(Ok(())));
+        BTCRelay::get_best_block_work.mock_safe(|| MockResult::Return(Ok(U256::one())));
 
         assert_ok!(BTCRelay::check_and_do_reorg(&fork));
         // assert that the new main chain is set
@@ -371,10 +380,95 @@ fn check_and_do_reorg_new_fork_is_main_chain() {
             best_block_hash,
             fork_block_height,
             fork.max_height - fork.start_height,
+            fork.chain_work,
         ));
         assert!(System::events().iter().any(|a| a.event == reorg_event));
     })
 }
+
+#[test]
+fn check_and_do_reorg_shorter_fork_with_more_work_is_main_chain() {
+    run_test(|| {
+        // insert the main chain in Chains and ChainsIndex
+        let main_chain_ref: u32 = 0;
+        let main_start_height: u32 = 4;
+        let main_block_height: u32 = 110;
+        let main = get_empty_block_chain_from_chain_id_and_height(
+            main_chain_ref,
+            main_start_height,
+            main_block_height,
+        );
+        BTCRelay::set_chain_from_position_and_id(0, main_chain_ref);
+        BTCRelay::set_block_chain_from_id(main_chain_ref, &main);
+
+        // insert a fork with fewer blocks at a higher difficulty
+        let fork_chain_ref: u32 = 4;
+        let fork_block_height: u32 = 100;
+        let mut fork = get_empty_block_chain_from_chain_id_and_height(
+            fork_chain_ref,
+            main_start_height,
+            fork_block_height,
+        );
+        fork.chain_work = U256::from(main_block_height + 50);
+        BTCRelay::set_chain_from_position_and_id(1, fork_chain_ref);
+        BTCRelay::set_block_chain_from_id(fork_chain_ref, &fork);
+
+        let best_block_hash = H256Le::zero();
+        BTCRelay::set_best_block(best_block_hash);
+        BTCRelay::set_best_block_height(fork_block_height);
+
+        BTCRelay::swap_main_blockchain.mock_safe(|_| MockResult::Return(Ok(())));
+        BTCRelay::get_best_block_work.mock_safe(|| MockResult::Return(Ok(U256::one())));
+
+        assert_ok!(BTCRelay::check_and_do_reorg(&fork));
+        let reorg_event = TestEvent::test_events(Event::ChainReorg(
+            best_block_hash,
+            fork_block_height,
+            fork.max_height - fork.start_height,
+            fork.chain_work,
+        ));
+        assert!(System::events().iter().any(|a| a.event == reorg_event));
+    })
+}
+
+#[test]
+fn check_and_do_reorg_longer_fork_with_less_work_is_not_main_chain() {
+    run_test(|| {
+        // insert the main chain in Chains and ChainsIndex
+        let main_chain_ref: u32 = 0;
+        let main_start_height: u32 = 4;
+        let main_block_height: u32 = 110;
+        let main = get_empty_block_chain_from_chain_id_and_height(
+            main_chain_ref,
+            main_start_height,
+            main_block_height,
+        );
+        BTCRelay::set_chain_from_position_and_id(0, main_chain_ref);
+        BTCRelay::set_block_chain_from_id(main_chain_ref, &main);
+
+        // insert a fork with more blocks at a lower difficulty
+        let fork_chain_ref: u32 = 4;
+        let fork_block_height: u32 = 130;
+        let mut fork = get_empty_block_chain_from_chain_id_and_height(
+            fork_chain_ref,
+            main_start_height,
+            fork_block_height,
+        );
+        fork.chain_work = U256::from(main_block_height - 1);
+        BTCRelay::set_chain_from_position_and_id(1, fork_chain_ref);
+        BTCRelay::set_block_chain_from_id(fork_chain_ref, &fork);
+
+        BTCRelay::swap_main_blockchain
+            .mock_safe(|_| panic!("fork with less work must not become the main chain"));
+
+        assert_ok!(BTCRelay::check_and_do_reorg(&fork));
+        assert_eq!(
+            BTCRelay::get_chain_position_from_chain_id(fork_chain_ref),
+            Ok(1)
+        );
+        assert!(System::events().is_empty());
+    })
+}
 #[test]
 fn check_and_do_reorg_new_fork_below_stable_transaction_confirmations() {
     run_test(|| {
@@ -414,6 +508,7 @@ fn check_and_do_reorg_new_fork_below_stable_transaction_confirmations() {
         assert_eq!(current_position, fork_position);
 
         BTCRelay::swap_main_blockchain.mock_safe(|_| MockResult::Return(Ok(())));
+        BTCRelay::get_best_block_work.mock_safe(|| MockResult::Return(Ok(U256::one())));
 
         assert_ok!(BTCRelay::check_and_do_reorg(&fork));
         // assert that the fork has not overtaken the main chain
@@ -421,6 +516,8 @@ fn check_and_do_reorg_new_fork_below_stable_transaction_confirmations() {
             main_block_height,
             fork_block_height,
             fork_chain_ref,
+            main.chain_work,
+            fork.chain_work,
         ));
         assert!(System::events().iter().any(|a| a.event == ahead_event));
     })
@@ -527,6 +624,7 @@ fn swap_main_blockchain_succeeds() {
         let new_main = BTCRelay::get_block_chain_from_id(main_chain_ref).unwrap();
         assert_eq!(fork_height, new_main.max_height);
         assert_eq!(main_start, new_main.start_height);
+        assert_eq!(fork.chain_work, new_main.chain_work);
         assert_eq!(main_chain_ref, new_main.chain_id);
         assert_eq!(
             fork_height + 1,
@@ -550,6 +648,7 @@ fn swap_main_blockchain_succeeds() {
         let old_main = BTCRelay::get_block_chain_from_id(old_main_ref).unwrap();
         assert_eq!(main_height, old_main.max_height);
         assert_eq!(fork_start, old_main.start_height);
+        assert_eq!(main.chain_work, old_main.chain_work);
         assert_eq!(old_main_ref, old_main.chain_id);
         let old_main_length = BTCRelay::_blocks_count(old_main.chain_id);
         assert_eq!(main_height - fork_start + 1, old_main_length as u32);
@@ -688,23 +787,308 @@ fn test_verify_block_header_missing_retarget_succeeds() {
 
 #[test]
 fn test_compute_new_target() {
-    let chain_ref: u32 = 0;
-    // no retarget at block 100
-    let block_height: u32 = 2016;
-    let retarget_headers = sample_retarget_interval_increase();
+    run_test(|| {
+        let chain_ref: u32 = 0;
+        // no retarget at block 100
+        let block_height: u32 = 2016;
+        let retarget_headers = sample_retarget_interval_increase();
+
+        let last_retarget_time = parse_block_header(&retarget_headers[0]).unwrap().timestamp as u64;
+        let prev_block_header =
+            RichBlockHeader::construct(retarget_headers[1], chain_ref, block_height).unwrap();
+
+        let curr_block_header = parse_block_header(&retarget_headers[2]).unwrap();
+
+        BTCRelay::get_last_retarget_time
+            .mock_safe(move |_, _| MockResult::Return(Ok(last_retarget_time)));
+
+        let new_target = BTCRelay::compute_new_target(&prev_block_header, block_height).unwrap();
+
+        assert_eq!(new_target, curr_block_header.target);
+    })
+}
+
+#[test]
+fn test_compute_new_target_applies_actual_timespan() {
+    run_test(|| {
+        let prev_target = U256::from(0xffff) << 180;
+        // half the target timespan
+        let prev_block_header =
+            sample_rich_header_with_target(prev_target, 604_800, H256Le::zero(), 2015);
+        BTCRelay::get_last_retarget_time.mock_safe(|_, _| MockResult::Return(Ok(0)));
+
+        assert_eq!(
+            BTCRelay::compute_new_target(&prev_block_header, 2016),
+            Ok(prev_target / 2)
+        );
+    })
+}
+
+#[test]
+fn test_compute_new_target_clamps_to_max_timespan() {
+    run_test(|| {
+        let prev_target = U256::from(0xffff) << 180;
+        // ten times the target timespan
+        let prev_block_header =
+            sample_rich_header_with_target(prev_target, 12_096_000, H256Le::zero(), 2015);
+        BTCRelay::get_last_retarget_time.mock_safe(|_, _| MockResult::Return(Ok(0)));
+
+        assert_eq!(
+            BTCRelay::compute_new_target(&prev_block_header, 2016),
+            Ok(prev_target * 4)
+        );
+    })
+}
+
+#[test]
+fn store_block_header_replays_mainnet_retargets() {
+    for boundary in sample_mainnet_retarget_boundaries() {
+        let period_end = match boundary.period_end {
+            Some(ref period_end) => RawBlockHeader::from_hex(period_end).unwrap(),
+            None => continue,
+        };
+        run_test(|| {
+            let period_start = RawBlockHeader::from_hex(&boundary.period_start).unwrap();
+            let retarget = RawBlockHeader::from_hex(&boundary.retarget).unwrap();
+
+            assert_ok!(BTCRelay::initialize(
+                Origin::signed(3),
+                period_end,
+                boundary.height - 1
+            ));
+            // only the first header of the period is required for the retarget
+            let period_start_header = RichBlockHeader::construct(
+                period_start,
+                crate::MAIN_CHAIN_ID,
+                boundary.height - 2016,
+            )
+            .unwrap();
+            BTCRelay::set_block_header_from_hash(
+                period_start_header.block_hash,
+                &period_start_header,
+            );
+            BTCRelay::insert_block_hash(
+                crate::MAIN_CHAIN_ID,
+                period_start_header.block_height,
+                period_start_header.block_hash,
+            );
+
+            assert_ok!(BTCRelay::store_block_header(Origin::signed(3), retarget));
+            assert_eq!(BTCRelay::get_best_block(), retarget.hash());
+            assert_eq!(BTCRelay::get_best_block_height(), boundary.height);
+        })
+    }
+}
+
+#[test]
+fn test_compute_new_target_matches_clamped_mainnet_retargets() {
+    let boundaries: Vec<RetargetBoundary> = sample_mainnet_retarget_boundaries()
+        .into_iter()
+        .filter(|boundary| boundary.period_end.is_none())
+        .collect();
+    assert!(!boundaries.is_empty());
+
+    for boundary in boundaries {
+        run_test(|| {
+            let period_start =
+                parse_block_header(&RawBlockHeader::from_hex(&boundary.period_start).unwrap())
+                    .unwrap();
+            let retarget =
+                parse_block_header(&RawBlockHeader::from_hex(&boundary.retarget).unwrap()).unwrap();
+            let last_retarget_time = period_start.timestamp as u64;
+            BTCRelay::get_last_retarget_time
+                .mock_safe(move |_, _| MockResult::Return(Ok(last_retarget_time)));
+
+            // the period is shorter than the minimum timespan even when it
+            // ends at the time of the retarget header
+            let period_end = sample_rich_header_with_target(
+                period_start.target,
+                retarget.timestamp,
+                H256Le::zero(),
+                boundary.height - 1,
+            );
+
+            assert_eq!(
+                BTCRelay::compute_new_target(&period_end, boundary.height),
+                Ok(retarget.target)
+            );
+        })
+    }
+}
+
+#[test]
+fn test_compute_new_target_no_retargeting() {
+    run_test(|| {
+        BTCRelay::get_network_params.mock_safe(|| MockResult::Return(Network::Regtest.params()));
+
+        let retarget_headers = sample_retarget_interval_increase();
+        let prev_block_header = RichBlockHeader::construct(retarget_headers[1], 0, 2015).unwrap();
+
+        let new_target = BTCRelay::compute_new_target(&prev_block_header, 2016).unwrap();
+        assert_eq!(new_target, prev_block_header.block_header.target);
+    })
+}
+
+#[test]
+fn test_get_min_difficulty_target_after_twice_target_spacing() {
+    run_test(|| {
+        let params = Network::Testnet.params();
+        BTCRelay::get_network_params.mock_safe(move || MockResult::Return(params));
+
+        let prev_block_header = sample_rich_header_with_target(
+            U256::from(2).pow(200.into()),
+            1_000_000,
+            H256Le::zero(),
+            100,
+        );
+
+        // more than 20 minutes after the previous block
+        assert_eq!(
+            BTCRelay::get_min_difficulty_target(&prev_block_header, 1_001_201),
+            Ok(params.min_difficulty_target())
+        );
+        // within 20 minutes, the regular target applies
+        assert_eq!(
+            BTCRelay::get_min_difficulty_target(&prev_block_header, 1_001_200),
+            Ok(prev_block_header.block_header.target)
+        );
+    })
+}
 
-    let last_retarget_time = parse_block_header(&retarget_headers[0]).unwrap().timestamp as u64;
-    let prev_block_header =
-        RichBlockHeader::construct(retarget_headers[1], chain_ref, block_height).unwrap();
+#[test]
+fn test_get_min_difficulty_target_skips_min_difficulty_blocks() {
+    run_test(|| {
+        let params = Network::Testnet.params();
+        BTCRelay::get_network_params.mock_safe(move || MockResult::Return(params));
+
+        let regular_target = U256::from(2).pow(200.into());
+        let regular_header =
+            sample_rich_header_with_target(regular_target, 1_000_000, H256Le::zero(), 98);
+        BTCRelay::set_block_header_from_hash(regular_header.block_hash, &regular_header);
+
+        let min_difficulty_header = sample_rich_header_with_target(
+            params.min_difficulty_target(),
+            1_001_500,
+            regular_header.block_hash,
+            99,
+        );
+        BTCRelay::set_block_header_from_hash(
+            min_difficulty_header.block_hash,
+            &min_difficulty_header,
+        );
 
-    let curr_block_header = parse_block_header(&retarget_headers[2]).unwrap();
+        let prev_block_header = sample_rich_header_with_target(
+            params.min_difficulty_target(),
+            1_003_000,
+            min_difficulty_header.block_hash,
+            100,
+        );
 
-    BTCRelay::get_last_retarget_time
-        .mock_safe(move |_, _| MockResult::Return(Ok(last_retarget_time)));
+        assert_eq!(
+            BTCRelay::get_min_difficulty_target(&prev_block_header, 1_003_600),
+            Ok(regular_target)
+        );
+    })
+}
 
-    let new_target = BTCRelay::compute_new_target(&prev_block_header, block_height).unwrap();
+#[test]
+fn test_verify_block_header_obsolete_version_fails() {
+    run_test(|| {
+        let chain_ref: u32 = 0;
+        // next block must be at least version 2 (BIP34)
+        let block_height: u32 = Network::Mainnet.params().bip34_height - 1;
+        let genesis_header = sample_parsed_genesis_header(chain_ref, block_height);
+        // version 1 block header
+        let raw_first_header = RawBlockHeader::from_hex(sample_raw_first_header()).unwrap();
+
+        BTCRelay::get_block_header_from_hash
+            .mock_safe(move |_| MockResult::Return(Ok(genesis_header)));
+        BTCRelay::block_header_exists.mock_safe(move |_| MockResult::Return(false));
 
-    assert_eq!(new_target, curr_block_header.target);
+        assert_err!(
+            BTCRelay::verify_block_header(&raw_first_header),
+            Error::ObsoleteBlockVersion
+        );
+    })
+}
+
+#[test]
+fn test_verify_block_header_timestamp_below_median_time_past_fails() {
+    run_test(|| {
+        let chain_ref: u32 = 0;
+        let block_height: u32 = 100;
+        let mut genesis_header = sample_parsed_genesis_header(chain_ref, block_height);
+        let raw_first_header = RawBlockHeader::from_hex(sample_raw_first_header()).unwrap();
+        let first_header = parse_block_header(&raw_first_header).unwrap();
+        // previous blocks are as old as the submitted block
+        genesis_header.block_header.timestamp = first_header.timestamp;
+
+        BTCRelay::get_block_header_from_hash
+            .mock_safe(move |_| MockResult::Return(Ok(genesis_header)));
+        BTCRelay::block_header_exists.mock_safe(move |_| MockResult::Return(false));
+
+        assert_err!(
+            BTCRelay::verify_block_header(&raw_first_header),
+            Error::TimestampBelowMedianTimePast
+        );
+    })
+}
+
+#[test]
+fn test_verify_block_header_timestamp_too_far_in_future_fails() {
+    run_test(|| {
+        let chain_ref: u32 = 0;
+        let block_height: u32 = 100;
+        let genesis_header = sample_parsed_genesis_header(chain_ref, block_height);
+        let raw_first_header = RawBlockHeader::from_hex(sample_raw_first_header()).unwrap();
+        let first_header = parse_block_header(&raw_first_header).unwrap();
+
+        BTCRelay::get_block_header_from_hash
+            .mock_safe(move |_| MockResult::Return(Ok(genesis_header)));
+        BTCRelay::block_header_exists.mock_safe(move |_| MockResult::Return(false));
+        // parachain time is more than two hours behind the block
+        let current_time =
+            (first_header.timestamp as u64 - crate::MAX_FUTURE_BLOCK_TIME - 1) * 1000;
+        BTCRelay::get_current_time.mock_safe(move || MockResult::Return(current_time));
+
+        assert_err!(
+            BTCRelay::verify_block_header(&raw_first_header),
+            Error::TimestampTooFarInFuture
+        );
+    })
+}
+
+#[test]
+fn test_get_median_time_past_succeeds() {
+    run_test(|| {
+        let mut headers: Vec<RichBlockHeader> = Vec::new();
+        // timestamps are not monotonic in Bitcoin
+        for (height, timestamp) in [5, 1, 4, 2, 3, 12, 11, 10, 9, 8, 7, 6].iter().enumerate() {
+            let prev_hash = headers
+                .last()
+                .map_or(H256Le::zero(), |header| header.block_hash);
+            let header =
+                sample_rich_header_with_target(U256::zero(), *timestamp, prev_hash, height as u32);
+            BTCRelay::set_block_header_from_hash(header.block_hash, &header);
+            headers.push(header);
+        }
+
+        // the first block (timestamp 5) is not considered
+        assert_eq!(BTCRelay::get_median_time_past(headers.last().unwrap()), 7);
+    })
+}
+
+#[test]
+fn test_get_median_time_past_with_few_blocks_succeeds() {
+    run_test(|| {
+        let first = sample_rich_header_with_target(U256::zero(), 10, H256Le::zero(), 0);
+        BTCRelay::set_block_header_from_hash(first.block_hash, &first);
+        let second = sample_rich_header_with_target(U256::zero(), 30, first.block_hash, 1);
+        BTCRelay::set_block_header_from_hash(second.block_hash, &second);
+        let third = sample_rich_header_with_target(U256::zero(), 20, second.block_hash, 2);
+
+        assert_eq!(BTCRelay::get_median_time_past(&third), 20);
+    })
 }
 
 #[test]
@@ -781,8 +1165,7 @@ fn test_validate_transaction_succeeds() {
     run_test(|| {
         let raw_tx = hex::decode(sample_accepted_transaction()).unwrap();
         let payment_value: i64 = 2500200000;
-        let recipient_btc_address =
-            hex::decode("66c7060feb882664ae62ffad0051fe843e318e85".to_owned()).unwrap();
+        let recipient_btc_address = sample_recipient_btc_address();
         let op_return_id = hex::decode(
             "aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned(),
         )
@@ -803,6 +1186,28 @@ fn test_validate_transaction_succeeds() {
     });
 }
 
+#[test]
+fn test_validate_transaction_returns_payment_value() {
+    run_test(|| {
+        let raw_tx = hex::decode(sample_accepted_transaction()).unwrap();
+        let recipient_btc_address = sample_recipient_btc_address();
+        let op_return_id = hex::decode(
+            "aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned(),
+        )
+        .unwrap();
+
+        let outputs = vec![sample_valid_payment_output(), sample_valid_data_output()];
+
+        BTCRelay::parse_transaction
+            .mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
+
+        assert_eq!(
+            BTCRelay::_validate_transaction(raw_tx, 100, recipient_btc_address, op_return_id),
+            Ok(2500200000)
+        );
+    });
+}
+
 #[test]
 fn test_validate_transaction_invalid_no_outputs_fails() {
     run_test(|| {
@@ -810,8 +1215,7 @@ fn test_validate_transaction_invalid_no_outputs_fails() {
         let raw_tx = hex::decode(sample_accepted_transaction()).unwrap();
 
         let payment_value: i64 = 2500200000;
-        let recipient_btc_address =
-            hex::decode("66c7060feb882664ae62ffad0051fe843e318e85".to_owned()).unwrap();
+        let recipient_btc_address = sample_recipient_btc_address();
         let op_return_id = hex::decode(
             "aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned(),
         )
@@ -842,8 +1246,7 @@ fn test_validate_transaction_insufficient_payment_value_fails() {
         let raw_tx = vec![0u8; 342];
 
         let payment_value: i64 = 2500200000;
-        let recipient_btc_address =
-            hex::decode("66c7060feb882664ae62ffad0051fe843e318e85".to_owned()).unwrap();
+        let recipient_btc_address = sample_recipient_btc_address();
         let op_return_id = hex::decode(
             "aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned(),
         )
@@ -871,73 +1274,339 @@ fn test_validate_transaction_insufficient_payment_value_fails() {
 }
 
 #[test]
-fn test_validate_transaction_wrong_recipient_fails() {
+fn test_validate_transaction_wrong_recipient_fails() {
+    run_test(|| {
+        // Simulate input (we mock the parsed transaction)
+        let raw_tx = vec![0u8; 342];
+
+        let payment_value: i64 = 2500200000;
+        let recipient_btc_address = sample_recipient_btc_address();
+        let op_return_id = hex::decode(
+            "aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned(),
+        )
+        .unwrap();
+
+        let outputs = vec![
+            sample_wrong_recipient_payment_output(),
+            sample_valid_data_output(),
+        ];
+
+        BTCRelay::parse_transaction
+            .mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
+
+        assert_err!(
+            BTCRelay::validate_transaction(
+                Origin::signed(3),
+                raw_tx,
+                payment_value,
+                recipient_btc_address,
+                op_return_id
+            ),
+            Error::WrongRecipient
+        )
+    });
+}
+
+#[test]
+fn test_validate_transaction_wrong_recipient_script_type_fails() {
+    run_test(|| {
+        // Simulate input (we mock the parsed transaction)
+        let raw_tx = vec![0u8; 342];
+
+        let payment_value: i64 = 2500200000;
+        // same hash as the P2SH payment output, but a different script type
+        let recipient_btc_address = BtcAddress::P2PKH(H160::from_slice(
+            &hex::decode("66c7060feb882664ae62ffad0051fe843e318e85").unwrap(),
+        ));
+        let op_return_id = hex::decode(
+            "aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned(),
+        )
+        .unwrap();
+
+        let outputs = vec![sample_valid_payment_output(), sample_valid_data_output()];
+
+        BTCRelay::parse_transaction
+            .mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
+
+        assert_err!(
+            BTCRelay::validate_transaction(
+                Origin::signed(3),
+                raw_tx,
+                payment_value,
+                recipient_btc_address,
+                op_return_id
+            ),
+            Error::WrongRecipient
+        )
+    });
+}
+
+#[test]
+fn test_validate_transaction_incorrect_opreturn_fails() {
+    run_test(|| {
+        // Simulate input (we mock the parsed transaction)
+        let raw_tx = vec![0u8; 342];
+
+        let payment_value: i64 = 2500200000;
+        let recipient_btc_address = sample_recipient_btc_address();
+        let op_return_id = hex::decode(
+            "6a24aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675"
+                .to_owned(),
+        )
+        .unwrap();
+
+        let outputs = vec![
+            sample_valid_payment_output(),
+            sample_incorrect_data_output(),
+        ];
+
+        BTCRelay::parse_transaction
+            .mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
+
+        assert_err!(
+            BTCRelay::validate_transaction(
+                Origin::signed(3),
+                raw_tx,
+                payment_value,
+                recipient_btc_address,
+                op_return_id
+            ),
+            Error::InvalidOpreturn
+        )
+    });
+}
+
+#[test]
+fn test_validate_transaction_with_outputs_in_any_order_succeeds() {
+    run_test(|| {
+        let op_return_id = hex::decode(
+            "aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned(),
+        )
+        .unwrap();
+
+        // change output first, OP_RETURN before the payment
+        let outputs = vec![
+            sample_wrong_recipient_payment_output(),
+            sample_valid_data_output(),
+            sample_valid_payment_output(),
+        ];
+
+        BTCRelay::parse_transaction
+            .mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
+
+        assert_eq!(
+            BTCRelay::_validate_transaction(
+                vec![0u8; 342],
+                2500200000,
+                sample_recipient_btc_address(),
+                op_return_id
+            ),
+            Ok(2500200000)
+        );
+    });
+}
+
+#[test]
+fn test_validate_transaction_with_several_op_returns_fails() {
+    run_test(|| {
+        let op_return_id = hex::decode(
+            "aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned(),
+        )
+        .unwrap();
+
+        let outputs = vec![
+            sample_valid_payment_output(),
+            sample_incorrect_data_output(),
+            sample_valid_data_output(),
+        ];
+
+        BTCRelay::parse_transaction
+            .mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
+
+        assert_err!(
+            BTCRelay::_validate_transaction(
+                vec![0u8; 342],
+                2500200000,
+                sample_recipient_btc_address(),
+                op_return_id
+            ),
+            Error::AmbiguousTransactionOutputs
+        );
+    });
+}
+
+#[test]
+fn test_validate_transaction_with_several_payments_to_recipient_fails() {
+    run_test(|| {
+        let op_return_id = hex::decode(
+            "aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned(),
+        )
+        .unwrap();
+
+        let outputs = vec![
+            sample_insufficient_value_payment_output(),
+            sample_valid_data_output(),
+            sample_valid_payment_output(),
+        ];
+
+        BTCRelay::parse_transaction
+            .mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
+
+        assert_err!(
+            BTCRelay::_validate_transaction(
+                vec![0u8; 342],
+                100,
+                sample_recipient_btc_address(),
+                op_return_id
+            ),
+            Error::AmbiguousTransactionOutputs
+        );
+    });
+}
+
+#[test]
+fn test_validate_batch_transaction_succeeds() {
+    run_test(|| {
+        let (first_id, second_id) = (vec![1u8; 32], vec![2u8; 32]);
+        let first_leaf = BTCRelay::batch_commitment_leaf(&first_id);
+        let second_leaf = BTCRelay::batch_commitment_leaf(&second_id);
+        let root = BTCRelay::batch_commitment_node(&first_leaf, &second_leaf);
+        let outputs = vec![
+            sample_wrong_recipient_payment_output(),
+            sample_valid_payment_output(),
+            TransactionOutput::op_return(0, &sample_batch_commitment(1, root)),
+        ];
+
+        BTCRelay::parse_transaction
+            .mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
+
+        assert_eq!(
+            BTCRelay::_validate_batch_transaction(
+                vec![0u8; 342],
+                1,
+                2500200000,
+                sample_recipient_btc_address(),
+                second_id,
+                vec![first_leaf]
+            ),
+            Ok(2500200000)
+        );
+    });
+}
+
+#[test]
+fn test_validate_batch_transaction_of_single_request_succeeds() {
     run_test(|| {
-        // Simulate input (we mock the parsed transaction)
-        let raw_tx = vec![0u8; 342];
+        let op_return_id = vec![1u8; 32];
+        let leaf = BTCRelay::batch_commitment_leaf(&op_return_id);
+        let outputs = vec![
+            sample_valid_payment_output(),
+            TransactionOutput::op_return(0, &sample_batch_commitment(0, leaf)),
+        ];
 
-        let payment_value: i64 = 2500200000;
-        let recipient_btc_address =
-            hex::decode("66c7060feb882664ae62ffad0051fe843e318e85".to_owned()).unwrap();
-        let op_return_id = hex::decode(
-            "aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned(),
-        )
-        .unwrap();
+        BTCRelay::parse_transaction
+            .mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
+
+        assert_ok!(BTCRelay::_validate_batch_transaction(
+            vec![0u8; 342],
+            0,
+            2500200000,
+            sample_recipient_btc_address(),
+            op_return_id,
+            vec![]
+        ));
+    });
+}
 
+#[test]
+fn test_validate_batch_transaction_with_id_at_other_index_fails() {
+    run_test(|| {
+        let (first_id, second_id) = (vec![1u8; 32], vec![2u8; 32]);
+        let first_leaf = BTCRelay::batch_commitment_leaf(&first_id);
+        let second_leaf = BTCRelay::batch_commitment_leaf(&second_id);
+        let root = BTCRelay::batch_commitment_node(&first_leaf, &second_leaf);
         let outputs = vec![
-            sample_wrong_recipient_payment_output(),
-            sample_valid_data_output(),
+            sample_valid_payment_output(),
+            sample_valid_payment_output(),
+            TransactionOutput::op_return(0, &sample_batch_commitment(1, root)),
         ];
 
         BTCRelay::parse_transaction
             .mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
 
+        // the second id is committed to at index 1, not at index 0
         assert_err!(
-            BTCRelay::validate_transaction(
-                Origin::signed(3),
-                raw_tx,
-                payment_value,
-                recipient_btc_address,
-                op_return_id
+            BTCRelay::_validate_batch_transaction(
+                vec![0u8; 342],
+                0,
+                2500200000,
+                sample_recipient_btc_address(),
+                second_id,
+                vec![first_leaf]
             ),
-            Error::WrongRecipient
-        )
+            Error::InvalidOpreturn
+        );
     });
 }
 
 #[test]
-fn test_validate_transaction_incorrect_opreturn_fails() {
+fn test_validate_batch_transaction_with_node_as_id_fails() {
     run_test(|| {
-        // Simulate input (we mock the parsed transaction)
-        let raw_tx = vec![0u8; 342];
-
-        let payment_value: i64 = 2500200000;
-        let recipient_btc_address =
-            hex::decode("66c7060feb882664ae62ffad0051fe843e318e85".to_owned()).unwrap();
-        let op_return_id = hex::decode(
-            "6a24aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675"
-                .to_owned(),
-        )
-        .unwrap();
-
+        let leaves: Vec<H256Le> = (1..=4u8)
+            .map(|i| BTCRelay::batch_commitment_leaf(&[i; 32]))
+            .collect();
+        let left = BTCRelay::batch_commitment_node(&leaves[0], &leaves[1]);
+        let right = BTCRelay::batch_commitment_node(&leaves[2], &leaves[3]);
+        let root = BTCRelay::batch_commitment_node(&left, &right);
         let outputs = vec![
             sample_valid_payment_output(),
-            sample_incorrect_data_output(),
+            sample_valid_payment_output(),
+            sample_valid_payment_output(),
+            sample_valid_payment_output(),
+            TransactionOutput::op_return(0, &sample_batch_commitment(2, root)),
         ];
 
         BTCRelay::parse_transaction
             .mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
 
+        // the first id is paid by the output at index 0
+        assert_ok!(BTCRelay::_validate_batch_transaction(
+            vec![0u8; 342],
+            0,
+            2500200000,
+            sample_recipient_btc_address(),
+            vec![1u8; 32],
+            vec![leaves[1], right]
+        ));
+        // a node claimed as id at a lower depth does not pay the same output
         assert_err!(
-            BTCRelay::validate_transaction(
-                Origin::signed(3),
-                raw_tx,
-                payment_value,
-                recipient_btc_address,
-                op_return_id
+            BTCRelay::_validate_batch_transaction(
+                vec![0u8; 342],
+                0,
+                2500200000,
+                sample_recipient_btc_address(),
+                left.to_bytes_le().to_vec(),
+                vec![right]
             ),
             Error::InvalidOpreturn
-        )
+        );
+    });
+}
+
+#[test]
+fn test_validate_batch_transaction_index_exceeding_path_fails() {
+    run_test(|| {
+        assert_err!(
+            BTCRelay::_validate_batch_transaction(
+                vec![0u8; 342],
+                2,
+                2500200000,
+                sample_recipient_btc_address(),
+                vec![2u8; 32],
+                vec![H256Le::zero()]
+            ),
+            Error::InvalidBatchCommitment
+        );
     });
 }
 
@@ -963,13 +1632,13 @@ fn test_verify_and_validate_transaction_succeeds() {
         let confirmations = 0;
         let insecure = false;
         let payment_value: i64 = 0;
-        let recipient_btc_address =
-            hex::decode("66c7060feb882664ae62ffad0051fe843e318e85".to_owned()).unwrap();
+        let recipient_btc_address = sample_recipient_btc_address();
         let op_return_id = hex::decode(
             "aa21a9ede5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned(),
         )
         .unwrap();
-        BTCRelay::_validate_transaction.mock_safe(move |_, _, _, _| MockResult::Return(Ok(())));
+        BTCRelay::_validate_transaction
+            .mock_safe(move |_, _, _, _| MockResult::Return(Ok(payment_value)));
         BTCRelay::_verify_transaction_inclusion
             .mock_safe(move |_, _, _, _, _| MockResult::Return(Ok(())));
 
@@ -1002,6 +1671,7 @@ fn test_flag_block_error_succeeds() {
             block_header: BlockHeader::from_le_bytes(&block_header).unwrap(),
             block_height: block_height,
             chain_ref: chain_ref,
+            chain_work: U256::zero(),
         };
 
         BTCRelay::set_block_header_from_hash(rich_header.block_hash, &rich_header);
@@ -1047,6 +1717,7 @@ fn test_flag_block_error_fails() {
             block_header: BlockHeader::from_le_bytes(&block_header).unwrap(),
             block_height: block_height,
             chain_ref: chain_ref,
+            chain_work: U256::zero(),
         };
 
         BTCRelay::set_block_header_from_hash(rich_header.block_hash, &rich_header);
@@ -1079,6 +1750,7 @@ fn test_clear_block_error_succeeds() {
             block_header: BlockHeader::from_le_bytes(&block_header).unwrap(),
             block_height: block_height,
             chain_ref: chain_ref,
+            chain_work: U256::zero(),
         };
 
         BTCRelay::set_block_header_from_hash(rich_header.block_hash, &rich_header);
@@ -1140,6 +1812,7 @@ fn test_clear_block_error_fails() {
             block_header: BlockHeader::from_le_bytes(&block_header).unwrap(),
             block_height: block_height,
             chain_ref: chain_ref,
+            chain_work: U256::zero(),
         };
 
         BTCRelay::set_block_header_from_hash(rich_header.block_hash, &rich_header);
@@ -1634,6 +2307,45 @@ fn get_chain_from_id_ok() {
     });
 }
 
+#[test]
+fn get_chains_returns_chains_in_order() {
+    run_test(|| {
+        let main = store_blockchain_and_random_headers(0, 0, 20, 0);
+        let fork = store_blockchain_and_random_headers(1, 10, 15, 1);
+
+        assert_eq!(Ok(vec![main, fork]), BTCRelay::get_chains());
+    })
+}
+
+#[test]
+fn get_main_chain_block_header_succeeds() {
+    run_test(|| {
+        let main = store_blockchain_and_random_headers(0, 0, 20, 0);
+        let expected = BTCRelay::get_block_header_from_height(&main, 12).unwrap();
+
+        assert_eq!(Ok(expected), BTCRelay::get_main_chain_block_header(12));
+        assert_err!(
+            BTCRelay::get_main_chain_block_header(21),
+            Error::MissingBlockHeight
+        );
+    })
+}
+
+#[test]
+fn get_block_confirmations_succeeds() {
+    run_test(|| {
+        store_blockchain_and_random_headers(0, 0, 20, 0);
+        BTCRelay::set_best_block_height(20);
+
+        assert_eq!(Ok(0), BTCRelay::get_block_confirmations(20));
+        assert_eq!(Ok(6), BTCRelay::get_block_confirmations(14));
+        assert_err!(
+            BTCRelay::get_block_confirmations(21),
+            Error::MissingBlockHeight
+        );
+    })
+}
+
 #[test]
 fn store_generated_block_headers() {
     let target = U256::from(2).pow(254.into());
@@ -1643,6 +2355,7 @@ fn store_generated_block_headers() {
     run_test(|| {
         let mut last_block = BlockBuilder::new()
             .with_coinbase(&miner, 50, 0)
+            .with_timestamp(1588813835)
             .mine(target);
         assert_ok!(BTCRelay::initialize(
             Origin::signed(3),
@@ -1653,6 +2366,7 @@ fn store_generated_block_headers() {
             last_block = BlockBuilder::new()
                 .with_coinbase(&miner, 50, i)
                 .with_previous_hash(last_block.header.hash())
+                .with_timestamp(1588813835 + i * 600)
                 .mine(target);
             assert_ok!(BTCRelay::store_block_header(
                 Origin::signed(3),
@@ -1666,8 +2380,356 @@ fn store_generated_block_headers() {
     })
 }
 
+#[test]
+fn store_generated_block_headers_batch_succeeds() {
+    let target = U256::from(2).pow(254.into());
+    let miner = Address::try_from("66c7060feb882664ae62ffad0051fe843e318e85").unwrap();
+    let get_header = |block: &Block| RawBlockHeader::from_bytes(&block.header.format()).unwrap();
+
+    run_test(|| {
+        let mut last_block = BlockBuilder::new()
+            .with_coinbase(&miner, 50, 0)
+            .with_timestamp(1588813835)
+            .mine(target);
+        assert_ok!(BTCRelay::initialize(
+            Origin::signed(3),
+            get_header(&last_block),
+            0
+        ));
+        let mut headers = Vec::new();
+        for i in 1..20 {
+            last_block = BlockBuilder::new()
+                .with_coinbase(&miner, 50, i)
+                .with_previous_hash(last_block.header.hash())
+                .with_timestamp(1588813835 + i * 600)
+                .mine(target);
+            headers.push(get_header(&last_block));
+        }
+        assert_ok!(BTCRelay::store_block_headers(Origin::signed(3), headers));
+
+        let main_chain: BlockChain =
+            BTCRelay::get_block_chain_from_id(crate::MAIN_CHAIN_ID).unwrap();
+        assert_eq!(main_chain.start_height, 0);
+        assert_eq!(main_chain.max_height, 19);
+        assert_eq!(BTCRelay::get_best_block(), last_block.header.hash());
+    })
+}
+
+#[test]
+fn store_block_headers_non_contiguous_fails_with_index() {
+    let target = U256::from(2).pow(254.into());
+    let miner = Address::try_from("66c7060feb882664ae62ffad0051fe843e318e85").unwrap();
+    let get_header = |block: &Block| RawBlockHeader::from_bytes(&block.header.format()).unwrap();
+
+    run_test(|| {
+        let genesis = BlockBuilder::new()
+            .with_coinbase(&miner, 50, 0)
+            .with_timestamp(1588813835)
+            .mine(target);
+        assert_ok!(BTCRelay::initialize(
+            Origin::signed(3),
+            get_header(&genesis),
+            0
+        ));
+        let first = BlockBuilder::new()
+            .with_coinbase(&miner, 50, 1)
+            .with_previous_hash(genesis.header.hash())
+            .with_timestamp(1588814435)
+            .mine(target);
+        let second = BlockBuilder::new()
+            .with_coinbase(&miner, 50, 2)
+            .with_previous_hash(first.header.hash())
+            .with_timestamp(1588815035)
+            .mine(target);
+        // builds on genesis instead of the preceding header in the batch
+        let orphan = BlockBuilder::new()
+            .with_coinbase(&miner, 50, 3)
+            .with_previous_hash(genesis.header.hash())
+            .with_timestamp(1588814435)
+            .mine(target);
+
+        assert_err!(
+            BTCRelay::store_block_headers(
+                Origin::signed(3),
+                vec![get_header(&first), get_header(&second), get_header(&orphan)]
+            ),
+            Error::InvalidHeaderBatch
+        );
+
+        let failed_event = TestEvent::test_events(Event::StoreBlockHeadersFailed(2));
+        assert!(System::events().iter().any(|a| a.event == failed_event));
+        // headers before the failing index are kept
+        assert_eq!(BTCRelay::get_best_block_height(), 2);
+    })
+}
+
+#[test]
+fn store_block_headers_empty_batch_fails() {
+    run_test(|| {
+        assert_err!(
+            BTCRelay::store_block_headers(Origin::signed(3), vec![]),
+            Error::InvalidHeaderBatch
+        );
+    })
+}
+
+#[test]
+fn store_block_headers_exceeding_max_batch_size_fails() {
+    run_test(|| {
+        let headers = vec![
+            RawBlockHeader::from_hex(sample_raw_genesis_header()).unwrap();
+            crate::MAX_HEADER_BATCH_SIZE + 1
+        ];
+        assert_err!(
+            BTCRelay::store_block_headers(Origin::signed(3), headers),
+            Error::InvalidHeaderBatch
+        );
+    })
+}
+
+#[test]
+fn store_block_headers_weight_scales_with_batch_size() {
+    let headers = vec![RawBlockHeader::from_hex(sample_raw_genesis_header()).unwrap(); 10];
+    let call = crate::Call::<Test>::store_block_headers(headers);
+    assert_eq!(
+        call.get_dispatch_info().weight,
+        10 * crate::STORE_BLOCK_HEADER_WEIGHT
+    );
+}
+
+/// prune_block_headers
+#[test]
+fn prune_block_headers_keeps_retarget_anchors() {
+    run_test(|| {
+        BTCRelay::get_network_params.mock_safe(|| {
+            MockResult::Return(NetworkParams {
+                difficulty_adjustment_interval: 10,
+                ..Network::Mainnet.params()
+            })
+        });
+        crate::PruningDepth::put(20);
+        crate::MaxPrunedPerBlock::put(100);
+
+        let main_chain = store_blockchain_and_random_headers(0, 0, 50, 0);
+        BTCRelay::set_best_block(BTCRelay::get_block_hash(0, 50).unwrap());
+        BTCRelay::set_best_block_height(main_chain.max_height);
+
+        // 30 headers below the pruning height, of which 3 are retarget anchors
+        assert_eq!(BTCRelay::prune_block_headers(), Ok(27));
+
+        for height in 0..30 {
+            assert_eq!(BTCRelay::block_exists(0, height), height % 10 == 0);
+        }
+        for height in 30..=50 {
+            let block_hash = BTCRelay::get_block_hash(0, height).unwrap();
+            assert!(BTCRelay::block_header_exists(block_hash));
+        }
+        assert_eq!(
+            BTCRelay::get_block_chain_from_id(0).unwrap().start_height,
+            30
+        );
+    })
+}
+
+#[test]
+fn prune_block_headers_respects_max_pruned_per_block() {
+    run_test(|| {
+        BTCRelay::get_network_params.mock_safe(|| {
+            MockResult::Return(NetworkParams {
+                difficulty_adjustment_interval: 10,
+                ..Network::Mainnet.params()
+            })
+        });
+        crate::PruningDepth::put(20);
+        crate::MaxPrunedPerBlock::put(5);
+
+        let main_chain = store_blockchain_and_random_headers(0, 0, 50, 0);
+        BTCRelay::set_best_block(BTCRelay::get_block_hash(0, 50).unwrap());
+        BTCRelay::set_best_block_height(main_chain.max_height);
+
+        assert_eq!(BTCRelay::prune_block_headers(), Ok(5));
+
+        assert!(!BTCRelay::block_exists(0, 5));
+        assert!(BTCRelay::block_exists(0, 6));
+        assert_eq!(
+            BTCRelay::get_block_chain_from_id(0).unwrap().start_height,
+            6
+        );
+
+        // pruning continues in the next block
+        assert_eq!(BTCRelay::prune_block_headers(), Ok(5));
+        assert!(!BTCRelay::block_exists(0, 11));
+        assert!(BTCRelay::block_exists(0, 12));
+    })
+}
+
+#[test]
+fn prune_block_headers_removes_stale_forks() {
+    run_test(|| {
+        BTCRelay::get_network_params.mock_safe(|| {
+            MockResult::Return(NetworkParams {
+                difficulty_adjustment_interval: 10,
+                ..Network::Mainnet.params()
+            })
+        });
+        crate::PruningDepth::put(20);
+        crate::MaxPrunedPerBlock::put(100);
+
+        let main_chain = store_blockchain_and_random_headers(0, 0, 50, 0);
+        BTCRelay::set_best_block(BTCRelay::get_block_hash(0, 50).unwrap());
+        BTCRelay::set_best_block_height(main_chain.max_height);
+        let recent_fork = store_blockchain_and_random_headers(1, 40, 45, 1);
+        let stale_fork = store_blockchain_and_random_headers(2, 10, 15, 2);
+        let stale_fork_hash = BTCRelay::get_block_hash(2, 12).unwrap();
+
+        assert_eq!(BTCRelay::prune_block_headers(), Ok(27 + 6));
+
+        assert_err!(
+            BTCRelay::get_block_chain_from_id(stale_fork.chain_id),
+            Error::InvalidChainID
+        );
+        assert_err!(
+            BTCRelay::get_chain_id_from_position(2),
+            Error::InvalidChainID
+        );
+        assert!(!BTCRelay::block_header_exists(stale_fork_hash));
+        assert_eq!(BTCRelay::_blocks_count(stale_fork.chain_id), 0);
+
+        assert_eq!(
+            BTCRelay::get_block_chain_from_id(recent_fork.chain_id),
+            Ok(recent_fork)
+        );
+        assert_eq!(BTCRelay::get_chain_id_from_position(1), Ok(1));
+    })
+}
+
+#[test]
+fn prune_block_headers_removes_stale_forks_at_any_position() {
+    run_test(|| {
+        BTCRelay::get_network_params.mock_safe(|| {
+            MockResult::Return(NetworkParams {
+                difficulty_adjustment_interval: 10,
+                ..Network::Mainnet.params()
+            })
+        });
+        crate::PruningDepth::put(20);
+        crate::MaxPrunedPerBlock::put(100);
+
+        let main_chain = store_blockchain_and_random_headers(0, 0, 50, 0);
+        BTCRelay::set_best_block(BTCRelay::get_block_hash(0, 50).unwrap());
+        BTCRelay::set_best_block_height(main_chain.max_height);
+        // the stale fork is not at the last position of Chains
+        let stale_fork = store_blockchain_and_random_headers(1, 10, 15, 1);
+        let recent_fork = store_blockchain_and_random_headers(2, 40, 45, 2);
+
+        assert_eq!(BTCRelay::prune_block_headers(), Ok(27 + 6));
+
+        assert_err!(
+            BTCRelay::get_block_chain_from_id(stale_fork.chain_id),
+            Error::InvalidChainID
+        );
+        assert_eq!(BTCRelay::_blocks_count(stale_fork.chain_id), 0);
+
+        // the remaining forks are moved up in Chains
+        assert_eq!(BTCRelay::get_chain_id_from_position(1), Ok(2));
+        assert_err!(
+            BTCRelay::get_chain_id_from_position(2),
+            Error::InvalidChainID
+        );
+        assert_eq!(
+            BTCRelay::get_block_chain_from_id(recent_fork.chain_id),
+            Ok(recent_fork)
+        );
+    })
+}
+
+#[test]
+fn prune_block_headers_partially_prunes_stale_fork() {
+    run_test(|| {
+        BTCRelay::get_network_params.mock_safe(|| {
+            MockResult::Return(NetworkParams {
+                difficulty_adjustment_interval: 10,
+                ..Network::Mainnet.params()
+            })
+        });
+        crate::PruningDepth::put(20);
+        crate::MaxPrunedPerBlock::put(30);
+
+        let main_chain = store_blockchain_and_random_headers(0, 0, 50, 0);
+        BTCRelay::set_best_block(BTCRelay::get_block_hash(0, 50).unwrap());
+        BTCRelay::set_best_block_height(main_chain.max_height);
+        let stale_fork = store_blockchain_and_random_headers(1, 10, 15, 1);
+
+        // 27 main chain headers, the budget is left for the 3 highest fork headers
+        assert_eq!(BTCRelay::prune_block_headers(), Ok(30));
+
+        let fork = BTCRelay::get_block_chain_from_id(stale_fork.chain_id).unwrap();
+        assert_eq!(fork.max_height, 12);
+        assert_eq!(fork.chain_work, U256::from(12));
+        assert!(BTCRelay::block_exists(stale_fork.chain_id, 12));
+        assert!(!BTCRelay::block_exists(stale_fork.chain_id, 13));
+        assert_eq!(BTCRelay::get_chain_id_from_position(1), Ok(1));
+    })
+}
+
+#[test]
+fn on_initialize_accounts_weight_of_pruned_headers() {
+    run_test(|| {
+        BTCRelay::get_network_params.mock_safe(|| {
+            MockResult::Return(NetworkParams {
+                difficulty_adjustment_interval: 10,
+                ..Network::Mainnet.params()
+            })
+        });
+        crate::PruningDepth::put(20);
+        crate::MaxPrunedPerBlock::put(5);
+
+        let main_chain = store_blockchain_and_random_headers(0, 0, 50, 0);
+        BTCRelay::set_best_block(BTCRelay::get_block_hash(0, 50).unwrap());
+        BTCRelay::set_best_block_height(main_chain.max_height);
+
+        assert_eq!(
+            BTCRelay::on_initialize(1),
+            5 * crate::PRUNE_BLOCK_HEADER_WEIGHT
+        );
+    })
+}
+
+#[test]
+fn on_initialize_does_not_write_if_pruning_fails() {
+    run_test(|| {
+        BTCRelay::get_network_params.mock_safe(|| {
+            MockResult::Return(NetworkParams {
+                difficulty_adjustment_interval: 10,
+                ..Network::Mainnet.params()
+            })
+        });
+        crate::PruningDepth::put(20);
+        crate::MaxPrunedPerBlock::put(100);
+
+        let main_chain = store_blockchain_and_random_headers(0, 0, 50, 0);
+        BTCRelay::set_best_block(BTCRelay::get_block_hash(0, 50).unwrap());
+        BTCRelay::set_best_block_height(main_chain.max_height);
+        // the fork references a chain which is missing in ChainsIndex
+        BTCRelay::set_chain_from_position_and_id(1, 1);
+
+        assert_eq!(BTCRelay::on_initialize(1), 0);
+
+        let failed_event = TestEvent::test_events(Event::PruneBlockHeadersFailed(50));
+        assert!(System::events().iter().any(|a| a.event == failed_event));
+        assert!(BTCRelay::block_exists(0, 1));
+        assert_eq!(BTCRelay::get_block_chain_from_id(0), Ok(main_chain));
+    })
+}
+
 /// # Util functions
 
+fn sample_batch_commitment(depth: u8, root: H256Le) -> Vec<u8> {
+    let mut commitment = vec![depth];
+    commitment.extend_from_slice(&root.to_bytes_le());
+    commitment
+}
+
 fn sample_valid_proof_result() -> ProofResult {
     let tx_id = H256Le::from_bytes_le(
         &hex::decode("c8589f304d3b9df1d4d8b3d15eb6edaaa2af9d796e9d9ace12b31f293705c5e9".to_owned())
@@ -1694,6 +2756,9 @@ fn get_empty_block_chain_from_chain_id_and_height(
         chain_id: chain_id,
         start_height: start_height,
         max_height: block_height,
+        // let the chain work grow with the height such that forks
+        // are ordered by height as well
+        chain_work: U256::from(block_height),
         no_data: BTreeSet::new(),
         invalid: BTreeSet::new(),
     };
@@ -1745,10 +2810,12 @@ fn store_blockchain_and_random_headers(
             block_header: BlockHeader::from_le_bytes(&block_header).unwrap(),
             block_height: height,
             chain_ref: id,
+            chain_work: U256::from(height),
         };
 
         BTCRelay::set_block_header_from_hash(block_hash, &rich_header);
-        chain = BTCRelay::extend_blockchain(height, &block_hash, chain).unwrap();
+        chain =
+            BTCRelay::extend_blockchain(height, &block_hash, U256::from(height), chain).unwrap();
     }
     // insert the main chain in Chains and ChainsIndex
     BTCRelay::set_chain_from_position_and_id(position, id);
@@ -1757,6 +2824,44 @@ fn store_blockchain_and_random_headers(
     chain
 }
 
+#[derive(Deserialize)]
+struct RetargetBoundary {
+    height: u32,
+    period_start: String,
+    period_end: Option<String>,
+    retarget: String,
+}
+
+fn sample_mainnet_retarget_boundaries() -> Vec<RetargetBoundary> {
+    serde_json::from_str(include_str!(
+        "../../../testdata/mainnet-retarget-headers.json"
+    ))
+    .unwrap()
+}
+
+fn sample_rich_header_with_target(
+    target: U256,
+    timestamp: u32,
+    hash_prev_block: H256Le,
+    block_height: u32,
+) -> RichBlockHeader {
+    let block_header = BlockHeader {
+        merkle_root: H256Le::zero(),
+        target,
+        timestamp,
+        version: 2,
+        hash_prev_block,
+        nonce: 0,
+    };
+    RichBlockHeader {
+        block_hash: block_header.hash(),
+        block_header,
+        block_height,
+        chain_ref: 0,
+        chain_work: U256::zero(),
+    }
+}
+
 fn sample_raw_genesis_header() -> String {
     "01000000".to_owned() + "a7c3299ed2475e1d6ea5ed18d5bfe243224add249cce99c5c67cc9fb00000000601c73862a0a7238e376f497783c8ecca2cf61a4f002ec8898024230787f399cb575d949ffff001d3a5de07f"
 }
@@ -1768,6 +2873,7 @@ fn sample_parsed_genesis_header(chain_ref: u32, block_height: u32) -> RichBlockH
         block_header: parse_block_header(&genesis_header).unwrap(),
         block_height: block_height,
         chain_ref: chain_ref,
+        chain_work: U256::zero(),
     }
 }
 
@@ -1791,6 +2897,7 @@ fn sample_parsed_first_block(chain_ref: u32, block_height: u32) -> RichBlockHead
         block_header: parse_block_header(&block_header).unwrap(),
         block_height: block_height,
         chain_ref: chain_ref,
+        chain_work: U256::zero(),
     }
 }
 
@@ -1838,9 +2945,16 @@ fn sample_rich_tx_block_header(chain_ref: u32, block_height: u32) -> RichBlockHe
         block_header: parse_block_header(&raw_header).unwrap(),
         block_height: block_height,
         chain_ref: chain_ref,
+        chain_work: U256::zero(),
     }
 }
 
+fn sample_recipient_btc_address() -> BtcAddress {
+    BtcAddress::P2SH(H160::from_slice(
+        &hex::decode("66c7060feb882664ae62ffad0051fe843e318e85").unwrap(),
+    ))
+}
+
 fn sample_valid_payment_output() -> TransactionOutput {
     TransactionOutput {
         value: 2500200000,
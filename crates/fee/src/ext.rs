@@ -0,0 +1,57 @@
+#[cfg(test)]
+use mocktopus::macros::mockable;
+
+#[cfg_attr(test, mockable)]
+pub(crate) mod vault_registry {
+    use crate::types::PolkaBTC;
+    use sp_std::vec::Vec;
+
+    pub fn get_vaults_with_issued_tokens<T: vault_registry::Trait>(
+    ) -> Vec<(T::AccountId, PolkaBTC<T>)> {
+        <vault_registry::Module<T>>::_get_vaults_with_issued_tokens()
+    }
+}
+
+#[cfg_attr(test, mockable)]
+pub(crate) mod treasury {
+    use crate::types::PolkaBTC;
+    use x_core::UnitResult;
+
+    pub fn transfer<T: treasury::Trait>(
+        sender: T::AccountId,
+        receiver: T::AccountId,
+        amount: PolkaBTC<T>,
+    ) -> UnitResult {
+        <treasury::Module<T>>::_transfer(sender, receiver, amount)
+    }
+}
+
+#[cfg_attr(test, mockable)]
+pub(crate) mod collateral {
+    use crate::types::DOT;
+    use x_core::UnitResult;
+
+    pub fn slash_collateral<T: collateral::Trait>(
+        sender: &T::AccountId,
+        receiver: &T::AccountId,
+        amount: DOT<T>,
+    ) -> UnitResult {
+        <collateral::Module<T>>::slash_collateral(sender.clone(), receiver.clone(), amount)
+    }
+
+    pub fn release_collateral<T: collateral::Trait>(
+        sender: &T::AccountId,
+        amount: DOT<T>,
+    ) -> UnitResult {
+        <collateral::Module<T>>::release_collateral(sender, amount)
+    }
+}
+
+#[cfg_attr(test, mockable)]
+pub(crate) mod security {
+    use x_core::UnitResult;
+
+    pub fn ensure_parachain_status_not_shutdown<T: security::Trait>() -> UnitResult {
+        <security::Module<T>>::_ensure_parachain_status_not_shutdown()
+    }
+}
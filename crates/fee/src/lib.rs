@@ -0,0 +1,401 @@
+#![deny(warnings)]
+#![cfg_attr(test, feature(proc_macro_hygiene))]
+#![cfg_attr(not(feature = "std"), no_std)]
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(test)]
+extern crate mocktopus;
+
+#[cfg(test)]
+use mocktopus::macros::mockable;
+
+mod ext;
+pub mod types;
+
+use crate::types::{PolkaBTC, DOT};
+/// # PolkaBTC Fee implementation
+/// The Fee module collects a share of each issued and redeemed amount of
+/// PolkaBTC in a fee pool and distributes it to the vaults at the end of
+/// each epoch, in proportion to their issued tokens. DOT paid into the fee
+/// pool, such as slashed griefing collateral, is distributed likewise.
+// Substrate
+use frame_support::{
+    decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure, traits::Get,
+    weights::Weight,
+};
+use sp_runtime::traits::{AccountIdConversion, Zero};
+use sp_runtime::ModuleId;
+use sp_std::convert::TryInto;
+use sp_std::prelude::*;
+use system::{ensure_root, ensure_signed};
+use x_core::{Error, Result, UnitResult};
+
+/// The fee module id, used for deriving the account ID of the fee pool.
+const MODULE_ID: ModuleId = ModuleId(*b"ily/fees");
+
+/// Granularity of `IssueFee` and `RedeemFee`
+pub const GRANULARITY: u32 = 5;
+
+/// Weight of distributing the rewards of an epoch to a single vault
+pub const DISTRIBUTE_REWARD_WEIGHT: Weight = 1000;
+
+/// The pallet's configuration trait.
+pub trait Trait:
+    system::Trait + treasury::Trait + collateral::Trait + vault_registry::Trait
+{
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+    /// The number of blocks after which the fees collected in the current
+    /// epoch are distributed to the vaults.
+    type EpochPeriod: Get<Self::BlockNumber>;
+}
+
+// The pallet's storage items.
+decl_storage! {
+    trait Store for Module<T: Trait> as Fee {
+        /// Share of each issue request paid as a fee in PolkaBTC.
+        /// For example, if the `IssueFee` is set to 500, it is equivalent to 0.5%.
+        IssueFee get(fn issue_fee) config(): u128;
+
+        /// Share of each redeem request paid as a fee in PolkaBTC.
+        /// For example, if the `RedeemFee` is set to 500, it is equivalent to 0.5%.
+        RedeemFee get(fn redeem_fee) config(): u128;
+
+        /// Fees paid into the fee pool during the current epoch, which
+        /// are distributed to the vaults at the end of the epoch.
+        EpochRewards get(fn epoch_rewards): PolkaBTC<T>;
+
+        /// Rewards distributed to a vault that have not yet been withdrawn
+        /// from the fee pool.
+        TotalRewards get(fn total_rewards): map hasher(blake2_128_concat) T::AccountId => PolkaBTC<T>;
+
+        /// DOT paid into the fee pool during the current epoch, which is
+        /// held as collateral of the fee pool until withdrawn by the vaults.
+        EpochDotRewards get(fn epoch_dot_rewards): DOT<T>;
+
+        /// DOT rewards distributed to a vault that have not yet been
+        /// withdrawn from the fee pool.
+        TotalDotRewards get(fn total_dot_rewards): map hasher(blake2_128_concat) T::AccountId => DOT<T>;
+    }
+}
+
+// The pallet's events.
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as system::Trait>::AccountId,
+        PolkaBTC = PolkaBTC<T>,
+        DOT = DOT<T>,
+    {
+        /// total amount distributed to the vaults
+        DistributeRewards(PolkaBTC),
+        /// vault, amount
+        WithdrawRewards(AccountId, PolkaBTC),
+        /// total amount of DOT distributed to the vaults
+        DistributeDotRewards(DOT),
+        /// vault, amount of DOT
+        WithdrawDotRewards(AccountId, DOT),
+    }
+);
+
+// The pallet's dispatchable functions.
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        // Initializing events
+        // this is needed only if you are using events in your pallet
+        fn deposit_event() = default;
+
+        const EpochPeriod: T::BlockNumber = T::EpochPeriod::get();
+
+        /// Withdraw rewards distributed to the vault from the fee pool
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the vault withdrawing its rewards
+        /// * `amount` - amount of PolkaBTC
+        #[weight = 1000]
+        fn withdraw_rewards(origin, amount: PolkaBTC<T>)
+            -> DispatchResult
+        {
+            let vault_id = ensure_signed(origin)?;
+            Self::_withdraw_rewards(vault_id, amount)?;
+            Ok(())
+        }
+
+        /// Withdraw DOT rewards distributed to the vault from the fee pool
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the vault withdrawing its rewards
+        /// * `amount` - amount of DOT
+        #[weight = 1000]
+        fn withdraw_dot_rewards(origin, amount: DOT<T>)
+            -> DispatchResult
+        {
+            let vault_id = ensure_signed(origin)?;
+            Self::_withdraw_dot_rewards(vault_id, amount)?;
+            Ok(())
+        }
+
+        /// Set the share of each issue request paid as a fee
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - must be root
+        /// * `fee` - the new fee, e.g. 500 is equivalent to 0.5%
+        #[weight = 1000]
+        fn set_issue_fee(origin, fee: u128) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::_set_issue_fee(fee)?;
+            Ok(())
+        }
+
+        /// Set the share of each redeem request paid as a fee
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - must be root
+        /// * `fee` - the new fee, e.g. 500 is equivalent to 0.5%
+        #[weight = 1000]
+        fn set_redeem_fee(origin, fee: u128) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::_set_redeem_fee(fee)?;
+            Ok(())
+        }
+
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            Self::begin_block(n)
+        }
+    }
+}
+
+// "Internal" functions, callable by code.
+#[cfg_attr(test, mockable)]
+impl<T: Trait> Module<T> {
+    /// Account holding the fees paid by users until withdrawn by the vaults
+    pub fn fee_pool_account_id() -> T::AccountId {
+        MODULE_ID.into_account()
+    }
+
+    /// Calculates the fee for issuing `amount` of PolkaBTC
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - amount of PolkaBTC to be issued
+    pub fn get_issue_fee(amount: PolkaBTC<T>) -> Result<PolkaBTC<T>> {
+        Self::calculate_fee(amount, <IssueFee>::get())
+    }
+
+    /// Calculates the fee for redeeming `amount` of PolkaBTC
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - amount of PolkaBTC to be redeemed
+    pub fn get_redeem_fee(amount: PolkaBTC<T>) -> Result<PolkaBTC<T>> {
+        Self::calculate_fee(amount, <RedeemFee>::get())
+    }
+
+    /// Adds fees which were paid into the fee pool to the rewards of the
+    /// current epoch
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - amount of PolkaBTC paid into the fee pool
+    pub fn increase_rewards_for_epoch(amount: PolkaBTC<T>) {
+        <EpochRewards<T>>::mutate(|rewards| *rewards += amount);
+    }
+
+    /// Adds DOT which was slashed to the fee pool to the rewards of the
+    /// current epoch
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - amount of DOT held as collateral of the fee pool
+    pub fn increase_dot_rewards_for_epoch(amount: DOT<T>) {
+        <EpochDotRewards<T>>::mutate(|rewards| *rewards += amount);
+    }
+
+    /// Withdraws rewards of a vault from the fee pool
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the vault withdrawing its rewards
+    /// * `amount` - amount of PolkaBTC to withdraw
+    fn _withdraw_rewards(vault_id: T::AccountId, amount: PolkaBTC<T>) -> UnitResult {
+        ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+
+        let rewards = <TotalRewards<T>>::get(&vault_id);
+        ensure!(amount <= rewards, Error::InsufficientFeeRewards);
+
+        ext::treasury::transfer::<T>(Self::fee_pool_account_id(), vault_id.clone(), amount)?;
+        <TotalRewards<T>>::insert(&vault_id, rewards - amount);
+
+        Self::deposit_event(<Event<T>>::WithdrawRewards(vault_id, amount));
+        Ok(())
+    }
+
+    /// Withdraws DOT rewards of a vault from the fee pool, which are paid
+    /// out as free DOT
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the vault withdrawing its rewards
+    /// * `amount` - amount of DOT to withdraw
+    fn _withdraw_dot_rewards(vault_id: T::AccountId, amount: DOT<T>) -> UnitResult {
+        ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+
+        let rewards = <TotalDotRewards<T>>::get(&vault_id);
+        ensure!(amount <= rewards, Error::InsufficientFeeRewards);
+
+        ext::collateral::slash_collateral::<T>(&Self::fee_pool_account_id(), &vault_id, amount)?;
+        ext::collateral::release_collateral::<T>(&vault_id, amount)?;
+        <TotalDotRewards<T>>::insert(&vault_id, rewards - amount);
+
+        Self::deposit_event(<Event<T>>::WithdrawDotRewards(vault_id, amount));
+        Ok(())
+    }
+
+    /// Distributes the rewards at the end of each epoch and returns the
+    /// weight consumed, which scales with the number of vaults.
+    fn begin_block(height: T::BlockNumber) -> Weight {
+        let period = T::EpochPeriod::get();
+        if period.is_zero() || !(height % period).is_zero() {
+            return 0;
+        }
+
+        let vaults = ext::vault_registry::get_vaults_with_issued_tokens::<T>();
+        let weight = DISTRIBUTE_REWARD_WEIGHT.saturating_mul(vaults.len() as Weight);
+        if let Err(e) = Self::update_rewards_for_epoch(vaults) {
+            sp_runtime::print(e.message());
+        }
+        weight
+    }
+
+    /// Distributes the PolkaBTC and DOT rewards of the current epoch to
+    /// the vaults in proportion to their issued tokens. If no vault has
+    /// issued tokens, the rewards are carried over to the next epoch, as is
+    /// the remainder of the division. All rewards are computed before the
+    /// first write.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaults` - the vaults with their issued tokens
+    fn update_rewards_for_epoch(vaults: Vec<(T::AccountId, PolkaBTC<T>)>) -> UnitResult {
+        let epoch_rewards = Self::polkabtc_to_u128(<EpochRewards<T>>::get())?;
+        let epoch_dot_rewards = Self::dot_to_u128(<EpochDotRewards<T>>::get())?;
+        if epoch_rewards == 0 && epoch_dot_rewards == 0 {
+            return Ok(());
+        }
+
+        let mut issued = Vec::with_capacity(vaults.len());
+        let mut total_issued: u128 = 0;
+        for (_, issued_tokens) in vaults.iter() {
+            let issued_tokens = Self::polkabtc_to_u128(*issued_tokens)?;
+            total_issued = total_issued
+                .checked_add(issued_tokens)
+                .ok_or(Error::RuntimeError)?;
+            issued.push(issued_tokens);
+        }
+        if total_issued == 0 {
+            return Ok(());
+        }
+
+        let (rewards, distributed) = Self::calculate_rewards(epoch_rewards, &issued, total_issued)?;
+        let (dot_rewards, distributed_dot) =
+            Self::calculate_rewards(epoch_dot_rewards, &issued, total_issued)?;
+        let rewards = rewards
+            .into_iter()
+            .map(Self::u128_to_polkabtc)
+            .collect::<Result<Vec<_>>>()?;
+        let dot_rewards = dot_rewards
+            .into_iter()
+            .map(Self::u128_to_dot)
+            .collect::<Result<Vec<_>>>()?;
+        let remainder = Self::u128_to_polkabtc(epoch_rewards - distributed)?;
+        let dot_remainder = Self::u128_to_dot(epoch_dot_rewards - distributed_dot)?;
+        let distributed = Self::u128_to_polkabtc(distributed)?;
+        let distributed_dot = Self::u128_to_dot(distributed_dot)?;
+
+        for (((vault_id, _), reward), dot_reward) in vaults.iter().zip(rewards).zip(dot_rewards) {
+            if reward > 0.into() {
+                <TotalRewards<T>>::mutate(vault_id, |rewards| *rewards += reward);
+            }
+            if dot_reward > 0.into() {
+                <TotalDotRewards<T>>::mutate(vault_id, |rewards| *rewards += dot_reward);
+            }
+        }
+        if epoch_rewards > 0 {
+            <EpochRewards<T>>::put(remainder);
+            Self::deposit_event(<Event<T>>::DistributeRewards(distributed));
+        }
+        if epoch_dot_rewards > 0 {
+            <EpochDotRewards<T>>::put(dot_remainder);
+            Self::deposit_event(<Event<T>>::DistributeDotRewards(distributed_dot));
+        }
+        Ok(())
+    }
+
+    /// Splits `epoch_rewards` in proportion to the `issued` tokens of each
+    /// vault and returns the share of each vault and the total distributed
+    fn calculate_rewards(
+        epoch_rewards: u128,
+        issued: &[u128],
+        total_issued: u128,
+    ) -> Result<(Vec<u128>, u128)> {
+        let mut distributed: u128 = 0;
+        let mut rewards = Vec::with_capacity(issued.len());
+        for issued_tokens in issued {
+            let reward = epoch_rewards
+                .checked_mul(*issued_tokens)
+                .ok_or(Error::RuntimeError)?
+                .checked_div(total_issued)
+                .ok_or(Error::RuntimeError)?;
+            distributed += reward;
+            rewards.push(reward);
+        }
+        Ok((rewards, distributed))
+    }
+
+    fn calculate_fee(amount: PolkaBTC<T>, fee: u128) -> Result<PolkaBTC<T>> {
+        let raw_fee = Self::polkabtc_to_u128(amount)?
+            .checked_mul(fee)
+            .ok_or(Error::RuntimeError)?
+            .checked_div(10u128.pow(GRANULARITY))
+            .ok_or(Error::RuntimeError)?;
+        Self::u128_to_polkabtc(raw_fee)
+    }
+
+    fn _set_issue_fee(fee: u128) -> UnitResult {
+        ensure!(fee <= 10u128.pow(GRANULARITY), Error::InvalidFee);
+        <IssueFee>::set(fee);
+        Ok(())
+    }
+
+    fn _set_redeem_fee(fee: u128) -> UnitResult {
+        ensure!(fee <= 10u128.pow(GRANULARITY), Error::InvalidFee);
+        <RedeemFee>::set(fee);
+        Ok(())
+    }
+
+    fn polkabtc_to_u128(x: PolkaBTC<T>) -> Result<u128> {
+        TryInto::<u128>::try_into(x).map_err(|_| Error::RuntimeError)
+    }
+
+    fn u128_to_polkabtc(x: u128) -> Result<PolkaBTC<T>> {
+        TryInto::<PolkaBTC<T>>::try_into(x).map_err(|_| Error::RuntimeError)
+    }
+
+    fn dot_to_u128(x: DOT<T>) -> Result<u128> {
+        TryInto::<u128>::try_into(x).map_err(|_| Error::RuntimeError)
+    }
+
+    fn u128_to_dot(x: u128) -> Result<DOT<T>> {
+        TryInto::<DOT<T>>::try_into(x).map_err(|_| Error::RuntimeError)
+    }
+}
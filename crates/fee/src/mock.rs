@@ -0,0 +1,178 @@
+/// Mocking the test environment
+use crate::{Module, Trait};
+use frame_support::{
+    impl_outer_event, impl_outer_origin, parameter_types,
+    weights::{
+        constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight},
+        Weight,
+    },
+};
+use pallet_balances as balances;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    Perbill,
+};
+
+use mocktopus::mocking::clear_mocks;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+mod test_events {
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        system<T>,
+        test_events<T>,
+        balances<T>,
+        vault_registry<T>,
+        collateral<T>,
+        treasury<T>,
+        exchange_rate_oracle<T>,
+        security,
+    }
+}
+
+// For testing the pallet, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of pallets we want to use.
+
+pub type AccountId = u64;
+pub type Balance = u64;
+pub type BlockNumber = u64;
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: Weight = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+impl system::Trait for Test {
+    type AccountId = AccountId;
+    type Call = ();
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Index = u64;
+    type BlockNumber = BlockNumber;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type Header = Header;
+    type Event = TestEvent;
+    type Origin = Origin;
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type BlockExecutionWeight = BlockExecutionWeight;
+    type DbWeight = RocksDbWeight;
+    type ExtrinsicBaseWeight = ExtrinsicBaseWeight;
+    type Version = ();
+    type ModuleToIndex = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type AccountData = pallet_balances::AccountData<u64>;
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Trait for Test {
+    type Balance = Balance;
+    type Event = TestEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+}
+
+impl vault_registry::Trait for Test {
+    type Event = TestEvent;
+    type OnVaultLiquidated = ();
+}
+
+impl collateral::Trait for Test {
+    type Event = TestEvent;
+    type DOT = Balances;
+}
+
+impl security::Trait for Test {
+    type Event = TestEvent;
+}
+
+impl treasury::Trait for Test {
+    type PolkaBTC = Balances;
+    type Event = TestEvent;
+}
+
+parameter_types! {
+    pub const MinimumPeriod: u64 = 5;
+}
+impl timestamp::Trait for Test {
+    type Moment = u64;
+    type OnTimestampSet = ();
+    type MinimumPeriod = MinimumPeriod;
+}
+
+impl exchange_rate_oracle::Trait for Test {
+    type Event = TestEvent;
+}
+
+parameter_types! {
+    pub const EpochPeriod: BlockNumber = 10;
+}
+impl Trait for Test {
+    type Event = TestEvent;
+    type EpochPeriod = EpochPeriod;
+}
+
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type Fee = Module<Test>;
+
+pub const ALICE: AccountId = 1;
+pub const BOB: AccountId = 2;
+pub const CAROL: AccountId = 3;
+
+pub const ALICE_BALANCE: u64 = 1_000_000;
+pub const BOB_BALANCE: u64 = 1_000_000;
+pub const CAROL_BALANCE: u64 = 1_000_000;
+
+pub struct ExtBuilder;
+
+impl ExtBuilder {
+    pub fn build() -> sp_io::TestExternalities {
+        let mut storage = system::GenesisConfig::default()
+            .build_storage::<Test>()
+            .unwrap();
+
+        pallet_balances::GenesisConfig::<Test> {
+            balances: vec![
+                (ALICE, ALICE_BALANCE),
+                (BOB, BOB_BALANCE),
+                (CAROL, CAROL_BALANCE),
+            ],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
+        storage.into()
+    }
+}
+
+pub fn run_test<T>(test: T) -> ()
+where
+    T: FnOnce() -> (),
+{
+    clear_mocks();
+    ExtBuilder::build().execute_with(|| {
+        System::set_block_number(1);
+        test();
+    });
+}
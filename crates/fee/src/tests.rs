@@ -0,0 +1,189 @@
+use crate::mock::*;
+use crate::RawEvent;
+use crate::{
+    ext, EpochDotRewards, EpochRewards, TotalDotRewards, TotalRewards, DISTRIBUTE_REWARD_WEIGHT,
+};
+use frame_support::{assert_err, assert_noop, assert_ok, StorageMap, StorageValue};
+use mocktopus::mocking::*;
+use sp_runtime::DispatchError;
+use system::RawOrigin;
+use x_core::Error;
+
+fn mock_vaults_with_issued_tokens(vaults: Vec<(AccountId, Balance)>) {
+    ext::vault_registry::get_vaults_with_issued_tokens::<Test>
+        .mock_safe(move || MockResult::Return(vaults.clone()));
+}
+
+fn assert_emitted(event: RawEvent<AccountId, Balance, Balance>) {
+    let test_event = TestEvent::test_events(event);
+    assert!(System::events().iter().any(|a| a.event == test_event));
+}
+
+#[test]
+fn test_get_issue_fee_succeeds() {
+    run_test(|| {
+        assert_ok!(Fee::set_issue_fee(RawOrigin::Root.into(), 500)); // 0.5%
+        assert_eq!(Fee::get_issue_fee(10_000), Ok(50));
+    })
+}
+
+#[test]
+fn test_get_redeem_fee_rounds_down() {
+    run_test(|| {
+        assert_ok!(Fee::set_redeem_fee(RawOrigin::Root.into(), 500)); // 0.5%
+        assert_eq!(Fee::get_redeem_fee(199), Ok(0));
+        assert_eq!(Fee::get_redeem_fee(200), Ok(1));
+    })
+}
+
+#[test]
+fn test_set_fee_requires_root() {
+    run_test(|| {
+        assert_noop!(
+            Fee::set_issue_fee(Origin::signed(ALICE), 500),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Fee::set_redeem_fee(Origin::signed(ALICE), 500),
+            DispatchError::BadOrigin
+        );
+    })
+}
+
+#[test]
+fn test_set_fee_above_100_percent_fails() {
+    run_test(|| {
+        assert_ok!(Fee::set_issue_fee(RawOrigin::Root.into(), 100_000));
+        assert_err!(
+            Fee::set_issue_fee(RawOrigin::Root.into(), 100_001),
+            Error::InvalidFee
+        );
+        assert_err!(
+            Fee::set_redeem_fee(RawOrigin::Root.into(), 100_001),
+            Error::InvalidFee
+        );
+        assert_eq!(Fee::issue_fee(), 100_000);
+    })
+}
+
+#[test]
+fn test_update_rewards_for_epoch_distributes_by_issued_tokens() {
+    run_test(|| {
+        mock_vaults_with_issued_tokens(vec![(ALICE, 30), (BOB, 10)]);
+        Fee::increase_rewards_for_epoch(101);
+
+        assert_eq!(Fee::begin_block(10), 2 * DISTRIBUTE_REWARD_WEIGHT);
+
+        assert_eq!(<TotalRewards<Test>>::get(ALICE), 75);
+        assert_eq!(<TotalRewards<Test>>::get(BOB), 25);
+        // the remainder is carried over to the next epoch
+        assert_eq!(<EpochRewards<Test>>::get(), 1);
+        assert_emitted(RawEvent::DistributeRewards(100));
+    })
+}
+
+#[test]
+fn test_update_rewards_for_epoch_distributes_dot_by_issued_tokens() {
+    run_test(|| {
+        mock_vaults_with_issued_tokens(vec![(ALICE, 30), (BOB, 10)]);
+        Fee::increase_dot_rewards_for_epoch(41);
+
+        assert_eq!(Fee::begin_block(10), 2 * DISTRIBUTE_REWARD_WEIGHT);
+
+        assert_eq!(<TotalDotRewards<Test>>::get(ALICE), 30);
+        assert_eq!(<TotalDotRewards<Test>>::get(BOB), 10);
+        assert_eq!(<EpochDotRewards<Test>>::get(), 1);
+        assert_eq!(<TotalRewards<Test>>::get(ALICE), 0);
+        assert_emitted(RawEvent::DistributeDotRewards(40));
+    })
+}
+
+#[test]
+fn test_begin_block_within_epoch_does_not_distribute() {
+    run_test(|| {
+        mock_vaults_with_issued_tokens(vec![(ALICE, 30)]);
+        Fee::increase_rewards_for_epoch(100);
+
+        assert_eq!(Fee::begin_block(5), 0);
+
+        assert_eq!(<TotalRewards<Test>>::get(ALICE), 0);
+        assert_eq!(<EpochRewards<Test>>::get(), 100);
+    })
+}
+
+#[test]
+fn test_update_rewards_for_epoch_without_issued_tokens_carries_over() {
+    run_test(|| {
+        mock_vaults_with_issued_tokens(vec![]);
+        Fee::increase_rewards_for_epoch(100);
+
+        assert_eq!(Fee::begin_block(10), 0);
+
+        assert_eq!(<EpochRewards<Test>>::get(), 100);
+    })
+}
+
+#[test]
+fn test_withdraw_rewards_succeeds() {
+    run_test(|| {
+        ext::security::ensure_parachain_status_not_shutdown::<Test>
+            .mock_safe(|| MockResult::Return(Ok(())));
+        <treasury::Module<Test>>::mint(Fee::fee_pool_account_id(), 100);
+        <TotalRewards<Test>>::insert(ALICE, 75);
+
+        assert_ok!(Fee::withdraw_rewards(Origin::signed(ALICE), 75));
+
+        assert_eq!(<TotalRewards<Test>>::get(ALICE), 0);
+        assert_eq!(Balances::free_balance(ALICE), ALICE_BALANCE + 75);
+        assert_eq!(Balances::free_balance(Fee::fee_pool_account_id()), 25);
+        assert_emitted(RawEvent::WithdrawRewards(ALICE, 75));
+    })
+}
+
+#[test]
+fn test_withdraw_rewards_exceeding_rewards_fails() {
+    run_test(|| {
+        ext::security::ensure_parachain_status_not_shutdown::<Test>
+            .mock_safe(|| MockResult::Return(Ok(())));
+        <treasury::Module<Test>>::mint(Fee::fee_pool_account_id(), 100);
+        <TotalRewards<Test>>::insert(ALICE, 75);
+
+        assert_noop!(
+            Fee::withdraw_rewards(Origin::signed(ALICE), 76),
+            Error::InsufficientFeeRewards
+        );
+    })
+}
+
+#[test]
+fn test_withdraw_dot_rewards_pays_free_balance() {
+    run_test(|| {
+        ext::security::ensure_parachain_status_not_shutdown::<Test>
+            .mock_safe(|| MockResult::Return(Ok(())));
+        // griefing collateral is slashed to the fee pool as collateral
+        let fee_pool = Fee::fee_pool_account_id();
+        assert_ok!(<collateral::Module<Test>>::lock_collateral(&CAROL, 100));
+        assert_ok!(<collateral::Module<Test>>::slash_collateral(
+            CAROL, fee_pool, 100
+        ));
+        <TotalDotRewards<Test>>::insert(ALICE, 75);
+
+        assert_noop!(
+            Fee::withdraw_dot_rewards(Origin::signed(ALICE), 76),
+            Error::InsufficientFeeRewards
+        );
+        assert_ok!(Fee::withdraw_dot_rewards(Origin::signed(ALICE), 75));
+
+        assert_eq!(<TotalDotRewards<Test>>::get(ALICE), 0);
+        assert_eq!(Balances::free_balance(ALICE), ALICE_BALANCE + 75);
+        assert_eq!(
+            <collateral::Module<Test>>::get_collateral_from_account(&ALICE),
+            0
+        );
+        assert_eq!(
+            <collateral::Module<Test>>::get_collateral_from_account(&fee_pool),
+            25
+        );
+        assert_emitted(RawEvent::WithdrawDotRewards(ALICE, 75));
+    })
+}
@@ -0,0 +1,31 @@
+//! Runtime API definition for the Issue module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use bitcoin::types::H256Le;
+use codec::Codec;
+use sp_core::H256;
+use sp_std::prelude::*;
+use x_core::Error;
+
+sp_api::decl_runtime_apis! {
+    pub trait IssueApi<AccountId> where
+        AccountId: Codec,
+    {
+        /// Checks the Bitcoin transaction of a issue request against the current
+        /// state without executing the request
+        fn verify_and_validate_transaction(
+            issue_id: H256,
+            tx_id: H256Le,
+            tx_block_height: u32,
+            merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+        ) -> Result<(), Error>;
+
+        /// Returns the ids of the open issue requests of the requester
+        fn get_issue_requests(account_id: AccountId) -> Vec<H256>;
+
+        /// Returns the ids of the open issue requests assigned to the vault
+        fn get_vault_issue_requests(vault_id: AccountId) -> Vec<H256>;
+    }
+}
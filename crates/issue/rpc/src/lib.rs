@@ -0,0 +1,140 @@
+//! RPC interface for the Issue module.
+
+use bitcoin::types::H256Le;
+use codec::Codec;
+pub use issue_rpc_runtime_api::IssueApi as IssueRuntimeApi;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{Bytes, H256};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::sync::Arc;
+
+#[rpc]
+pub trait IssueApi<BlockHash, AccountId> {
+    /// Dry-runs the transaction checks of `execute_issue` and fails with
+    /// the error the extrinsic would fail with.
+    #[rpc(name = "issue_verifyAndValidateTransaction")]
+    fn verify_and_validate_transaction(
+        &self,
+        issue_id: H256,
+        tx_id: H256Le,
+        tx_block_height: u32,
+        merkle_proof: Bytes,
+        raw_tx: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<()>;
+
+    /// Returns the ids of the open issue requests of the requester
+    #[rpc(name = "issue_getIssueRequests")]
+    fn get_issue_requests(&self, account_id: AccountId, at: Option<BlockHash>)
+        -> Result<Vec<H256>>;
+
+    /// Returns the ids of the open issue requests assigned to the vault
+    #[rpc(name = "issue_getVaultIssueRequests")]
+    fn get_vault_issue_requests(
+        &self,
+        vault_id: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<H256>>;
+}
+
+/// A struct that implements the [`IssueApi`].
+pub struct Issue<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Issue<C, B> {
+    /// Create new `Issue` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Issue {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+/// Error code returned when the runtime API call fails.
+const RUNTIME_ERROR: i64 = 1;
+/// Error code returned when the checked transaction is rejected.
+const TRANSACTION_ERROR: i64 = 2;
+
+fn runtime_error<E: std::fmt::Debug>(message: &str, error: E) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(RUNTIME_ERROR),
+        message: message.into(),
+        data: Some(format!("{:?}", error).into()),
+    }
+}
+
+impl<C, Block> Issue<C, Block>
+where
+    Block: BlockT,
+    C: HeaderBackend<Block>,
+{
+    /// Returns the given block or, if not specified, the best block
+    fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId<Block> {
+        // if the block hash is not supplied assume the best block
+        BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash))
+    }
+}
+
+impl<C, Block, AccountId> IssueApi<<Block as BlockT>::Hash, AccountId> for Issue<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: IssueRuntimeApi<Block, AccountId>,
+    AccountId: Codec,
+{
+    fn verify_and_validate_transaction(
+        &self,
+        issue_id: H256,
+        tx_id: H256Le,
+        tx_block_height: u32,
+        merkle_proof: Bytes,
+        raw_tx: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<()> {
+        let api = self.client.runtime_api();
+        let at = self.block_id(at);
+
+        api.verify_and_validate_transaction(
+            &at,
+            issue_id,
+            tx_id,
+            tx_block_height,
+            merkle_proof.to_vec(),
+            raw_tx.to_vec(),
+        )
+        .map_err(|e| runtime_error("Unable to check the transaction.", e))?
+        .map_err(|e| RpcError {
+            code: ErrorCode::ServerError(TRANSACTION_ERROR),
+            message: e.message().into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn get_issue_requests(
+        &self,
+        account_id: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<H256>> {
+        self.client
+            .runtime_api()
+            .get_issue_requests(&self.block_id(at), account_id)
+            .map_err(|e| runtime_error("Unable to query the issue requests.", e))
+    }
+
+    fn get_vault_issue_requests(
+        &self,
+        vault_id: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<H256>> {
+        self.client
+            .runtime_api()
+            .get_vault_issue_requests(&self.block_id(at), vault_id)
+            .map_err(|e| runtime_error("Unable to query the issue requests.", e))
+    }
+}
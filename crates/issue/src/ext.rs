@@ -3,9 +3,10 @@ use mocktopus::macros::mockable;
 
 #[cfg_attr(test, mockable)]
 pub(crate) mod btc_relay {
+    use bitcoin::address::BtcAddress;
     use bitcoin::types::H256Le;
     use sp_std::vec::Vec;
-    use x_core::UnitResult;
+    use x_core::{Result, UnitResult};
 
     pub fn verify_transaction_inclusion<T: btc_relay::Trait>(
         tx_id: H256Le,
@@ -24,9 +25,9 @@ pub(crate) mod btc_relay {
     pub fn validate_transaction<T: btc_relay::Trait>(
         raw_tx: Vec<u8>,
         amount: i64,
-        btc_address: Vec<u8>,
+        btc_address: BtcAddress,
         issue_id: Vec<u8>,
-    ) -> UnitResult {
+    ) -> Result<i64> {
         <btc_relay::Module<T>>::_validate_transaction(raw_tx, amount, btc_address, issue_id)
     }
 }
@@ -34,7 +35,8 @@ pub(crate) mod btc_relay {
 #[cfg_attr(test, mockable)]
 pub(crate) mod vault_registry {
     use crate::types::PolkaBTC;
-    use sp_core::H160;
+    use bitcoin::address::BtcAddress;
+    use sp_std::vec::Vec;
     use x_core::{Result, UnitResult};
 
     pub fn get_vault_from_id<T: vault_registry::Trait>(
@@ -43,10 +45,28 @@ pub(crate) mod vault_registry {
         <vault_registry::Module<T>>::_get_vault_from_id(vault_id)
     }
 
+    pub fn get_vault_with_sufficient_collateral<T: vault_registry::Trait>(
+        amount: PolkaBTC<T>,
+    ) -> Result<T::AccountId> {
+        <vault_registry::Module<T>>::_get_vault_with_sufficient_collateral(amount)
+    }
+
+    pub fn get_vaults_for_split_issue<T: vault_registry::Trait>(
+        amount: PolkaBTC<T>,
+    ) -> Result<Vec<(T::AccountId, PolkaBTC<T>)>> {
+        <vault_registry::Module<T>>::_get_vaults_for_split_issue(amount)
+    }
+
+    pub fn get_issuable_tokens_from_vault<T: vault_registry::Trait>(
+        vault_id: &T::AccountId,
+    ) -> Result<PolkaBTC<T>> {
+        <vault_registry::Module<T>>::_get_issuable_tokens_from_vault(vault_id)
+    }
+
     pub fn increase_to_be_issued_tokens<T: vault_registry::Trait>(
         vault_id: &T::AccountId,
         amount: PolkaBTC<T>,
-    ) -> Result<H160> {
+    ) -> Result<BtcAddress> {
         <vault_registry::Module<T>>::_increase_to_be_issued_tokens(vault_id, amount)
     }
 
@@ -84,6 +104,17 @@ pub(crate) mod collateral {
         <collateral::Module<T>>::lock_collateral(sender, amount)
     }
 
+    pub fn release_collateral<T: collateral::Trait>(
+        sender: &T::AccountId,
+        amount: DOT<T>,
+    ) -> UnitResult {
+        <collateral::Module<T>>::release_collateral(sender, amount)
+    }
+
+    pub fn get_collateral_from_account<T: collateral::Trait>(account: &T::AccountId) -> DOT<T> {
+        <collateral::Module<T>>::get_collateral_from_account(account)
+    }
+
     pub fn slash_collateral<T: collateral::Trait>(
         sender: &T::AccountId,
         receiver: &T::AccountId,
@@ -102,6 +133,38 @@ pub(crate) mod treasury {
     }
 }
 
+#[cfg_attr(test, mockable)]
+pub(crate) mod fee {
+    use crate::types::{PolkaBTC, DOT};
+    use x_core::Result;
+
+    pub fn fee_pool_account_id<T: fee::Trait>() -> T::AccountId {
+        <fee::Module<T>>::fee_pool_account_id()
+    }
+
+    pub fn get_issue_fee<T: fee::Trait>(amount: PolkaBTC<T>) -> Result<PolkaBTC<T>> {
+        <fee::Module<T>>::get_issue_fee(amount)
+    }
+
+    pub fn increase_rewards_for_epoch<T: fee::Trait>(amount: PolkaBTC<T>) {
+        <fee::Module<T>>::increase_rewards_for_epoch(amount)
+    }
+
+    pub fn increase_dot_rewards_for_epoch<T: fee::Trait>(amount: DOT<T>) {
+        <fee::Module<T>>::increase_dot_rewards_for_epoch(amount)
+    }
+}
+
+#[cfg_attr(test, mockable)]
+pub(crate) mod oracle {
+    use crate::types::{PolkaBTC, DOT};
+    use x_core::Result;
+
+    pub fn btc_to_dots<T: exchange_rate_oracle::Trait>(amount: PolkaBTC<T>) -> Result<DOT<T>> {
+        <exchange_rate_oracle::Module<T>>::btc_to_dots(amount)
+    }
+}
+
 #[cfg_attr(test, mockable)]
 pub(crate) mod security {
     use primitive_types::H256;
@@ -17,6 +17,7 @@ mod ext;
 pub mod types;
 
 use crate::types::{PolkaBTC, DOT};
+use bitcoin::address::BtcAddress;
 use bitcoin::types::H256Le;
 use codec::{Decode, Encode};
 /// # PolkaBTC Issue implementation
@@ -25,21 +26,39 @@ use codec::{Decode, Encode};
 // Substrate
 use frame_support::{
     decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure, traits::Get,
+    weights::Weight,
 };
 use primitive_types::H256;
-use sp_core::H160;
-use sp_runtime::ModuleId;
+use sp_runtime::{
+    traits::{One, Zero},
+    ModuleId,
+};
 use sp_std::convert::TryInto;
 use sp_std::vec::Vec;
 use system::ensure_signed;
-use x_core::Error;
+use x_core::{index, Error};
 
 /// The issue module id, used for deriving its sovereign account ID.
 const _MODULE_ID: ModuleId = ModuleId(*b"issuemod");
 
+/// The weight of cancelling a single expired issue request.
+const EXPIRE_ISSUE_WEIGHT: Weight = 1000;
+
+/// The weight of examining a single vault when selecting the vault of an
+/// issue request.
+const SELECT_VAULT_WEIGHT: Weight = 1000;
+
+/// Granularity of `IssueGriefingCollateralRate` and `GriefingCollateralFeeShare`
+pub const GRANULARITY: u32 = 5;
+
 /// The pallet's configuration trait.
 pub trait Trait:
-    system::Trait + vault_registry::Trait + collateral::Trait + btc_relay::Trait + treasury::Trait
+    system::Trait
+    + vault_registry::Trait
+    + collateral::Trait
+    + btc_relay::Trait
+    + treasury::Trait
+    + fee::Trait
 {
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
@@ -48,6 +67,16 @@ pub trait Trait:
     /// and required completion time by a user. The issue period has an upper limit
     /// to prevent griefing of vault collateral.
     type IssuePeriod: Get<Self::BlockNumber>;
+
+    /// The time difference in number of blocks between the requester setting
+    /// the BTC address of a refund request and the deadline for the vault to
+    /// return the BTC. After the deadline, the requester can cancel the
+    /// refund request and is compensated from the vault's collateral.
+    type RefundPeriod: Get<Self::BlockNumber>;
+
+    /// The maximum number of expired issue requests which are cancelled
+    /// automatically at the start of a block.
+    type MaxExpiredIssuesPerBlock: Get<u32>;
 }
 
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
@@ -57,9 +86,23 @@ pub struct Issue<AccountId, BlockNumber, PolkaBTC, DOT> {
     opentime: BlockNumber,
     griefing_collateral: DOT,
     amount: PolkaBTC,
+    fee: PolkaBTC,
     requester: AccountId,
-    btc_address: H160,
+    btc_address: BtcAddress,
     completed: bool,
+    parent: Option<H256>,
+}
+
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Refund<AccountId, BlockNumber, PolkaBTC> {
+    vault: AccountId,
+    // the refund period starts once the requester has set the BTC address
+    opentime: BlockNumber,
+    requester: AccountId,
+    amount_btc: PolkaBTC,
+    issue_id: H256,
+    btc_address: Option<BtcAddress>,
 }
 
 // The pallet's storage items.
@@ -68,9 +111,44 @@ decl_storage! {
         /// The minimum collateral (DOT) a user needs to provide as griefing protection.
         IssueGriefingCollateral: DOT<T>;
 
+        /// Share of the requested amount, converted to DOT, a user needs to provide
+        /// as griefing protection, but at least `IssueGriefingCollateral`.
+        /// For example, if the `IssueGriefingCollateralRate` is set to 5000, it is equivalent to 5%.
+        IssueGriefingCollateralRate get(fn issue_griefing_collateral_rate) config(): u128;
+
+        /// Share of the griefing collateral of a cancelled issue request which
+        /// is paid into the fee pool, the rest is paid to the vault.
+        /// For example, if the `GriefingCollateralFeeShare` is set to 10000, it is equivalent to 10%.
+        GriefingCollateralFeeShare get(fn griefing_collateral_fee_share) config(): u128;
+
         /// Users create issue requests to issue PolkaBTC. This mapping provides access
         /// from a unique hash `IssueId` to an `Issue` struct.
         IssueRequests: map hasher(blake2_128_concat) H256 => Issue<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>;
+
+        /// Issue requests split across multiple vaults are tracked under a
+        /// parent id. This mapping provides access from the parent id to the
+        /// ids of the parts which have not yet been executed or cancelled.
+        IssueGroups: map hasher(blake2_128_concat) H256 => Vec<H256>;
+
+        /// Ids of the open issue requests of each requester
+        IssueRequestsByRequester: map hasher(blake2_128_concat) T::AccountId => Vec<H256>;
+
+        /// Ids of the open issue requests assigned to each vault
+        IssueRequestsByVault: map hasher(blake2_128_concat) T::AccountId => Vec<H256>;
+
+        /// BTC paid for an issue request beyond what the vault could back is
+        /// returned by the vault. This mapping provides access from a unique
+        /// hash `RefundId` to a `Refund` struct.
+        RefundRequests: map hasher(blake2_128_concat) H256 => Refund<T::AccountId, T::BlockNumber, PolkaBTC<T>>;
+
+        /// Ids of the issue requests expiring at each block, which are
+        /// cancelled automatically once the block is reached.
+        IssueExpiryQueue: map hasher(blake2_128_concat) T::BlockNumber => Vec<H256>;
+
+        /// The next block of the `IssueExpiryQueue` to be processed. It is
+        /// behind the current block if more requests expired than could be
+        /// cancelled in a single block.
+        NextIssueExpiry: T::BlockNumber;
     }
 }
 
@@ -80,10 +158,31 @@ decl_event!(
     where
         AccountId = <T as system::Trait>::AccountId,
         PolkaBTC = PolkaBTC<T>,
+        DOT = DOT<T>,
     {
-        RequestIssue(H256, AccountId, PolkaBTC, AccountId, H160),
+        RequestIssue(H256, AccountId, PolkaBTC, AccountId, BtcAddress),
+        /// parent id, requester, total amount, (issue id, vault, amount, btc address) of each part
+        RequestIssueSplit(
+            H256,
+            AccountId,
+            PolkaBTC,
+            Vec<(H256, AccountId, PolkaBTC, BtcAddress)>,
+        ),
         ExecuteIssue(H256, AccountId, AccountId),
-        CancelIssue(H256, AccountId),
+        /// refund id, requester, amount of BTC, vault, issue id
+        RequestRefund(H256, AccountId, PolkaBTC, AccountId, H256),
+        /// refund id, btc address
+        SetRefundAddress(H256, BtcAddress),
+        /// refund id, vault
+        ExecuteRefund(H256, AccountId),
+        /// refund id, requester, vault, collateral paid to the requester
+        CancelRefund(H256, AccountId, AccountId, DOT),
+        /// issue id, sender, griefing collateral paid to the vault, griefing collateral paid to the fee pool
+        CancelIssue(H256, AccountId, DOT, DOT),
+        /// issue id, griefing collateral paid to the vault, griefing collateral paid to the fee pool
+        ExpireIssue(H256, DOT, DOT),
+        /// issue id
+        ExpireIssueFailed(H256),
     }
 );
 
@@ -97,12 +196,14 @@ decl_module! {
 
         const IssuePeriod: T::BlockNumber = T::IssuePeriod::get();
 
+        const MaxExpiredIssuesPerBlock: u32 = T::MaxExpiredIssuesPerBlock::get();
+
         /// Request the issuance of PolkaBTC
         ///
         /// # Arguments
         ///
         /// * `origin` - sender of the transaction
-        /// * `amount` - amount of PolkaBTC
+        /// * `amount` - amount of PolkaBTC, including the issue fee
         /// * `vault` - address of the vault
         /// * `griefing_collateral` - amount of DOT
         #[weight = 1000]
@@ -114,6 +215,44 @@ decl_module! {
             Ok(())
         }
 
+        /// Request the issuance of PolkaBTC from a vault selected by the
+        /// vault registry
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - sender of the transaction
+        /// * `amount` - amount of PolkaBTC, including the issue fee
+        /// * `griefing_collateral` - amount of DOT
+        #[weight = 1000 + SELECT_VAULT_WEIGHT.saturating_mul(vault_registry::MAX_VAULTS_EXAMINED as Weight)]
+        fn request_issue_auto(origin, amount: PolkaBTC<T>, griefing_collateral: DOT<T>)
+            -> DispatchResult
+        {
+            let requester = ensure_signed(origin)?;
+            let vault_id = ext::vault_registry::get_vault_with_sufficient_collateral::<T>(amount)?;
+            Self::_request_issue(requester, amount, vault_id, griefing_collateral)?;
+            Ok(())
+        }
+
+        /// Request the issuance of PolkaBTC split across as many vaults as
+        /// needed to back the amount. Each part is a separate issue request
+        /// which is executed or cancelled on its own.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - sender of the transaction
+        /// * `amount` - amount of PolkaBTC, including the issue fee
+        /// * `griefing_collateral` - amount of DOT, split across the parts
+        ///
+        /// Every vault examined may become a part with the weight of `request_issue`.
+        #[weight = (1000 + SELECT_VAULT_WEIGHT).saturating_mul(vault_registry::MAX_VAULTS_EXAMINED as Weight)]
+        fn request_issue_split(origin, amount: PolkaBTC<T>, griefing_collateral: DOT<T>)
+            -> DispatchResult
+        {
+            let requester = ensure_signed(origin)?;
+            Self::_request_issue_split(requester, amount, griefing_collateral)?;
+            Ok(())
+        }
+
         /// Finalize the issuance of PolkaBTC
         ///
         /// # Arguments
@@ -133,6 +272,58 @@ decl_module! {
             Ok(())
         }
 
+        /// Set the BTC address to which the vault returns the BTC paid for an
+        /// issue request beyond what the vault could back
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - requester of the issue request
+        /// * `refund_id` - identifier of the refund request
+        /// * `btc_address` - BTC address of the requester
+        #[weight = 1000]
+        fn set_refund_address(origin, refund_id: H256, btc_address: BtcAddress)
+            -> DispatchResult
+        {
+            let requester = ensure_signed(origin)?;
+            Self::_set_refund_address(requester, refund_id, btc_address)?;
+            Ok(())
+        }
+
+        /// Finalize a refund after the vault has returned the BTC
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - vault of the refund request
+        /// * `refund_id` - identifier of the refund request
+        /// * `tx_id` - transaction hash
+        /// * `tx_block_height` - block number of backing chain
+        /// * `merkle_proof` - raw bytes
+        /// * `raw_tx` - raw bytes
+        #[weight = 1000]
+        fn execute_refund(origin, refund_id: H256, tx_id: H256Le, tx_block_height: u32, merkle_proof: Vec<u8>, raw_tx: Vec<u8>)
+            -> DispatchResult
+        {
+            let vault_id = ensure_signed(origin)?;
+            Self::_execute_refund(vault_id, refund_id, tx_id, tx_block_height, merkle_proof, raw_tx)?;
+            Ok(())
+        }
+
+        /// Cancel a refund the vault has not completed within the refund
+        /// period, slashing the vault's collateral
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - requester of the refund request
+        /// * `refund_id` - identifier of the refund request
+        #[weight = 1000]
+        fn cancel_refund(origin, refund_id: H256)
+            -> DispatchResult
+        {
+            let requester = ensure_signed(origin)?;
+            Self::_cancel_refund(requester, refund_id)?;
+            Ok(())
+        }
+
         /// Cancel the issuance of PolkaBTC if expired
         ///
         /// # Arguments
@@ -147,6 +338,11 @@ decl_module! {
             Self::_cancel_issue(requester, issue_id)?;
             Ok(())
         }
+
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            let expired = Self::begin_block(n);
+            EXPIRE_ISSUE_WEIGHT.saturating_mul(expired as Weight)
+        }
     }
 }
 
@@ -169,16 +365,121 @@ impl<T: Trait> Module<T> {
         ext::vault_registry::ensure_not_banned::<T>(&vault_id, height)?;
 
         ensure!(
-            griefing_collateral >= <IssueGriefingCollateral<T>>::get(),
+            griefing_collateral >= Self::get_required_griefing_collateral(amount)?,
             Error::InsufficientCollateral
         );
+        index::ensure_capacity::<IssueRequestsByRequester<T>, _, _>(&requester, 1)?;
+
+        ext::collateral::lock_collateral::<T>(&requester, griefing_collateral)?;
+
+        let (key, amount_user, btc_address) = Self::create_issue_request(
+            &requester,
+            amount,
+            &vault_id,
+            griefing_collateral,
+            height,
+            None,
+        )?;
+
+        Self::deposit_event(<Event<T>>::RequestIssue(
+            key,
+            requester,
+            amount_user,
+            vault_id,
+            btc_address,
+        ));
+        Ok(key)
+    }
+
+    /// Requests CBA issuance split across multiple vaults, returns the
+    /// parent id under which the parts are tracked.
+    fn _request_issue_split(
+        requester: T::AccountId,
+        amount: PolkaBTC<T>,
+        griefing_collateral: DOT<T>,
+    ) -> Result<H256, Error> {
+        // Check that Parachain is RUNNING
+        ext::security::ensure_parachain_status_running::<T>()?;
+
+        let height = <system::Module<T>>::block_number();
+        let vaults = ext::vault_registry::get_vaults_for_split_issue::<T>(amount)?;
+
+        // the griefing collateral is split in proportion to the amount of
+        // each part, the last part receives the remainder of the division
+        let raw_amount = Self::polkabtc_to_u128(amount)?;
+        let raw_griefing_collateral = Self::dot_to_u128(griefing_collateral)?;
+        let mut raw_remaining_collateral = raw_griefing_collateral;
+        let count = vaults.len();
+        let mut parts = Vec::new();
+        for (i, (vault_id, part_amount)) in vaults.into_iter().enumerate() {
+            let raw_part_collateral = if i + 1 == count {
+                raw_remaining_collateral
+            } else {
+                raw_griefing_collateral
+                    .checked_mul(Self::polkabtc_to_u128(part_amount)?)
+                    .ok_or(Error::RuntimeError)?
+                    .checked_div(raw_amount)
+                    .ok_or(Error::RuntimeError)?
+            };
+            raw_remaining_collateral -= raw_part_collateral;
+            let part_collateral = Self::u128_to_dot(raw_part_collateral)?;
+            ensure!(
+                part_collateral >= Self::get_required_griefing_collateral(part_amount)?,
+                Error::InsufficientCollateral
+            );
+            parts.push((vault_id, part_amount, part_collateral));
+        }
+        index::ensure_capacity::<IssueRequestsByRequester<T>, _, _>(&requester, count)?;
 
         ext::collateral::lock_collateral::<T>(&requester, griefing_collateral)?;
 
-        let btc_address =
-            ext::vault_registry::increase_to_be_issued_tokens::<T>(&vault_id, amount)?;
+        let parent = ext::security::get_secure_id::<T>(&requester);
+        let mut ids = Vec::new();
+        let mut issues = Vec::new();
+        let mut total_amount_user: PolkaBTC<T> = 0.into();
+        for (vault_id, part_amount, part_collateral) in parts {
+            let (key, amount_user, btc_address) = Self::create_issue_request(
+                &requester,
+                part_amount,
+                &vault_id,
+                part_collateral,
+                height,
+                Some(parent),
+            )?;
+            total_amount_user += amount_user;
+            ids.push(key);
+            issues.push((key, vault_id, amount_user, btc_address));
+        }
+        <IssueGroups>::insert(parent, ids);
+
+        Self::deposit_event(<Event<T>>::RequestIssueSplit(
+            parent,
+            requester,
+            total_amount_user,
+            issues,
+        ));
+        Ok(parent)
+    }
+
+    /// Increases the to-be-issued tokens of the vault and stores a new
+    /// issue request. Returns the issue id, the amount of PolkaBTC issued
+    /// to the user and the BTC address the user has to pay to.
+    fn create_issue_request(
+        requester: &T::AccountId,
+        amount: PolkaBTC<T>,
+        vault_id: &T::AccountId,
+        griefing_collateral: DOT<T>,
+        height: T::BlockNumber,
+        parent: Option<H256>,
+    ) -> Result<(H256, PolkaBTC<T>, BtcAddress), Error> {
+        // the fee is paid out of the requested amount, which is fully
+        // backed by the vault
+        let fee = ext::fee::get_issue_fee::<T>(amount)?;
+        let amount_user = amount - fee;
+
+        let btc_address = ext::vault_registry::increase_to_be_issued_tokens::<T>(vault_id, amount)?;
 
-        let key = ext::security::get_secure_id::<T>(&requester);
+        let key = ext::security::get_secure_id::<T>(requester);
 
         Self::insert_issue_request(
             key,
@@ -186,21 +487,15 @@ impl<T: Trait> Module<T> {
                 vault: vault_id.clone(),
                 opentime: height,
                 griefing_collateral: griefing_collateral,
-                amount: amount,
+                amount: amount_user,
+                fee,
                 requester: requester.clone(),
                 btc_address: btc_address,
                 completed: false,
+                parent,
             },
         );
-
-        Self::deposit_event(<Event<T>>::RequestIssue(
-            key,
-            requester,
-            amount,
-            vault_id,
-            btc_address,
-        ));
-        Ok(key)
+        Ok((key, amount_user, btc_address))
     }
 
     /// Completes CBA issuance, removing request from storage and minting token.
@@ -218,51 +513,432 @@ impl<T: Trait> Module<T> {
         let issue = Self::get_issue_request_from_id(&issue_id)?;
         ensure!(requester == issue.requester, Error::UnauthorizedUser);
 
+        let amount_paid = Self::verify_and_validate_transaction(
+            issue_id,
+            &issue,
+            tx_id,
+            tx_block_height,
+            merkle_proof,
+            raw_tx,
+        )?;
+
+        let amount_requested = issue.amount + issue.fee;
+        let vault = ext::vault_registry::get_vault_from_id::<T>(&issue.vault)?;
+        ensure!(
+            vault.to_be_issued_tokens >= amount_requested,
+            Error::InsufficientTokensCommitted
+        );
+
+        // compute the issued amount and the fee before the first write
+        let mut amount_issued = amount_requested;
+        let mut additional: PolkaBTC<T> = 0.into();
+        let mut refund: PolkaBTC<T> = 0.into();
+        if amount_paid < amount_requested {
+            // only the requester can execute the issue request, so executing
+            // it with a transaction paying less confirms the partial issue
+            amount_issued = amount_paid;
+        } else if amount_paid > amount_requested {
+            // the surplus is issued as far as the vault has free collateral,
+            // anything beyond is refunded by the vault
+            let surplus = amount_paid - amount_requested;
+            let issuable = ext::vault_registry::get_issuable_tokens_from_vault::<T>(&issue.vault)?;
+            additional = if surplus < issuable {
+                surplus
+            } else {
+                issuable
+            };
+            amount_issued += additional;
+            refund = surplus - additional;
+        }
+        let fee = if amount_issued == amount_requested {
+            issue.fee
+        } else {
+            ext::fee::get_issue_fee::<T>(amount_issued)?
+        };
+        // the vault receives the griefing collateral in proportion to the
+        // underpayment, such that a dust payment costs as much as cancelling
+        let slashed = if amount_paid < amount_requested {
+            Self::u128_to_dot(
+                Self::dot_to_u128(issue.griefing_collateral)?
+                    .checked_mul(Self::polkabtc_to_u128(amount_requested - amount_paid)?)
+                    .ok_or(Error::RuntimeError)?
+                    .checked_div(Self::polkabtc_to_u128(amount_requested)?)
+                    .ok_or(Error::RuntimeError)?,
+            )?
+        } else {
+            0.into()
+        };
+        ensure!(
+            ext::collateral::get_collateral_from_account::<T>(&issue.requester) >= slashed,
+            Error::InsufficientCollateralAvailable
+        );
+
+        if additional > 0.into() {
+            ext::vault_registry::increase_to_be_issued_tokens::<T>(&issue.vault, additional)?;
+        }
+        ext::vault_registry::issue_tokens::<T>(&issue.vault, amount_issued)?;
+        if amount_issued < amount_requested {
+            ext::vault_registry::decrease_to_be_issued_tokens::<T>(
+                &issue.vault,
+                amount_requested - amount_issued,
+            )?;
+        }
+        if slashed > 0.into() {
+            ext::collateral::slash_collateral::<T>(&issue.requester, &issue.vault, slashed)?;
+        }
+        if refund > 0.into() {
+            Self::create_refund_request(&issue.requester, &issue.vault, refund, issue_id);
+        }
+        ext::treasury::mint::<T>(issue.requester, amount_issued - fee);
+        if fee > 0.into() {
+            ext::treasury::mint::<T>(ext::fee::fee_pool_account_id::<T>(), fee);
+            ext::fee::increase_rewards_for_epoch::<T>(fee);
+        }
+        // Remove issue request from storage
+        Self::remove_issue_request(issue_id);
+
+        Self::deposit_event(<Event<T>>::ExecuteIssue(issue_id, requester, issue.vault));
+        Ok(())
+    }
+
+    /// Stores a request for the vault to return `amount_btc` to the requester
+    fn create_refund_request(
+        requester: &T::AccountId,
+        vault_id: &T::AccountId,
+        amount_btc: PolkaBTC<T>,
+        issue_id: H256,
+    ) {
+        let key = ext::security::get_secure_id::<T>(requester);
+        <RefundRequests<T>>::insert(
+            key,
+            Refund {
+                vault: vault_id.clone(),
+                opentime: Zero::zero(),
+                requester: requester.clone(),
+                amount_btc,
+                issue_id,
+                btc_address: None,
+            },
+        );
+        Self::deposit_event(<Event<T>>::RequestRefund(
+            key,
+            requester.clone(),
+            amount_btc,
+            vault_id.clone(),
+            issue_id,
+        ));
+    }
+
+    fn _set_refund_address(
+        requester: T::AccountId,
+        refund_id: H256,
+        btc_address: BtcAddress,
+    ) -> Result<(), Error> {
+        let mut refund = Self::get_refund_request_from_id(&refund_id)?;
+        ensure!(requester == refund.requester, Error::UnauthorizedUser);
+        // the vault may already have paid to the address set before
+        ensure!(refund.btc_address.is_none(), Error::RefundAddressAlreadySet);
+
+        refund.opentime = <system::Module<T>>::block_number();
+        refund.btc_address = Some(btc_address);
+        <RefundRequests<T>>::insert(refund_id, refund);
+
+        Self::deposit_event(<Event<T>>::SetRefundAddress(refund_id, btc_address));
+        Ok(())
+    }
+
+    fn _execute_refund(
+        vault_id: T::AccountId,
+        refund_id: H256,
+        tx_id: H256Le,
+        tx_block_height: u32,
+        merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> Result<(), Error> {
+        // Check that Parachain is RUNNING
+        ext::security::ensure_parachain_status_running::<T>()?;
+
+        let refund = Self::get_refund_request_from_id(&refund_id)?;
+        ensure!(vault_id == refund.vault, Error::UnauthorizedVault);
+        let btc_address = refund.btc_address.ok_or(Error::RefundAddressNotSet)?;
         let height = <system::Module<T>>::block_number();
-        let period = T::IssuePeriod::get();
         ensure!(
-            height <= issue.opentime + period,
-            Error::CommitPeriodExpired
+            height <= refund.opentime + T::RefundPeriod::get(),
+            Error::RefundPeriodExpired
         );
 
         ext::btc_relay::verify_transaction_inclusion::<T>(tx_id, tx_block_height, merkle_proof)?;
         ext::btc_relay::validate_transaction::<T>(
             raw_tx,
-            TryInto::<u64>::try_into(issue.amount).map_err(|_e| Error::RuntimeError)? as i64,
-            issue.btc_address.as_bytes().to_vec(),
-            issue_id.clone().as_bytes().to_vec(),
+            TryInto::<u64>::try_into(refund.amount_btc).map_err(|_e| Error::RuntimeError)? as i64,
+            btc_address,
+            refund_id.clone().as_bytes().to_vec(),
         )?;
 
-        ext::vault_registry::issue_tokens::<T>(&issue.vault, issue.amount)?;
-        ext::treasury::mint::<T>(issue.requester, issue.amount);
-        // Remove issue request from storage
-        Self::remove_issue_request(issue_id);
+        <RefundRequests<T>>::remove(refund_id);
 
-        Self::deposit_event(<Event<T>>::ExecuteIssue(issue_id, requester, issue.vault));
+        Self::deposit_event(<Event<T>>::ExecuteRefund(refund_id, vault_id));
         Ok(())
     }
 
+    /// Cancels a refund request the vault has not completed within the
+    /// refund period and compensates the requester with the value of the
+    /// BTC in DOT from the vault's collateral.
+    fn _cancel_refund(requester: T::AccountId, refund_id: H256) -> Result<(), Error> {
+        let refund = Self::get_refund_request_from_id(&refund_id)?;
+        ensure!(requester == refund.requester, Error::UnauthorizedUser);
+        ensure!(refund.btc_address.is_some(), Error::RefundAddressNotSet);
+        let height = <system::Module<T>>::block_number();
+        ensure!(
+            height > refund.opentime + T::RefundPeriod::get(),
+            Error::RefundPeriodNotExpired
+        );
+
+        // the compensation is limited by the collateral the vault has left
+        let amount_dot = ext::oracle::btc_to_dots::<T>(refund.amount_btc)?;
+        let collateral = ext::collateral::get_collateral_from_account::<T>(&refund.vault);
+        let slashed = if amount_dot < collateral {
+            amount_dot
+        } else {
+            collateral
+        };
+        if slashed > 0.into() {
+            // the compensation is paid out as free DOT rather than as collateral
+            ext::collateral::slash_collateral::<T>(&refund.vault, &requester, slashed)?;
+            ext::collateral::release_collateral::<T>(&requester, slashed)?;
+        }
+
+        <RefundRequests<T>>::remove(refund_id);
+
+        Self::deposit_event(<Event<T>>::CancelRefund(
+            refund_id,
+            requester,
+            refund.vault,
+            slashed,
+        ));
+        Ok(())
+    }
+
+    /// Checks the Bitcoin transaction of an issue request against the current
+    /// state without executing the request. Returns the error `execute_issue`
+    /// would fail with, except for the check of the requester.
+    ///
+    /// # Arguments
+    ///
+    /// * `issue_id` - identifier of issue request as output from request_issue
+    /// * `tx_id` - transaction hash
+    /// * `tx_block_height` - block number of backing chain
+    /// * `merkle_proof` - raw bytes
+    /// * `raw_tx` - raw bytes
+    pub fn _verify_and_validate_transaction(
+        issue_id: H256,
+        tx_id: H256Le,
+        tx_block_height: u32,
+        merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> Result<(), Error> {
+        // Check that Parachain is RUNNING
+        ext::security::ensure_parachain_status_running::<T>()?;
+
+        let issue = Self::get_issue_request_from_id(&issue_id)?;
+        Self::verify_and_validate_transaction(
+            issue_id,
+            &issue,
+            tx_id,
+            tx_block_height,
+            merkle_proof,
+            raw_tx,
+        )?;
+        Ok(())
+    }
+
+    /// Checks that the issue request has not expired and that the Bitcoin
+    /// transaction is included in the main chain and pays the vault.
+    /// Returns the amount of BTC paid, which may differ from the amount
+    /// of the issue request.
+    fn verify_and_validate_transaction(
+        issue_id: H256,
+        issue: &Issue<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>,
+        tx_id: H256Le,
+        tx_block_height: u32,
+        merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> Result<PolkaBTC<T>, Error> {
+        let height = <system::Module<T>>::block_number();
+        let period = T::IssuePeriod::get();
+        ensure!(
+            height <= issue.opentime + period,
+            Error::CommitPeriodExpired
+        );
+
+        ext::btc_relay::verify_transaction_inclusion::<T>(tx_id, tx_block_height, merkle_proof)?;
+        // any non-zero payment is accepted, partial and over-payments are
+        // handled on execution
+        let amount_paid = ext::btc_relay::validate_transaction::<T>(
+            raw_tx,
+            1,
+            issue.btc_address,
+            issue_id.clone().as_bytes().to_vec(),
+        )?;
+        let amount_paid =
+            TryInto::<u64>::try_into(amount_paid).map_err(|_e| Error::RuntimeError)?;
+        TryInto::<PolkaBTC<T>>::try_into(amount_paid).map_err(|_e| Error::RuntimeError)
+    }
+
     /// Cancels CBA issuance if time has expired and slashes collateral.
     fn _cancel_issue(requester: T::AccountId, issue_id: H256) -> Result<(), Error> {
         let issue = Self::get_issue_request_from_id(&issue_id)?;
         let height = <system::Module<T>>::block_number();
         let period = T::IssuePeriod::get();
 
-        ensure!(issue.opentime + period > height, Error::TimeNotExpired);
+        ensure!(height > issue.opentime + period, Error::TimeNotExpired);
         ensure!(!issue.completed, Error::IssueCompleted);
 
-        ext::vault_registry::decrease_to_be_issued_tokens::<T>(&issue.vault, issue.amount)?;
-        ext::collateral::slash_collateral::<T>(
-            &issue.requester,
+        let (vault_share, fee_share) = Self::cancel_issue_request(issue_id, &issue)?;
+
+        Self::deposit_event(<Event<T>>::CancelIssue(
+            issue_id,
+            requester,
+            vault_share,
+            fee_share,
+        ));
+        Ok(())
+    }
+
+    /// Cancels issue requests which expired at or before `height`. At most
+    /// `MaxExpiredIssuesPerBlock` requests are processed, the rest remain
+    /// queued for the following blocks. Nothing is processed unless the
+    /// parachain is running. Returns the number of processed requests.
+    fn begin_block(height: T::BlockNumber) -> u32 {
+        // requesters cannot execute their requests unless the parachain is
+        // running, so the queue is only processed while it is
+        if ext::security::ensure_parachain_status_running::<T>().is_err() {
+            return 0;
+        }
+        let mut expiry = <NextIssueExpiry<T>>::get();
+        if expiry.is_zero() {
+            // no issue request has been queued yet
+            return 0;
+        }
+
+        let max_expired = T::MaxExpiredIssuesPerBlock::get() as usize;
+        let mut remaining = max_expired;
+        while expiry <= height && remaining > 0 {
+            let mut issue_ids = <IssueExpiryQueue<T>>::take(expiry);
+            let pending = issue_ids.split_off(issue_ids.len().min(remaining));
+            remaining -= issue_ids.len();
+
+            for issue_id in issue_ids {
+                // a request which failed to expire remains open and can
+                // still be cancelled
+                if Self::expire_issue(issue_id).is_err() {
+                    Self::deposit_event(<Event<T>>::ExpireIssueFailed(issue_id));
+                }
+            }
+
+            if !pending.is_empty() {
+                <IssueExpiryQueue<T>>::insert(expiry, pending);
+                break;
+            }
+            expiry += One::one();
+        }
+        <NextIssueExpiry<T>>::put(expiry);
+        (max_expired - remaining) as u32
+    }
+
+    /// Cancels an expired issue request, unless it has already been
+    /// executed or cancelled.
+    fn expire_issue(issue_id: H256) -> Result<(), Error> {
+        if !<IssueRequests<T>>::contains_key(issue_id) {
+            return Ok(());
+        }
+        let issue = <IssueRequests<T>>::get(issue_id);
+        if issue.completed {
+            return Ok(());
+        }
+
+        let (vault_share, fee_share) = Self::cancel_issue_request(issue_id, &issue)?;
+
+        Self::deposit_event(<Event<T>>::ExpireIssue(issue_id, vault_share, fee_share));
+        Ok(())
+    }
+
+    /// Releases the to-be-issued tokens of the vault, slashes the griefing
+    /// collateral of the requester and removes the issue request. Returns
+    /// the griefing collateral paid to the vault and to the fee pool.
+    fn cancel_issue_request(
+        issue_id: H256,
+        issue: &Issue<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>,
+    ) -> Result<(DOT<T>, DOT<T>), Error> {
+        // the griefing collateral is split between the vault and the fee pool
+        let fee_share = Self::get_griefing_collateral_fee_share(issue.griefing_collateral)?;
+        let vault_share = issue.griefing_collateral - fee_share;
+
+        // all checks run before the first write
+        let vault = ext::vault_registry::get_vault_from_id::<T>(&issue.vault)?;
+        ensure!(
+            vault.to_be_issued_tokens >= issue.amount + issue.fee,
+            Error::InsufficientTokensCommitted
+        );
+        ensure!(
+            ext::collateral::get_collateral_from_account::<T>(&issue.requester)
+                >= issue.griefing_collateral,
+            Error::InsufficientCollateralAvailable
+        );
+
+        ext::vault_registry::decrease_to_be_issued_tokens::<T>(
             &issue.vault,
-            issue.griefing_collateral,
+            issue.amount + issue.fee,
         )?;
+        if vault_share > 0.into() {
+            ext::collateral::slash_collateral::<T>(&issue.requester, &issue.vault, vault_share)?;
+        }
+        if fee_share > 0.into() {
+            ext::collateral::slash_collateral::<T>(
+                &issue.requester,
+                &ext::fee::fee_pool_account_id::<T>(),
+                fee_share,
+            )?;
+            // the fee pool holds the DOT as collateral until the vaults
+            // withdraw their share
+            ext::fee::increase_dot_rewards_for_epoch::<T>(fee_share);
+        }
 
         // Remove issue request from storage
         Self::remove_issue_request(issue_id);
 
-        Self::deposit_event(<Event<T>>::CancelIssue(issue_id, requester));
-        Ok(())
+        Ok((vault_share, fee_share))
+    }
+
+    /// Calculates the griefing collateral a user needs to provide to issue
+    /// `amount` of PolkaBTC
+    fn get_required_griefing_collateral(amount: PolkaBTC<T>) -> Result<DOT<T>, Error> {
+        let amount_in_dot = ext::oracle::btc_to_dots::<T>(amount)?;
+        let raw_griefing_collateral = Self::dot_to_u128(amount_in_dot)?
+            .checked_mul(<IssueGriefingCollateralRate>::get())
+            .ok_or(Error::RuntimeError)?
+            .checked_div(10u128.pow(GRANULARITY))
+            .ok_or(Error::RuntimeError)?;
+        let griefing_collateral = Self::u128_to_dot(raw_griefing_collateral)?;
+
+        let minimum = <IssueGriefingCollateral<T>>::get();
+        if griefing_collateral > minimum {
+            Ok(griefing_collateral)
+        } else {
+            Ok(minimum)
+        }
+    }
+
+    /// Calculates the share of the griefing collateral paid into the fee
+    /// pool, which is at most the full griefing collateral
+    fn get_griefing_collateral_fee_share(griefing_collateral: DOT<T>) -> Result<DOT<T>, Error> {
+        let raw_griefing_collateral = Self::dot_to_u128(griefing_collateral)?;
+        let raw_fee_share = raw_griefing_collateral
+            .checked_mul(<GriefingCollateralFeeShare>::get())
+            .ok_or(Error::RuntimeError)?
+            .checked_div(10u128.pow(GRANULARITY))
+            .ok_or(Error::RuntimeError)?;
+        Self::u128_to_dot(raw_fee_share.min(raw_griefing_collateral))
     }
 
     fn get_issue_request_from_id(
@@ -275,10 +951,51 @@ impl<T: Trait> Module<T> {
         Ok(<IssueRequests<T>>::get(*issue_id))
     }
 
+    fn get_refund_request_from_id(
+        refund_id: &H256,
+    ) -> Result<Refund<T::AccountId, T::BlockNumber, PolkaBTC<T>>, Error> {
+        ensure!(
+            <RefundRequests<T>>::contains_key(*refund_id),
+            Error::RefundIdNotFound
+        );
+        Ok(<RefundRequests<T>>::get(*refund_id))
+    }
+
+    /// Returns the ids of the open issue requests of the requester
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - account of the requester
+    pub fn get_issue_requests_for_requester(account_id: T::AccountId) -> Vec<H256> {
+        <IssueRequestsByRequester<T>>::get(account_id)
+    }
+
+    /// Returns the ids of the open issue requests assigned to the vault
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - account of the vault
+    pub fn get_issue_requests_for_vault(vault_id: T::AccountId) -> Vec<H256> {
+        <IssueRequestsByVault<T>>::get(vault_id)
+    }
+
     fn insert_issue_request(
         key: H256,
         value: Issue<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>,
     ) {
+        index::insert::<IssueRequestsByRequester<T>, _, _>(&value.requester, key);
+        index::insert::<IssueRequestsByVault<T>, _, _>(&value.vault, key);
+
+        // a request can still be executed at `opentime + period`, so it
+        // expires in the following block. Requests are never queued at
+        // blocks which were already processed.
+        let next_expiry = <NextIssueExpiry<T>>::get();
+        let expiry = (value.opentime + T::IssuePeriod::get() + One::one()).max(next_expiry);
+        <IssueExpiryQueue<T>>::mutate(expiry, |ids| ids.push(key));
+        if next_expiry.is_zero() {
+            <NextIssueExpiry<T>>::put(expiry);
+        }
+
         <IssueRequests<T>>::insert(key, value)
     }
 
@@ -287,7 +1004,55 @@ impl<T: Trait> Module<T> {
         <IssueGriefingCollateral<T>>::set(amount);
     }
 
+    #[allow(dead_code)]
+    fn set_issue_griefing_collateral_rate(rate: u128) {
+        <IssueGriefingCollateralRate>::set(rate);
+    }
+
+    #[allow(dead_code)]
+    fn set_griefing_collateral_fee_share(share: u128) {
+        <GriefingCollateralFeeShare>::set(share);
+    }
+
     fn remove_issue_request(id: H256) {
-        <IssueRequests<T>>::remove(id);
+        let issue = <IssueRequests<T>>::take(id);
+        index::remove::<IssueRequestsByRequester<T>, _, _>(&issue.requester, id);
+        index::remove::<IssueRequestsByVault<T>, _, _>(&issue.vault, id);
+        if let Some(parent) = issue.parent {
+            let mut ids = <IssueGroups>::get(parent);
+            ids.retain(|part| *part != id);
+            if ids.is_empty() {
+                <IssueGroups>::remove(parent);
+            } else {
+                <IssueGroups>::insert(parent, ids);
+            }
+        }
+    }
+
+    fn polkabtc_to_u128(x: PolkaBTC<T>) -> Result<u128, Error> {
+        TryInto::<u128>::try_into(x).map_err(|_| Error::RuntimeError)
+    }
+
+    fn dot_to_u128(x: DOT<T>) -> Result<u128, Error> {
+        TryInto::<u128>::try_into(x).map_err(|_| Error::RuntimeError)
+    }
+
+    fn u128_to_dot(x: u128) -> Result<DOT<T>, Error> {
+        TryInto::<DOT<T>>::try_into(x).map_err(|_| Error::RuntimeError)
+    }
+}
+
+impl<T: Trait> vault_registry::OnVaultLiquidated<T::AccountId> for Module<T> {
+    /// Reassigns the open issue requests of a liquidated vault to the
+    /// `LiquidationVault`, which took over the vault's to-be-issued tokens,
+    /// such that BTC already paid can still be used to execute them.
+    fn on_vault_liquidated(vault_id: &T::AccountId, liquidation_vault_id: &T::AccountId) {
+        for issue_id in <IssueRequestsByVault<T>>::take(vault_id) {
+            <IssueRequests<T>>::mutate(issue_id, |issue| {
+                issue.vault = liquidation_vault_id.clone()
+            });
+            // the LiquidationVault is not limited in its number of open requests
+            index::insert::<IssueRequestsByVault<T>, _, _>(liquidation_vault_id, issue_id);
+        }
     }
 }
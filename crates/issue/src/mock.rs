@@ -34,6 +34,7 @@ impl_outer_event! {
         collateral<T>,
         btc_relay,
         treasury<T>,
+        fee<T>,
         exchange_rate_oracle<T>,
         security,
     }
@@ -95,6 +96,7 @@ impl pallet_balances::Trait for Test {
 
 impl vault_registry::Trait for Test {
     type Event = TestEvent;
+    type OnVaultLiquidated = Issue;
 }
 
 impl collateral::Trait for Test {
@@ -128,12 +130,24 @@ impl exchange_rate_oracle::Trait for Test {
     type Event = TestEvent;
 }
 
+parameter_types! {
+    pub const EpochPeriod: BlockNumber = 100;
+}
+impl fee::Trait for Test {
+    type Event = TestEvent;
+    type EpochPeriod = EpochPeriod;
+}
+
 parameter_types! {
     pub const IssuePeriod: BlockNumber = 10;
+    pub const RefundPeriod: BlockNumber = 10;
+    pub const MaxExpiredIssuesPerBlock: u32 = 2;
 }
 impl Trait for Test {
     type Event = TestEvent;
     type IssuePeriod = IssuePeriod;
+    type RefundPeriod = RefundPeriod;
+    type MaxExpiredIssuesPerBlock = MaxExpiredIssuesPerBlock;
 }
 
 pub type System = system::Module<Test>;
@@ -1,14 +1,17 @@
 use crate::mock::*;
 use crate::types::PolkaBTC;
 use crate::RawEvent;
-use crate::{ext, Trait};
+use crate::{
+    ext, IssueGroups, IssueRequests, IssueRequestsByRequester, IssueRequestsByVault,
+    RefundRequests, Trait, EXPIRE_ISSUE_WEIGHT,
+};
+use bitcoin::address::BtcAddress;
 use bitcoin::types::H256Le;
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{assert_noop, assert_ok, traits::OnInitialize, IterableStorageMap, StorageMap};
 use mocktopus::mocking::*;
 use primitive_types::H256;
-use sp_core::H160;
-use vault_registry::Vault;
-use x_core::Error;
+use vault_registry::{OnVaultLiquidated, Vault, Wallet};
+use x_core::{index, Error};
 
 fn request_issue(
     origin: AccountId,
@@ -22,7 +25,7 @@ fn request_issue(
     ext::security::get_secure_id::<Test>.mock_safe(|_| MockResult::Return(get_dummy_request_id()));
 
     ext::vault_registry::increase_to_be_issued_tokens::<Test>
-        .mock_safe(|_, _| MockResult::Return(Ok(H160::from_slice(&[0; 20]))));
+        .mock_safe(|_, _| MockResult::Return(Ok(BtcAddress::default())));
 
     Issue::_request_issue(origin, amount, vault, collateral)
 }
@@ -41,7 +44,7 @@ fn request_issue_ok(
     ext::security::get_secure_id::<Test>.mock_safe(|_| MockResult::Return(get_dummy_request_id()));
 
     ext::vault_registry::increase_to_be_issued_tokens::<Test>
-        .mock_safe(|_, _| MockResult::Return(Ok(H160::from_slice(&[0; 20]))));
+        .mock_safe(|_, _| MockResult::Return(Ok(BtcAddress::default())));
 
     match Issue::_request_issue(origin, amount, vault, collateral) {
         Ok(act) => act,
@@ -71,11 +74,23 @@ fn execute_issue_ok(origin: AccountId, issue_id: &H256) {
     ext::btc_relay::verify_transaction_inclusion::<Test>
         .mock_safe(|_, _, _| MockResult::Return(Ok(())));
 
-    ext::btc_relay::validate_transaction::<Test>.mock_safe(|_, _, _, _| MockResult::Return(Ok(())));
+    let issue = <IssueRequests<Test>>::get(issue_id);
+    let amount_paid = (issue.amount + issue.fee) as i64;
+    ext::btc_relay::validate_transaction::<Test>
+        .mock_safe(move |_, _, _, _| MockResult::Return(Ok(amount_paid)));
 
     assert_ok!(execute_issue(origin, issue_id));
 }
 
+fn execute_issue_with_payment(issue_id: &H256, amount_paid: i64) -> Result<(), Error> {
+    ext::btc_relay::verify_transaction_inclusion::<Test>
+        .mock_safe(|_, _, _| MockResult::Return(Ok(())));
+    ext::btc_relay::validate_transaction::<Test>
+        .mock_safe(move |_, _, _, _| MockResult::Return(Ok(amount_paid)));
+
+    execute_issue(ALICE, issue_id)
+}
+
 fn cancel_issue(origin: AccountId, issue_id: &H256) -> Result<(), Error> {
     Issue::_cancel_issue(origin, *issue_id)
 }
@@ -86,6 +101,15 @@ fn init_zero_vault<T: Trait>(id: T::AccountId) -> Vault<T::AccountId, T::BlockNu
     vault
 }
 
+fn init_issuing_vault(
+    id: AccountId,
+    to_be_issued_tokens: Balance,
+) -> Vault<AccountId, BlockNumber, Balance> {
+    let mut vault = init_zero_vault::<Test>(id);
+    vault.to_be_issued_tokens = to_be_issued_tokens;
+    vault
+}
+
 fn get_dummy_request_id() -> H256 {
     return H256::zero();
 }
@@ -102,7 +126,7 @@ fn test_request_issue_banned_fails() {
                 to_be_issued_tokens: 0,
                 issued_tokens: 0,
                 to_be_redeemed_tokens: 0,
-                btc_address: H160([0; 20]),
+                wallet: Wallet::new(BtcAddress::default()),
                 banned_until: Some(1),
             },
         );
@@ -125,6 +149,32 @@ fn test_request_issue_insufficient_collateral_fails() {
     })
 }
 
+#[test]
+fn test_request_issue_griefing_collateral_scales_with_amount() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_zero_vault::<Test>(BOB))));
+        ext::vault_registry::ensure_not_banned::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|amount| MockResult::Return(Ok(amount * 2)));
+        Issue::set_issue_griefing_collateral(2);
+        Issue::set_issue_griefing_collateral_rate(10_000); // 10%
+
+        // 100 PolkaBTC are worth 200 DOT, of which 10% are required
+        assert_noop!(
+            request_issue(ALICE, 100, BOB, 19),
+            Error::InsufficientCollateral,
+        );
+        request_issue_ok(ALICE, 100, BOB, 20);
+
+        // small requests require at least the minimum griefing collateral
+        assert_noop!(
+            request_issue(ALICE, 5, BOB, 1),
+            Error::InsufficientCollateral,
+        );
+        request_issue_ok(ALICE, 5, BOB, 2);
+    })
+}
+
 #[test]
 fn test_request_issue_succeeds() {
     run_test(|| {
@@ -141,7 +191,7 @@ fn test_request_issue_succeeds() {
             origin,
             amount,
             vault,
-            H160([0; 20]),
+            BtcAddress::default(),
         ));
         assert!(System::events()
             .iter()
@@ -149,6 +199,128 @@ fn test_request_issue_succeeds() {
     })
 }
 
+#[test]
+fn test_request_issue_auto_selects_vault() {
+    run_test(|| {
+        ext::vault_registry::get_vault_with_sufficient_collateral::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(BOB)));
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_zero_vault::<Test>(BOB))));
+        ext::vault_registry::ensure_not_banned::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::security::ensure_parachain_status_running::<Test>
+            .mock_safe(|| MockResult::Return(Ok(())));
+        ext::security::get_secure_id::<Test>
+            .mock_safe(|_| MockResult::Return(get_dummy_request_id()));
+        ext::vault_registry::increase_to_be_issued_tokens::<Test>
+            .mock_safe(|_, _| MockResult::Return(Ok(BtcAddress::default())));
+
+        assert_ok!(Issue::request_issue_auto(Origin::signed(ALICE), 3, 0));
+
+        let request_issue_event = TestEvent::test_events(RawEvent::RequestIssue(
+            get_dummy_request_id(),
+            ALICE,
+            3,
+            BOB,
+            BtcAddress::default(),
+        ));
+        assert!(System::events()
+            .iter()
+            .any(|a| a.event == request_issue_event));
+    })
+}
+
+#[test]
+fn test_request_issue_auto_without_vault_fails() {
+    run_test(|| {
+        ext::vault_registry::get_vault_with_sufficient_collateral::<Test>
+            .mock_safe(|_| MockResult::Return(Err(Error::NoVaultWithSufficientCollateral)));
+
+        assert_noop!(
+            Issue::request_issue_auto(Origin::signed(ALICE), 3, 0),
+            Error::NoVaultWithSufficientCollateral,
+        );
+    })
+}
+
+fn request_issue_split(amount: Balance, collateral: Balance) -> Result<H256, Error> {
+    ext::security::ensure_parachain_status_running::<Test>.mock_safe(|| MockResult::Return(Ok(())));
+    ext::vault_registry::get_vaults_for_split_issue::<Test>
+        .mock_safe(|_| MockResult::Return(Ok(vec![(BOB, 6), (CAROL, 4)])));
+    ext::vault_registry::increase_to_be_issued_tokens::<Test>
+        .mock_safe(|_, _| MockResult::Return(Ok(BtcAddress::default())));
+
+    Issue::_request_issue_split(ALICE, amount, collateral)
+}
+
+#[test]
+fn test_request_issue_split_succeeds() {
+    run_test(|| {
+        Issue::set_issue_griefing_collateral(2);
+
+        let parent = request_issue_split(10, 10).unwrap();
+
+        let ids = <IssueGroups>::get(parent);
+        assert_eq!(ids.len(), 2);
+        let first = <IssueRequests<Test>>::get(ids[0]);
+        let second = <IssueRequests<Test>>::get(ids[1]);
+        assert_eq!(
+            (first.vault, first.amount, first.griefing_collateral),
+            (BOB, 6, 6)
+        );
+        assert_eq!(
+            (second.vault, second.amount, second.griefing_collateral),
+            (CAROL, 4, 4)
+        );
+        assert_eq!(first.parent, Some(parent));
+        assert_eq!(
+            <collateral::Module<Test>>::get_collateral_from_account(&ALICE),
+            10
+        );
+
+        let request_issue_event = TestEvent::test_events(RawEvent::RequestIssueSplit(
+            parent,
+            ALICE,
+            10,
+            vec![
+                (ids[0], BOB, 6, BtcAddress::default()),
+                (ids[1], CAROL, 4, BtcAddress::default()),
+            ],
+        ));
+        assert!(System::events()
+            .iter()
+            .any(|a| a.event == request_issue_event));
+    })
+}
+
+#[test]
+fn test_request_issue_split_insufficient_collateral_fails() {
+    run_test(|| {
+        Issue::set_issue_griefing_collateral(2);
+
+        // the second part would only receive 1 DOT of griefing collateral
+        assert_noop!(request_issue_split(10, 3), Error::InsufficientCollateral);
+    })
+}
+
+#[test]
+fn test_execute_issue_part_of_split_issue_succeeds() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|id| MockResult::Return(Ok(init_issuing_vault(*id, 6))));
+        ext::vault_registry::issue_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+
+        let parent = request_issue_split(10, 0).unwrap();
+        let ids = <IssueGroups>::get(parent);
+        execute_issue_ok(ALICE, &ids[0]);
+
+        assert_eq!(<IssueGroups>::get(parent), vec![ids[1]]);
+        assert_eq!(Balances::free_balance(ALICE), ALICE_BALANCE + 6);
+
+        execute_issue_ok(ALICE, &ids[1]);
+        assert!(!<IssueGroups>::contains_key(parent));
+    })
+}
+
 #[test]
 fn test_execute_issue_not_found_fails() {
     run_test(|| {
@@ -184,7 +356,7 @@ fn test_execute_issue_commit_period_expired_fails() {
 fn test_execute_issue_succeeds() {
     run_test(|| {
         ext::vault_registry::get_vault_from_id::<Test>
-            .mock_safe(|_| MockResult::Return(Ok(init_zero_vault::<Test>(BOB))));
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 3))));
         ext::vault_registry::issue_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
 
         let issue_id = request_issue_ok(ALICE, 3, BOB, 0);
@@ -201,6 +373,379 @@ fn test_execute_issue_succeeds() {
     })
 }
 
+#[test]
+fn test_execute_issue_pays_fee_into_fee_pool() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 100))));
+        ext::fee::get_issue_fee::<Test>.mock_safe(|amount| MockResult::Return(Ok(amount / 20)));
+        ext::vault_registry::issue_tokens::<Test>.mock_safe(|_, amount| {
+            assert_eq!(amount, 100);
+            MockResult::Return(Ok(()))
+        });
+
+        let issue_id = request_issue_ok(ALICE, 100, BOB, 0);
+        <system::Module<Test>>::set_block_number(5);
+
+        ext::btc_relay::verify_transaction_inclusion::<Test>
+            .mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        // the vault receives the full amount including the fee
+        ext::btc_relay::validate_transaction::<Test>
+            .mock_safe(|_, _, _, _| MockResult::Return(Ok(100)));
+        assert_ok!(execute_issue(ALICE, &issue_id));
+
+        let fee_pool = ext::fee::fee_pool_account_id::<Test>();
+        assert_eq!(Balances::free_balance(ALICE), ALICE_BALANCE + 95);
+        assert_eq!(Balances::free_balance(fee_pool), 5);
+        assert_eq!(<fee::Module<Test>>::epoch_rewards(), 5);
+    })
+}
+
+#[test]
+fn test_execute_issue_with_underpayment_issues_paid_amount() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 100))));
+        ext::fee::get_issue_fee::<Test>.mock_safe(|amount| MockResult::Return(Ok(amount / 20)));
+        ext::vault_registry::decrease_to_be_issued_tokens::<Test>.mock_safe(|_, amount| {
+            assert_eq!(amount, 40);
+            MockResult::Return(Ok(()))
+        });
+        ext::vault_registry::issue_tokens::<Test>.mock_safe(|_, amount| {
+            assert_eq!(amount, 60);
+            MockResult::Return(Ok(()))
+        });
+
+        let issue_id = request_issue_ok(ALICE, 100, BOB, 0);
+        assert_ok!(execute_issue_with_payment(&issue_id, 60));
+
+        let fee_pool = ext::fee::fee_pool_account_id::<Test>();
+        assert_eq!(Balances::free_balance(ALICE), ALICE_BALANCE + 57);
+        assert_eq!(Balances::free_balance(fee_pool), 3);
+    })
+}
+
+#[test]
+fn test_execute_issue_with_dust_payment_slashes_griefing_collateral() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 100))));
+        ext::fee::get_issue_fee::<Test>.mock_safe(|_| MockResult::Return(Ok(0)));
+        ext::vault_registry::decrease_to_be_issued_tokens::<Test>
+            .mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::issue_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+
+        let issue_id = request_issue_ok(ALICE, 100, BOB, 20);
+        assert_ok!(execute_issue_with_payment(&issue_id, 1));
+
+        // 99% of the requested amount were not paid
+        assert_eq!(
+            <collateral::Module<Test>>::get_collateral_from_account(&BOB),
+            19
+        );
+        assert_eq!(
+            <collateral::Module<Test>>::get_collateral_from_account(&ALICE),
+            1
+        );
+    })
+}
+
+#[test]
+fn test_execute_issue_with_overpayment_issues_surplus() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 100))));
+        ext::vault_registry::get_issuable_tokens_from_vault::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(50)));
+        ext::vault_registry::issue_tokens::<Test>.mock_safe(|_, amount| {
+            assert_eq!(amount, 120);
+            MockResult::Return(Ok(()))
+        });
+
+        let issue_id = request_issue_ok(ALICE, 100, BOB, 0);
+        assert_ok!(execute_issue_with_payment(&issue_id, 120));
+
+        assert_eq!(Balances::free_balance(ALICE), ALICE_BALANCE + 120);
+        assert_eq!(<RefundRequests<Test>>::iter().count(), 0);
+    })
+}
+
+#[test]
+fn test_execute_issue_with_overpayment_beyond_capacity_requests_refund() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 100))));
+        ext::vault_registry::get_issuable_tokens_from_vault::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(5)));
+        ext::vault_registry::issue_tokens::<Test>.mock_safe(|_, amount| {
+            assert_eq!(amount, 105);
+            MockResult::Return(Ok(()))
+        });
+
+        let issue_id = request_issue_ok(ALICE, 100, BOB, 0);
+        ext::security::get_secure_id::<Test>.mock_safe(|_| MockResult::Return(H256([1; 32])));
+        assert_ok!(execute_issue_with_payment(&issue_id, 120));
+
+        assert_eq!(Balances::free_balance(ALICE), ALICE_BALANCE + 105);
+        let refund_id = H256([1; 32]);
+        let request_refund_event =
+            TestEvent::test_events(RawEvent::RequestRefund(refund_id, ALICE, 15, BOB, issue_id));
+        assert!(System::events()
+            .iter()
+            .any(|a| a.event == request_refund_event));
+
+        assert_noop!(
+            Issue::execute_refund(
+                Origin::signed(BOB),
+                refund_id,
+                H256Le::zero(),
+                0,
+                vec![0u8; 100],
+                vec![0u8; 100]
+            ),
+            Error::RefundAddressNotSet
+        );
+        assert_ok!(Issue::set_refund_address(
+            Origin::signed(ALICE),
+            refund_id,
+            BtcAddress::default()
+        ));
+        ext::btc_relay::validate_transaction::<Test>.mock_safe(|_, amount, _, _| {
+            assert_eq!(amount, 15);
+            MockResult::Return(Ok(amount))
+        });
+        assert_ok!(Issue::execute_refund(
+            Origin::signed(BOB),
+            refund_id,
+            H256Le::zero(),
+            0,
+            vec![0u8; 100],
+            vec![0u8; 100]
+        ));
+        assert!(!<RefundRequests<Test>>::contains_key(refund_id));
+    })
+}
+
+#[test]
+fn test_execute_issue_with_insufficient_committed_tokens_fails() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 99))));
+        ext::vault_registry::get_issuable_tokens_from_vault::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(50)));
+
+        let issue_id = request_issue_ok(ALICE, 100, BOB, 0);
+        ext::vault_registry::increase_to_be_issued_tokens::<Test>
+            .mock_safe(|_, _| panic!("no tokens must be committed"));
+        assert_noop!(
+            execute_issue_with_payment(&issue_id, 120),
+            Error::InsufficientTokensCommitted
+        );
+    })
+}
+
+#[test]
+fn test_execute_issue_failing_fee_calculation_does_not_write() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 100))));
+        ext::vault_registry::decrease_to_be_issued_tokens::<Test>
+            .mock_safe(|_, _| panic!("the vault must not be updated"));
+
+        let issue_id = request_issue_ok(ALICE, 100, BOB, 0);
+        ext::fee::get_issue_fee::<Test>.mock_safe(|_| MockResult::Return(Err(Error::RuntimeError)));
+        assert_noop!(
+            execute_issue_with_payment(&issue_id, 60),
+            Error::RuntimeError
+        );
+    })
+}
+
+#[test]
+fn test_execute_issue_failing_issue_tokens_does_not_request_refund() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 100))));
+        ext::vault_registry::get_issuable_tokens_from_vault::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(0)));
+        ext::vault_registry::issue_tokens::<Test>
+            .mock_safe(|_, _| MockResult::Return(Err(Error::InsufficientTokensCommitted)));
+
+        let issue_id = request_issue_ok(ALICE, 100, BOB, 0);
+        assert_noop!(
+            execute_issue_with_payment(&issue_id, 120),
+            Error::InsufficientTokensCommitted
+        );
+        assert_eq!(<RefundRequests<Test>>::iter().count(), 0);
+    })
+}
+
+/// Executes an issue request of 100 paid with 120, of which the vault can
+/// only back 5, and returns the id of the refund request for the remaining 15
+fn request_refund_ok() -> H256 {
+    ext::vault_registry::get_vault_from_id::<Test>
+        .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 100))));
+    ext::vault_registry::get_issuable_tokens_from_vault::<Test>
+        .mock_safe(|_| MockResult::Return(Ok(5)));
+    ext::vault_registry::issue_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+
+    let issue_id = request_issue_ok(ALICE, 100, BOB, 0);
+    ext::security::get_secure_id::<Test>.mock_safe(|_| MockResult::Return(H256([1; 32])));
+    assert_ok!(execute_issue_with_payment(&issue_id, 120));
+    H256([1; 32])
+}
+
+#[test]
+fn test_set_refund_address_twice_fails() {
+    run_test(|| {
+        let refund_id = request_refund_ok();
+        assert_ok!(Issue::set_refund_address(
+            Origin::signed(ALICE),
+            refund_id,
+            BtcAddress::default()
+        ));
+        assert_noop!(
+            Issue::set_refund_address(Origin::signed(ALICE), refund_id, BtcAddress::default()),
+            Error::RefundAddressAlreadySet
+        );
+    })
+}
+
+#[test]
+fn test_execute_refund_after_refund_period_fails() {
+    run_test(|| {
+        let refund_id = request_refund_ok();
+        assert_ok!(Issue::set_refund_address(
+            Origin::signed(ALICE),
+            refund_id,
+            BtcAddress::default()
+        ));
+
+        <system::Module<Test>>::set_block_number(12);
+        assert_noop!(
+            Issue::execute_refund(
+                Origin::signed(BOB),
+                refund_id,
+                H256Le::zero(),
+                0,
+                vec![0u8; 100],
+                vec![0u8; 100]
+            ),
+            Error::RefundPeriodExpired
+        );
+    })
+}
+
+#[test]
+fn test_cancel_refund_before_refund_period_fails() {
+    run_test(|| {
+        let refund_id = request_refund_ok();
+        assert_noop!(
+            Issue::cancel_refund(Origin::signed(ALICE), refund_id),
+            Error::RefundAddressNotSet
+        );
+        assert_ok!(Issue::set_refund_address(
+            Origin::signed(ALICE),
+            refund_id,
+            BtcAddress::default()
+        ));
+
+        // the refund period ends at block 11
+        <system::Module<Test>>::set_block_number(11);
+        assert_noop!(
+            Issue::cancel_refund(Origin::signed(ALICE), refund_id),
+            Error::RefundPeriodNotExpired
+        );
+        assert_noop!(
+            Issue::cancel_refund(Origin::signed(CAROL), refund_id),
+            Error::UnauthorizedUser
+        );
+    })
+}
+
+#[test]
+fn test_cancel_refund_slashes_vault() {
+    run_test(|| {
+        assert_ok!(<collateral::Module<Test>>::lock_collateral(&BOB, 50));
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|amount| MockResult::Return(Ok(amount * 2)));
+
+        let refund_id = request_refund_ok();
+        let alice_balance = Balances::free_balance(ALICE);
+        assert_ok!(Issue::set_refund_address(
+            Origin::signed(ALICE),
+            refund_id,
+            BtcAddress::default()
+        ));
+
+        <system::Module<Test>>::set_block_number(12);
+        assert_ok!(Issue::cancel_refund(Origin::signed(ALICE), refund_id));
+
+        // the 15 BTC are worth 30 DOT, paid out as free balance
+        assert_eq!(Balances::free_balance(ALICE), alice_balance + 30);
+        assert_eq!(
+            <collateral::Module<Test>>::get_collateral_from_account(&BOB),
+            20
+        );
+        assert!(!<RefundRequests<Test>>::contains_key(refund_id));
+        let cancel_refund_event =
+            TestEvent::test_events(RawEvent::CancelRefund(refund_id, ALICE, BOB, 30));
+        assert!(System::events()
+            .iter()
+            .any(|a| a.event == cancel_refund_event));
+    })
+}
+
+#[test]
+fn test_verify_and_validate_transaction_succeeds() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_zero_vault::<Test>(BOB))));
+        ext::btc_relay::verify_transaction_inclusion::<Test>
+            .mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        ext::btc_relay::validate_transaction::<Test>
+            .mock_safe(|_, _, _, _| MockResult::Return(Ok(3)));
+
+        let issue_id = request_issue_ok(ALICE, 3, BOB, 0);
+        <system::Module<Test>>::set_block_number(5);
+
+        assert_ok!(Issue::_verify_and_validate_transaction(
+            issue_id,
+            H256Le::zero(),
+            0,
+            vec![0u8; 100],
+            vec![0u8; 100],
+        ));
+        // the issue request is left untouched
+        assert!(Issue::get_issue_request_from_id(&issue_id).is_ok());
+    })
+}
+
+#[test]
+fn test_verify_and_validate_transaction_returns_validation_error() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_zero_vault::<Test>(BOB))));
+        ext::btc_relay::verify_transaction_inclusion::<Test>
+            .mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        ext::btc_relay::validate_transaction::<Test>
+            .mock_safe(|_, _, _, _| MockResult::Return(Err(Error::WrongRecipient)));
+
+        let issue_id = request_issue_ok(ALICE, 3, BOB, 0);
+        <system::Module<Test>>::set_block_number(5);
+
+        assert_noop!(
+            Issue::_verify_and_validate_transaction(
+                issue_id,
+                H256Le::zero(),
+                0,
+                vec![0u8; 100],
+                vec![0u8; 100],
+            ),
+            Error::WrongRecipient
+        );
+    })
+}
+
 #[test]
 fn test_cancel_issue_not_found_fails() {
     run_test(|| {
@@ -215,7 +760,8 @@ fn test_cancel_issue_not_expired_fails() {
             .mock_safe(|_| MockResult::Return(Ok(init_zero_vault::<Test>(BOB))));
 
         let issue_id = request_issue_ok(ALICE, 3, BOB, 0);
-        <system::Module<Test>>::set_block_number(99);
+        // the request can still be executed in the last block of the period
+        <system::Module<Test>>::set_block_number(11);
         assert_noop!(cancel_issue(ALICE, &issue_id), Error::TimeNotExpired,);
     })
 }
@@ -223,14 +769,273 @@ fn test_cancel_issue_not_expired_fails() {
 #[test]
 fn test_cancel_issue_succeeds() {
     run_test(|| {
-        <system::Module<Test>>::set_block_number(20);
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 3))));
+        ext::vault_registry::decrease_to_be_issued_tokens::<Test>
+            .mock_safe(|_, _| MockResult::Return(Ok(())));
+
+        let issue_id = request_issue_ok(ALICE, 3, BOB, 0);
+        <system::Module<Test>>::set_block_number(12);
+        assert_ok!(cancel_issue(ALICE, &issue_id));
+    })
+}
+
+#[test]
+fn test_cancel_issue_insufficient_tokens_committed_fails() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 2))));
+
+        let issue_id = request_issue_ok(ALICE, 3, BOB, 8);
+        <system::Module<Test>>::set_block_number(12);
+        assert_noop!(
+            cancel_issue(ALICE, &issue_id),
+            Error::InsufficientTokensCommitted
+        );
+    })
+}
+
+#[test]
+fn test_begin_block_cancels_expired_issue_requests() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 3))));
+        ext::vault_registry::decrease_to_be_issued_tokens::<Test>.mock_safe(|vault, amount| {
+            assert_eq!(vault, &BOB);
+            assert_eq!(amount, 3);
+            MockResult::Return(Ok(()))
+        });
+
+        // requested at block 1, can be executed until block 11
+        let issue_id = request_issue_ok(ALICE, 3, BOB, 8);
+
+        Issue::begin_block(11);
+        assert!(<IssueRequests<Test>>::contains_key(issue_id));
+
+        Issue::begin_block(12);
+        assert!(!<IssueRequests<Test>>::contains_key(issue_id));
+        assert_eq!(
+            <collateral::Module<Test>>::get_collateral_from_account(&BOB),
+            8
+        );
+        assert!(System::events()
+            .iter()
+            .any(|a| a.event == TestEvent::test_events(RawEvent::ExpireIssue(issue_id, 8, 0))));
+    })
+}
+
+#[test]
+fn test_begin_block_cancels_expired_issue_requests_in_batches() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 3))));
+        ext::vault_registry::decrease_to_be_issued_tokens::<Test>
+            .mock_safe(|_, _| MockResult::Return(Ok(())));
+
+        ext::vault_registry::ensure_not_banned::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::security::ensure_parachain_status_running::<Test>
+            .mock_safe(|| MockResult::Return(Ok(())));
+        ext::vault_registry::increase_to_be_issued_tokens::<Test>
+            .mock_safe(|_, _| MockResult::Return(Ok(BtcAddress::default())));
+
+        let issue_ids: Vec<H256> = (1..=3)
+            .map(|i| {
+                ext::security::get_secure_id::<Test>
+                    .mock_safe(move |_| MockResult::Return(H256([i; 32])));
+                Issue::_request_issue(ALICE, 3, BOB, 0).unwrap()
+            })
+            .collect();
+
+        // at most two expired requests are cancelled per block
+        assert_eq!(Issue::begin_block(12), 2);
+        assert!(!<IssueRequests<Test>>::contains_key(issue_ids[0]));
+        assert!(!<IssueRequests<Test>>::contains_key(issue_ids[1]));
+        assert!(<IssueRequests<Test>>::contains_key(issue_ids[2]));
+
+        assert_eq!(Issue::begin_block(13), 1);
+        assert!(!<IssueRequests<Test>>::contains_key(issue_ids[2]));
+    })
+}
+
+#[test]
+fn test_begin_block_keeps_issue_request_failing_to_expire() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 0))));
+        let issue_id = request_issue_ok(ALICE, 3, BOB, 8);
+
+        // the vault has not committed the tokens of the request
+        assert_eq!(Issue::begin_block(12), 1);
+        assert!(<IssueRequests<Test>>::contains_key(issue_id));
+        assert!(System::events()
+            .iter()
+            .any(|a| a.event == TestEvent::test_events(RawEvent::ExpireIssueFailed(issue_id))));
+    })
+}
+
+#[test]
+fn test_begin_block_does_not_expire_issue_requests_unless_running() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 3))));
+        ext::vault_registry::decrease_to_be_issued_tokens::<Test>
+            .mock_safe(|_, _| MockResult::Return(Ok(())));
+        let issue_id = request_issue_ok(ALICE, 3, BOB, 8);
+
+        ext::security::ensure_parachain_status_running::<Test>
+            .mock_safe(|| MockResult::Return(Err(Error::ParachainNotRunning)));
+        assert_eq!(Issue::begin_block(12), 0);
+        assert!(<IssueRequests<Test>>::contains_key(issue_id));
+
+        ext::security::ensure_parachain_status_running::<Test>
+            .mock_safe(|| MockResult::Return(Ok(())));
+        assert_eq!(Issue::begin_block(13), 1);
+        assert!(!<IssueRequests<Test>>::contains_key(issue_id));
+    })
+}
+
+#[test]
+fn test_on_initialize_weighs_expired_issue_requests() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 3))));
+        ext::vault_registry::decrease_to_be_issued_tokens::<Test>
+            .mock_safe(|_, _| MockResult::Return(Ok(())));
+        request_issue_ok(ALICE, 3, BOB, 0);
+
+        assert_eq!(Issue::on_initialize(11), 0);
+        assert_eq!(Issue::on_initialize(12), EXPIRE_ISSUE_WEIGHT);
+    })
+}
+
+#[test]
+fn test_execute_issue_in_last_block_of_period_succeeds() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 3))));
+        ext::vault_registry::issue_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+
+        let issue_id = request_issue_ok(ALICE, 3, BOB, 0);
+        // the last block of the period starts before the user executes
+        <system::Module<Test>>::set_block_number(11);
+        Issue::on_initialize(11);
+        execute_issue_ok(ALICE, &issue_id);
+
+        let execute_issue_event =
+            TestEvent::test_events(RawEvent::ExecuteIssue(issue_id, ALICE, BOB));
+        assert!(System::events()
+            .iter()
+            .any(|a| a.event == execute_issue_event));
+    })
+}
+
+#[test]
+fn test_request_issue_with_too_many_open_requests_fails() {
+    run_test(|| {
         ext::vault_registry::get_vault_from_id::<Test>
             .mock_safe(|_| MockResult::Return(Ok(init_zero_vault::<Test>(BOB))));
+        ext::vault_registry::ensure_not_banned::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        let open_ids = (0..index::MAX_OPEN_REQUESTS as u64)
+            .map(H256::from_low_u64_be)
+            .collect::<Vec<_>>();
+        <IssueRequestsByRequester<Test>>::insert(ALICE, open_ids.clone());
+        <IssueRequestsByVault<Test>>::insert(BOB, open_ids);
+
+        assert_noop!(
+            request_issue(ALICE, 3, BOB, 0),
+            Error::MaxOpenRequestsReached
+        );
+        // the requests of the vault are not capped
+        request_issue_ok(CAROL, 3, BOB, 0);
+    })
+}
+
+#[test]
+fn test_issue_requests_are_indexed_by_account() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 3))));
         ext::vault_registry::decrease_to_be_issued_tokens::<Test>
             .mock_safe(|_, _| MockResult::Return(Ok(())));
 
+        let first_id = request_issue_ok(ALICE, 3, BOB, 0);
+        ext::security::get_secure_id::<Test>.mock_safe(|_| MockResult::Return(H256([1; 32])));
+        let second_id = Issue::_request_issue(ALICE, 3, BOB, 0).unwrap();
+        assert_eq!(
+            Issue::get_issue_requests_for_requester(ALICE),
+            vec![first_id, second_id]
+        );
+        assert_eq!(
+            Issue::get_issue_requests_for_vault(BOB),
+            vec![first_id, second_id]
+        );
+
+        <system::Module<Test>>::set_block_number(12);
+        assert_ok!(cancel_issue(ALICE, &first_id));
+        assert_eq!(
+            Issue::get_issue_requests_for_requester(ALICE),
+            vec![second_id]
+        );
+        assert_eq!(Issue::get_issue_requests_for_vault(BOB), vec![second_id]);
+
+        assert_ok!(cancel_issue(ALICE, &second_id));
+        assert!(Issue::get_issue_requests_for_requester(ALICE).is_empty());
+        assert!(Issue::get_issue_requests_for_vault(BOB).is_empty());
+    })
+}
+
+#[test]
+fn test_liquidation_reassigns_open_issue_requests_to_liquidation_vault() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 3))));
+
         let issue_id = request_issue_ok(ALICE, 3, BOB, 0);
+        <Issue as OnVaultLiquidated<AccountId>>::on_vault_liquidated(&BOB, &CAROL);
+
+        assert_eq!(<IssueRequests<Test>>::get(issue_id).vault, CAROL);
+        assert!(Issue::get_issue_requests_for_vault(BOB).is_empty());
+        assert_eq!(Issue::get_issue_requests_for_vault(CAROL), vec![issue_id]);
+
+        // the requester can still execute the issue request with the BTC
+        // paid to the liquidated vault
+        ext::vault_registry::issue_tokens::<Test>.mock_safe(|vault, _| {
+            assert_eq!(vault, &CAROL);
+            MockResult::Return(Ok(()))
+        });
+        <system::Module<Test>>::set_block_number(5);
+        execute_issue_ok(ALICE, &issue_id);
+    })
+}
+
+#[test]
+fn test_cancel_issue_splits_griefing_collateral() {
+    run_test(|| {
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_issuing_vault(BOB, 3))));
+        ext::vault_registry::decrease_to_be_issued_tokens::<Test>
+            .mock_safe(|_, _| MockResult::Return(Ok(())));
+        Issue::set_griefing_collateral_fee_share(25_000); // 25%
+
+        let issue_id = request_issue_ok(ALICE, 3, BOB, 8);
+        <system::Module<Test>>::set_block_number(12);
         assert_ok!(cancel_issue(ALICE, &issue_id));
+
+        let fee_pool = ext::fee::fee_pool_account_id::<Test>();
+        assert_eq!(
+            <collateral::Module<Test>>::get_collateral_from_account(&BOB),
+            6
+        );
+        assert_eq!(
+            <collateral::Module<Test>>::get_collateral_from_account(&fee_pool),
+            2
+        );
+        assert_eq!(<fee::Module<Test>>::epoch_dot_rewards(), 2);
+        let cancel_issue_event =
+            TestEvent::test_events(RawEvent::CancelIssue(issue_id, ALICE, 6, 2));
+        assert!(System::events()
+            .iter()
+            .any(|a| a.event == cancel_issue_event));
     })
 }
 
@@ -0,0 +1,31 @@
+//! Runtime API definition for the Redeem module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use bitcoin::types::H256Le;
+use codec::Codec;
+use sp_core::H256;
+use sp_std::prelude::*;
+use x_core::Error;
+
+sp_api::decl_runtime_apis! {
+    pub trait RedeemApi<AccountId> where
+        AccountId: Codec,
+    {
+        /// Checks the Bitcoin transaction of a redeem request against the current
+        /// state without executing the request
+        fn verify_and_validate_transaction(
+            redeem_id: H256,
+            tx_id: H256Le,
+            tx_block_height: u32,
+            merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+        ) -> Result<(), Error>;
+
+        /// Returns the ids of the open redeem requests of the redeemer
+        fn get_redeem_requests(account_id: AccountId) -> Vec<H256>;
+
+        /// Returns the ids of the open redeem requests the vault has to fulfill
+        fn get_vault_redeem_requests(vault_id: AccountId) -> Vec<H256>;
+    }
+}
@@ -3,6 +3,7 @@ use mocktopus::macros::mockable;
 
 #[cfg_attr(test, mockable)]
 pub(crate) mod btc_relay {
+    use bitcoin::address::BtcAddress;
     use bitcoin::types::H256Le;
     use sp_std::vec::Vec;
     use x_core::UnitResult;
@@ -24,10 +25,30 @@ pub(crate) mod btc_relay {
     pub fn validate_transaction<T: btc_relay::Trait>(
         raw_tx: Vec<u8>,
         amount: i64,
-        btc_address: Vec<u8>,
+        btc_address: BtcAddress,
         issue_id: Vec<u8>,
     ) -> UnitResult {
-        <btc_relay::Module<T>>::_validate_transaction(raw_tx, amount, btc_address, issue_id)
+        <btc_relay::Module<T>>::_validate_transaction(raw_tx, amount, btc_address, issue_id)?;
+        Ok(())
+    }
+
+    pub fn validate_batch_transaction<T: btc_relay::Trait>(
+        raw_tx: Vec<u8>,
+        output_index: u32,
+        amount: i64,
+        btc_address: BtcAddress,
+        redeem_id: Vec<u8>,
+        commitment_path: Vec<H256Le>,
+    ) -> UnitResult {
+        <btc_relay::Module<T>>::_validate_batch_transaction(
+            raw_tx,
+            output_index,
+            amount,
+            btc_address,
+            redeem_id,
+            commitment_path,
+        )?;
+        Ok(())
     }
 }
 
@@ -72,6 +93,13 @@ pub(crate) mod vault_registry {
         <vault_registry::Module<T>>::_redeem_tokens_liquidation(redeemer, redeem_dot_in_btc)
     }
 
+    pub fn decrease_to_be_redeemed_tokens<T: vault_registry::Trait>(
+        vault_id: &T::AccountId,
+        tokens: PolkaBTC<T>,
+    ) -> UnitResult {
+        <vault_registry::Module<T>>::_decrease_to_be_redeemed_tokens(vault_id, tokens)
+    }
+
     pub fn decrease_tokens<T: vault_registry::Trait>(
         vault_id: &T::AccountId,
         user_id: &T::AccountId,
@@ -87,6 +115,13 @@ pub(crate) mod vault_registry {
         <vault_registry::Module<T>>::_ban_vault(vault_id, height)
     }
 
+    pub fn record_failed_redeem<T: vault_registry::Trait>(
+        vault_id: &T::AccountId,
+        height: T::BlockNumber,
+    ) -> UnitResult {
+        <vault_registry::Module<T>>::_record_failed_redeem(vault_id, height)
+    }
+
     pub fn ensure_not_banned<T: vault_registry::Trait>(
         vault: &T::AccountId,
         height: T::BlockNumber,
@@ -102,8 +137,8 @@ pub(crate) mod vault_registry {
         <vault_registry::Module<T>>::_punishment_fee()
     }
 
-    pub fn get_redeem_premium_fee<T: vault_registry::Trait>() -> Result<DOT<T>> {
-        <vault_registry::Module<T>>::_get_redeem_premium_fee()
+    pub fn get_redeem_premium<T: vault_registry::Trait>(tokens: PolkaBTC<T>) -> Result<DOT<T>> {
+        <vault_registry::Module<T>>::_get_redeem_premium(tokens)
     }
 
     pub fn is_vault_below_premium_threshold<T: vault_registry::Trait>(
@@ -125,6 +160,17 @@ pub(crate) mod collateral {
     ) -> UnitResult {
         <collateral::Module<T>>::slash_collateral(sender.clone(), receiver.clone(), amount)
     }
+
+    pub fn release_collateral<T: collateral::Trait>(
+        sender: &T::AccountId,
+        amount: DOT<T>,
+    ) -> UnitResult {
+        <collateral::Module<T>>::release_collateral(sender, amount)
+    }
+
+    pub fn get_collateral_from_account<T: collateral::Trait>(account: &T::AccountId) -> DOT<T> {
+        <collateral::Module<T>>::get_collateral_from_account(account)
+    }
 }
 
 #[cfg_attr(test, mockable)]
@@ -136,19 +182,53 @@ pub(crate) mod treasury {
         <treasury::Module<T>>::get_balance_from_account(account)
     }
 
+    pub fn get_locked_balance<T: treasury::Trait>(account: T::AccountId) -> PolkaBTC<T> {
+        <treasury::Module<T>>::get_locked_balance_from_account(account)
+    }
+
     pub fn get_total_supply<T: treasury::Trait>() -> PolkaBTC<T> {
         <treasury::Module<T>>::get_total_supply()
     }
 
+    pub fn transfer<T: treasury::Trait>(
+        sender: T::AccountId,
+        receiver: T::AccountId,
+        amount: PolkaBTC<T>,
+    ) -> UnitResult {
+        <treasury::Module<T>>::_transfer(sender, receiver, amount)
+    }
+
     pub fn lock<T: treasury::Trait>(redeemer: T::AccountId, amount: PolkaBTC<T>) -> UnitResult {
         <treasury::Module<T>>::lock(redeemer, amount)
     }
 
+    pub fn unlock<T: treasury::Trait>(redeemer: T::AccountId, amount: PolkaBTC<T>) -> UnitResult {
+        <treasury::Module<T>>::unlock(redeemer, amount)
+    }
+
     pub fn burn<T: treasury::Trait>(redeemer: T::AccountId, amount: PolkaBTC<T>) -> UnitResult {
         <treasury::Module<T>>::burn(redeemer, amount)
     }
 }
 
+#[cfg_attr(test, mockable)]
+pub(crate) mod fee {
+    use crate::types::PolkaBTC;
+    use x_core::Result;
+
+    pub fn fee_pool_account_id<T: fee::Trait>() -> T::AccountId {
+        <fee::Module<T>>::fee_pool_account_id()
+    }
+
+    pub fn get_redeem_fee<T: fee::Trait>(amount: PolkaBTC<T>) -> Result<PolkaBTC<T>> {
+        <fee::Module<T>>::get_redeem_fee(amount)
+    }
+
+    pub fn increase_rewards_for_epoch<T: fee::Trait>(amount: PolkaBTC<T>) {
+        <fee::Module<T>>::increase_rewards_for_epoch(amount)
+    }
+}
+
 #[cfg_attr(test, mockable)]
 pub(crate) mod security {
     use primitive_types::H256;
@@ -17,30 +17,48 @@ mod ext;
 pub mod types;
 
 use crate::types::{PolkaBTC, Redeem, DOT};
+use bitcoin::address::BtcAddress;
 use bitcoin::types::H256Le;
 /// # PolkaBTC Redeem implementation
 /// The Redeem module according to the specification at
 /// https://interlay.gitlab.io/polkabtc-spec/spec/redeem.html
 // Substrate
-use frame_support::{decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure};
+use frame_support::{
+    decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure, traits::Get,
+    weights::Weight,
+};
 use primitive_types::H256;
 use security::ErrorCode;
-use sp_core::H160;
-use sp_runtime::ModuleId;
+use sp_runtime::{
+    traits::{One, Zero},
+    ModuleId,
+};
 use sp_std::convert::TryInto;
 use sp_std::vec::Vec;
 use system::ensure_signed;
-use x_core::{Error, UnitResult};
+use x_core::{index, Error, UnitResult};
 
 /// The redeem module id, used for deriving its sovereign account ID.
 const _MODULE_ID: ModuleId = ModuleId(*b"i/redeem");
 
+/// The weight of cancelling a single expired redeem request.
+const EXPIRE_REDEEM_WEIGHT: Weight = 1000;
+
 /// The pallet's configuration trait.
 pub trait Trait:
-    system::Trait + vault_registry::Trait + collateral::Trait + btc_relay::Trait + treasury::Trait
+    system::Trait
+    + vault_registry::Trait
+    + collateral::Trait
+    + btc_relay::Trait
+    + treasury::Trait
+    + fee::Trait
 {
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+    /// The maximum number of expired redeem requests which are cancelled
+    /// automatically at the start of a block.
+    type MaxExpiredRedeemsPerBlock: Get<u32>;
 }
 
 // The pallet's storage items.
@@ -48,11 +66,26 @@ decl_storage! {
     trait Store for Module<T: Trait> as Redeem {
         /// The time difference in number of blocks between a redeem request is created and required completion time by a vault.
         /// The redeem period has an upper limit to ensure the user gets their BTC in time and to potentially punish a vault for inactivity or stealing BTC.
-        RedeemPeriod get(fn redeem_period): T::BlockNumber;
+        RedeemPeriod get(fn redeem_period) config(): T::BlockNumber;
 
         /// Users create redeem requests to receive BTC in return for PolkaBTC.
         /// This mapping provides access from a unique hash redeemId to a Redeem struct.
         RedeemRequests: map hasher(blake2_128_concat) H256 => Redeem<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>;
+
+        /// Ids of the open redeem requests of each redeemer
+        RedeemRequestsByRedeemer: map hasher(blake2_128_concat) T::AccountId => Vec<H256>;
+
+        /// Ids of the open redeem requests assigned to each vault
+        RedeemRequestsByVault: map hasher(blake2_128_concat) T::AccountId => Vec<H256>;
+
+        /// Ids of the redeem requests expiring at each block, which are
+        /// cancelled automatically once the block is reached.
+        RedeemExpiryQueue: map hasher(blake2_128_concat) T::BlockNumber => Vec<H256>;
+
+        /// The next block of the `RedeemExpiryQueue` to be processed. It is
+        /// behind the current block if more requests expired than could be
+        /// cancelled in a single block.
+        NextRedeemExpiry: T::BlockNumber;
     }
 }
 
@@ -62,10 +95,18 @@ decl_event!(
     where
         AccountId = <T as system::Trait>::AccountId,
         PolkaBTC = PolkaBTC<T>,
+        DOT = DOT<T>,
     {
-        RequestRedeem(H256, AccountId, PolkaBTC, AccountId, H160),
-        ExecuteRedeem(H256, AccountId, AccountId),
+        RequestRedeem(H256, AccountId, PolkaBTC, AccountId, BtcAddress),
+        /// redeem id, redeemer, vault, premium paid to the redeemer in DOT
+        ExecuteRedeem(H256, AccountId, AccountId, DOT),
         CancelRedeem(H256, AccountId),
+        /// redeem id, vault
+        ExpireRedeem(H256, AccountId),
+        /// redeem id
+        ExpireRedeemFailed(H256),
+        /// redeemer, amount of PolkaBTC burned
+        RedeemLiquidation(AccountId, PolkaBTC),
     }
 );
 
@@ -77,17 +118,19 @@ decl_module! {
         // this is needed only if you are using events in your pallet
         fn deposit_event() = default;
 
+        const MaxExpiredRedeemsPerBlock: u32 = T::MaxExpiredRedeemsPerBlock::get();
+
         /// A user requests to start the redeem procedure. This function checks the BTC Parachain
         /// status in Security and decides how the Redeem process is to be executed.
         ///
         /// # Arguments
         ///
         /// * `origin` - sender of the transaction
-        /// * `amount` - amount of PolkaBTC
+        /// * `amount` - amount of PolkaBTC, including the redeem fee
         /// * `btc_address` - the address to receive BTC
         /// * `vault` - address of the vault
         #[weight = 1000]
-        fn request_redeem(origin, amount_polka_btc: PolkaBTC<T>, btc_address: H160, vault_id: T::AccountId)
+        fn request_redeem(origin, amount_polka_btc: PolkaBTC<T>, btc_address: BtcAddress, vault_id: T::AccountId)
             -> DispatchResult
         {
             let redeemer = ensure_signed(origin)?;
@@ -99,6 +142,22 @@ decl_module! {
                 amount_polka_btc <= redeemer_balance,
                 Error::AmountExceedsUserBalance
             );
+
+            // the fee is paid into the fee pool and only the remainder is redeemed
+            let fee_polka_btc = ext::fee::get_redeem_fee::<T>(amount_polka_btc)?;
+            let amount_polka_btc = amount_polka_btc - fee_polka_btc;
+
+            // while the BTC Parachain is liquidating vaults, a share of the PolkaBTC is
+            // redeemed for DOT against the LiquidationVault right away, the rest with the vault
+            let raw_amount_dot_in_btc = if ext::security::is_parachain_error_liquidation::<T>() {
+                Self::partial_redeem(Self::btc_to_u128(amount_polka_btc)?)?
+            } else {
+                0
+            };
+            let amount_dot_in_btc = Self::u128_to_btc(raw_amount_dot_in_btc)?;
+            let amount_dot = Self::u128_to_dot(Self::rawbtc_to_rawdot(raw_amount_dot_in_btc)?)?;
+            let amount_polka_btc = amount_polka_btc - amount_dot_in_btc;
+
             let vault = ext::vault_registry::get_vault_from_id::<T>(&vault_id)?;
             let height = <system::Module<T>>::block_number();
             ext::vault_registry::ensure_not_banned::<T>(&vault_id, height)?;
@@ -106,48 +165,51 @@ decl_module! {
                 amount_polka_btc <= vault.issued_tokens,
                 Error::AmountExceedsVaultBalance
             );
-
-            let (amount_btc, amount_dot): (u128, u128) =
-                if ext::security::is_parachain_error_liquidation::<T>() {
-                    let raw_amount_polka_btc = Self::btc_to_u128(amount_polka_btc)?;
-                    let amount_dot_in_btc = Self::partial_redeem(raw_amount_polka_btc)?;
-                    let amount_btc: u128 = raw_amount_polka_btc - amount_dot_in_btc;
-                    let amount_dot: u128 = Self::rawbtc_to_rawdot(amount_dot_in_btc)?;
-                    (
-                        amount_btc,
-                        amount_dot.try_into().map_err(|_e| Error::RuntimeError)?,
-                    )
-                } else {
-                    (Self::btc_to_u128(amount_polka_btc)?, 0)
-                };//how much you locked
-            ext::vault_registry::increase_to_be_redeemed_tokens::<T>(
-                &vault_id,
-                amount_btc.try_into().map_err(|_e| Error::RuntimeError)?,
-            )?;
-            if amount_dot > 0 {
-                ext::vault_registry::redeem_tokens_liquidation::<T>(
-                    &vault_id,
-                    amount_dot.try_into().map_err(|_e| Error::RuntimeError)?,
-                )?;
-            }
-            ext::treasury::lock::<T>(redeemer.clone(), amount_polka_btc)?;
-            let redeem_id = ext::security::get_secure_id::<T>(&redeemer);
+            index::ensure_capacity::<RedeemRequestsByRedeemer<T>, _, _>(&redeemer, 1)?;
 
             let below_premium_redeem = ext::vault_registry::is_vault_below_premium_threshold::<T>(&vault_id)?;
             let premium_dot = if below_premium_redeem {
-                ext::vault_registry::get_redeem_premium_fee::<T>()?
+                ext::vault_registry::get_redeem_premium::<T>(amount_polka_btc)?
             } else {
                 Self::u128_to_dot(0u128)?
             };
 
+            if amount_dot_in_btc > 0.into() {
+                ext::vault_registry::redeem_tokens_liquidation::<T>(&redeemer, amount_dot_in_btc)?;
+                ext::treasury::lock::<T>(redeemer.clone(), amount_dot_in_btc)?;
+                ext::treasury::burn::<T>(redeemer.clone(), amount_dot_in_btc)?;
+                Self::deposit_event(<Event<T>>::RedeemLiquidation(
+                    redeemer.clone(),
+                    amount_dot_in_btc,
+                ));
+            }
+            // the fee is transferred after all other checks passed
+            if fee_polka_btc > 0.into() {
+                ext::treasury::transfer::<T>(
+                    redeemer.clone(),
+                    ext::fee::fee_pool_account_id::<T>(),
+                    fee_polka_btc,
+                )?;
+                ext::fee::increase_rewards_for_epoch::<T>(fee_polka_btc);
+            }
+            if amount_polka_btc == 0.into() {
+                // all of the PolkaBTC was redeemed for DOT
+                return Ok(());
+            }
+
+            ext::vault_registry::increase_to_be_redeemed_tokens::<T>(&vault_id, amount_polka_btc)?;
+            ext::treasury::lock::<T>(redeemer.clone(), amount_polka_btc)?;
+            let redeem_id = ext::security::get_secure_id::<T>(&redeemer);
+
             Self::insert_redeem_request(
                 redeem_id,
                 Redeem {
                     vault: vault_id.clone(),
                     opentime: height,
                     amount_polka_btc,
-                    amount_btc: amount_btc.try_into().map_err(|_e| Error::RuntimeError)?,
-                    amount_dot: amount_dot.try_into().map_err(|_e| Error::RuntimeError)?,
+                    fee_polka_btc,
+                    amount_btc: amount_polka_btc,
+                    amount_dot,
                     premium_dot,
                     redeemer: redeemer.clone(),
                     btc_address,
@@ -186,40 +248,65 @@ decl_module! {
 
             let redeem = Self::get_redeem_request_from_id(&redeem_id)?;
             ensure!(vault_id == redeem.vault, Error::UnauthorizedVault);
-            let height = <system::Module<T>>::block_number();
-            let period = Self::redeem_period();
-            ensure!(
-                redeem.opentime + period < height,
-                Error::CommitPeriodExpired
-            );
-            let amount: usize = redeem
-                .amount_btc
-                .try_into()
-                .map_err(|_e| Error::RuntimeError)?;
-            ext::btc_relay::verify_transaction_inclusion::<T>(tx_id, tx_block_height, merkle_proof)?;
-            ext::btc_relay::validate_transaction::<T>(
+            Self::verify_and_validate_transaction(
+                redeem_id,
+                &redeem,
+                tx_id,
+                tx_block_height,
+                merkle_proof,
                 raw_tx,
-                amount as i64,
-                redeem.btc_address.as_bytes().to_vec(),
-                redeem_id.clone().as_bytes().to_vec(),
             )?;
-            ext::treasury::burn::<T>(redeem.redeemer.clone(), redeem.amount_polka_btc)?;
-            if redeem.premium_dot > 0.into() {
-                ext::vault_registry::redeem_tokens_premium::<T>(
-                    &redeem.vault,
-                    redeem.amount_polka_btc,
-                    redeem.premium_dot,
-                    &redeem.redeemer,
-                )?;
-            } else {
-                ext::vault_registry::redeem_tokens::<T>(&redeem.vault, redeem.amount_polka_btc)?;
-            }
-            <RedeemRequests<T>>::remove(redeem_id);
-            Self::deposit_event(<Event<T>>::ExecuteRedeem(
-                redeem_id,
-                redeem.redeemer,
-                redeem.vault,
-            ));
+            Self::complete_redeem(redeem_id, redeem)?;
+            Ok(())
+        }
+
+        /// Completes a redeem request with a Bitcoin transaction that pays several redeem
+        /// requests at once. The OP_RETURN output of the transaction contains the depth and
+        /// root of the merkle tree of the ids of all paid requests, where the id at leaf
+        /// `output_index` is paid by the output at the same index.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the vault responsible for executing this redeem request
+        /// * `redeem_id` - identifier of redeem request as output from request_redeem
+        /// * `tx_id` - transaction hash
+        /// * `tx_block_height` - block number of backing chain
+        /// * `merkle_proof` - raw bytes
+        /// * `raw_tx` - raw bytes
+        /// * `output_index` - index of the output paying the redeemer
+        /// * `commitment_path` - sibling hashes from `redeem_id` up to the committed merkle root
+        #[weight = 1000]
+        fn execute_batched_redeem(
+            origin,
+            redeem_id: H256,
+            tx_id: H256Le,
+            tx_block_height: u32,
+            merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+            output_index: u32,
+            commitment_path: Vec<H256Le>
+        ) -> DispatchResult {
+            let vault_id = ensure_signed(origin)?;
+
+            ext::security::ensure_parachain_status_running::<T>()?;
+
+            let redeem = Self::get_redeem_request_from_id(&redeem_id)?;
+            ensure!(vault_id == redeem.vault, Error::UnauthorizedVault);
+            let amount = Self::verify_transaction_inclusion(
+                &redeem,
+                tx_id,
+                tx_block_height,
+                merkle_proof,
+            )?;
+            ext::btc_relay::validate_batch_transaction::<T>(
+                raw_tx,
+                output_index,
+                amount,
+                redeem.btc_address,
+                redeem_id.as_bytes().to_vec(),
+                commitment_path,
+            )?;
+            Self::complete_redeem(redeem_id, redeem)?;
             Ok(())
         }
 
@@ -244,49 +331,207 @@ decl_module! {
 
             let height = <system::Module<T>>::block_number();
             let period = Self::redeem_period();
-            ensure!(redeem.opentime + period > height, Error::TimeNotExpired);
-
-            let punishment_fee = ext::vault_registry::punishment_fee::<T>()?;
-            let raw_punishment_fee = Self::dot_to_u128(punishment_fee)?;
-            let raw_amount_polka_btc = Self::btc_to_u128(redeem.amount_polka_btc)?;
-            let raw_amount_in_dot = Self::rawbtc_to_rawdot(raw_amount_polka_btc)?;
-            if reimburse {
-                ext::vault_registry::decrease_tokens::<T>(
-                    &redeem.vault,
-                    &redeem.redeemer,
-                    redeem.amount_polka_btc,
-                )?;
-                ext::treasury::burn::<T>(redeem.redeemer.clone(), redeem.amount_polka_btc)?;
-                let reimburse_in_dot = raw_amount_in_dot
-                    .checked_mul(100_000 + raw_punishment_fee).ok_or(Error::RuntimeError)?
-                    .checked_div(100_000).ok_or(Error::RuntimeError)?;
-                let reimburse_amount: DOT<T> = reimburse_in_dot
-                    .try_into()
-                    .map_err(|_| Error::RuntimeError)?;
-                ext::collateral::slash_collateral::<T>(
-                    &redeem.redeemer,
-                    &redeem.vault,
-                    reimburse_amount,
-                )?;
-            } else {
-                let slash_in_dot = raw_amount_in_dot
-                    .checked_mul(raw_punishment_fee).ok_or(Error::RuntimeError)?
-                    .checked_div(100_000).ok_or(Error::RuntimeError)?;
-                let slash_amount: DOT<T> = Self::u128_to_dot(slash_in_dot)?;
-                ext::collateral::slash_collateral::<T>(&redeem.redeemer, &redeem.vault, slash_amount)?;
-            }
-            ext::vault_registry::ban_vault::<T>(redeem.vault, height)?;
-            <RedeemRequests<T>>::remove(redeem_id);
+            ensure!(height > redeem.opentime + period, Error::TimeNotExpired);
+
+            Self::cancel_redeem_request(redeem_id, &redeem, reimburse, height)?;
             Self::deposit_event(<Event<T>>::CancelRedeem(redeem_id, redeemer));
 
             Ok(())
         }
+
+        /// Burns PolkaBTC in exchange for DOT from the collateral of the
+        /// LiquidationVault, valued at the current exchange rate plus a premium.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - sender of the transaction
+        /// * `amount_polka_btc` - amount of PolkaBTC to burn
+        #[weight = 1000]
+        fn redeem_liquidation(origin, amount_polka_btc: PolkaBTC<T>)
+            -> DispatchResult
+        {
+            let redeemer = ensure_signed(origin)?;
+            Self::_redeem_liquidation(redeemer, amount_polka_btc)?;
+            Ok(())
+        }
+
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            let expired = Self::begin_block(n);
+            EXPIRE_REDEEM_WEIGHT.saturating_mul(expired as Weight)
+        }
     }
 }
 
 // "Internal" functions, callable by code.
 #[cfg_attr(test, mockable)]
 impl<T: Trait> Module<T> {
+    /// Redeems PolkaBTC against the LiquidationVault, which pays out DOT
+    /// from the collateral of liquidated vaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `redeemer` - account burning the PolkaBTC
+    /// * `amount_polka_btc` - amount of PolkaBTC to burn
+    fn _redeem_liquidation(redeemer: T::AccountId, amount_polka_btc: PolkaBTC<T>) -> UnitResult {
+        let redeemer_balance = ext::treasury::get_balance::<T>(redeemer.clone());
+        ensure!(
+            amount_polka_btc <= redeemer_balance,
+            Error::AmountExceedsUserBalance
+        );
+
+        ext::vault_registry::redeem_tokens_liquidation::<T>(&redeemer, amount_polka_btc)?;
+        ext::treasury::lock::<T>(redeemer.clone(), amount_polka_btc)?;
+        ext::treasury::burn::<T>(redeemer.clone(), amount_polka_btc)?;
+
+        Self::deposit_event(<Event<T>>::RedeemLiquidation(redeemer, amount_polka_btc));
+        Ok(())
+    }
+
+    /// Cancels redeem requests which expired at or before `height`. At most
+    /// `MaxExpiredRedeemsPerBlock` requests are processed, the rest remain
+    /// queued for the following blocks. Nothing is processed unless the
+    /// parachain is running. Returns the number of processed requests.
+    fn begin_block(height: T::BlockNumber) -> u32 {
+        // requesters cannot execute their requests unless the parachain is
+        // running, so the queue is only processed while it is
+        if ext::security::ensure_parachain_status_running::<T>().is_err() {
+            return 0;
+        }
+        let mut expiry = <NextRedeemExpiry<T>>::get();
+        if expiry.is_zero() {
+            // no redeem request has been queued yet
+            return 0;
+        }
+
+        let max_expired = T::MaxExpiredRedeemsPerBlock::get() as usize;
+        let mut remaining = max_expired;
+        while expiry <= height && remaining > 0 {
+            let mut redeem_ids = <RedeemExpiryQueue<T>>::take(expiry);
+            let pending = redeem_ids.split_off(redeem_ids.len().min(remaining));
+            remaining -= redeem_ids.len();
+
+            for redeem_id in redeem_ids {
+                // a request which failed to expire remains open and can
+                // still be cancelled
+                if Self::expire_redeem(redeem_id, height).is_err() {
+                    Self::deposit_event(<Event<T>>::ExpireRedeemFailed(redeem_id));
+                }
+            }
+
+            if !pending.is_empty() {
+                <RedeemExpiryQueue<T>>::insert(expiry, pending);
+                break;
+            }
+            expiry += One::one();
+        }
+        <NextRedeemExpiry<T>>::put(expiry);
+        (max_expired - remaining) as u32
+    }
+
+    /// Cancels an expired redeem request, unless it has already been
+    /// executed or cancelled. The redeemer keeps the PolkaBTC and is
+    /// compensated by slashing the vault, as if the request was cancelled
+    /// without reimbursement.
+    fn expire_redeem(redeem_id: H256, height: T::BlockNumber) -> UnitResult {
+        if !<RedeemRequests<T>>::contains_key(redeem_id) {
+            return Ok(());
+        }
+        let redeem = <RedeemRequests<T>>::get(redeem_id);
+
+        Self::cancel_redeem_request(redeem_id, &redeem, false, height)?;
+
+        Self::deposit_event(<Event<T>>::ExpireRedeem(redeem_id, redeem.vault));
+        Ok(())
+    }
+
+    /// Compensates the redeemer by slashing the vault, bans the vault, records
+    /// the failed redeem of the vault and removes the redeem request. Unless
+    /// the redeemer is reimbursed, the locked PolkaBTC is returned to the
+    /// redeemer and the vault's to-be-redeemed tokens are released.
+    ///
+    /// # Arguments
+    ///
+    /// * `redeem_id` - identifier of the redeem request
+    /// * `redeem` - the redeem request
+    /// * `reimburse` - whether the redeemer is reimbursed in DOT in exchange
+    /// for the PolkaBTC, or keeps the PolkaBTC
+    /// * `height` - current block height
+    fn cancel_redeem_request(
+        redeem_id: H256,
+        redeem: &Redeem<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>,
+        reimburse: bool,
+        height: T::BlockNumber,
+    ) -> UnitResult {
+        let punishment_fee = ext::vault_registry::punishment_fee::<T>()?;
+        let raw_punishment_fee = Self::dot_to_u128(punishment_fee)?;
+        let raw_amount_polka_btc = Self::btc_to_u128(redeem.amount_polka_btc)?;
+        let raw_amount_in_dot = Self::rawbtc_to_rawdot(raw_amount_polka_btc)?;
+        // the redeemer is either reimbursed the value of the PolkaBTC plus the
+        // punishment fee, or only receives the punishment fee
+        let raw_compensation = if reimburse {
+            raw_amount_in_dot
+                .checked_mul(100_000 + raw_punishment_fee)
+                .ok_or(Error::RuntimeError)?
+        } else {
+            raw_amount_in_dot
+                .checked_mul(raw_punishment_fee)
+                .ok_or(Error::RuntimeError)?
+        }
+        .checked_div(100_000)
+        .ok_or(Error::RuntimeError)?;
+        let compensation = Self::u128_to_dot(raw_compensation)?;
+
+        // all checks run before the first write
+        let vault = ext::vault_registry::get_vault_from_id::<T>(&redeem.vault)?;
+        if reimburse {
+            ensure!(
+                vault.to_be_redeemed_tokens >= redeem.amount_polka_btc
+                    && vault.issued_tokens >= redeem.amount_polka_btc,
+                Error::InsufficientTokensCommitted
+            );
+        } else {
+            ensure!(
+                vault.to_be_redeemed_tokens >= redeem.amount_btc,
+                Error::InsufficientTokensCommitted
+            );
+        }
+        ensure!(
+            ext::treasury::get_locked_balance::<T>(redeem.redeemer.clone())
+                >= redeem.amount_polka_btc,
+            Error::InsufficientLockedFunds
+        );
+        // the vault cannot be slashed for more than its remaining collateral
+        let compensation = compensation.min(ext::collateral::get_collateral_from_account::<T>(
+            &redeem.vault,
+        ));
+
+        if reimburse {
+            ext::vault_registry::decrease_tokens::<T>(
+                &redeem.vault,
+                &redeem.redeemer,
+                redeem.amount_polka_btc,
+            )?;
+            ext::treasury::burn::<T>(redeem.redeemer.clone(), redeem.amount_polka_btc)?;
+        } else {
+            // the redeemer keeps the PolkaBTC and may redeem with another vault
+            ext::vault_registry::decrease_to_be_redeemed_tokens::<T>(
+                &redeem.vault,
+                redeem.amount_btc,
+            )?;
+            ext::treasury::unlock::<T>(redeem.redeemer.clone(), redeem.amount_polka_btc)?;
+        }
+        if !compensation.is_zero() {
+            ext::collateral::slash_collateral::<T>(&redeem.vault, &redeem.redeemer, compensation)?;
+            // the slashed collateral is paid out as free DOT
+            ext::collateral::release_collateral::<T>(&redeem.redeemer, compensation)?;
+        }
+        ext::vault_registry::ban_vault::<T>(redeem.vault.clone(), height)?;
+        // vaults which repeatedly fail to execute redeems are liquidated
+        ext::vault_registry::record_failed_redeem::<T>(&redeem.vault, height)?;
+        Self::remove_redeem_request(redeem_id);
+        Ok(())
+    }
+
     /// Insert a new redeem request into state.
     ///
     /// # Arguments
@@ -297,9 +542,51 @@ impl<T: Trait> Module<T> {
         key: H256,
         value: Redeem<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>,
     ) {
+        index::insert::<RedeemRequestsByRedeemer<T>, _, _>(&value.redeemer, key);
+        index::insert::<RedeemRequestsByVault<T>, _, _>(&value.vault, key);
+
+        // a request can still be executed at `opentime + period`, so it
+        // expires in the following block. Requests are never queued at
+        // blocks which were already processed.
+        let next_expiry = <NextRedeemExpiry<T>>::get();
+        let expiry = (value.opentime + Self::redeem_period() + One::one()).max(next_expiry);
+        <RedeemExpiryQueue<T>>::mutate(expiry, |ids| ids.push(key));
+        if next_expiry.is_zero() {
+            <NextRedeemExpiry<T>>::put(expiry);
+        }
+
         <RedeemRequests<T>>::insert(key, value)
     }
 
+    /// Remove a redeem request from state.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - 256-bit identifier of the redeem request
+    fn remove_redeem_request(key: H256) {
+        let redeem = <RedeemRequests<T>>::take(key);
+        index::remove::<RedeemRequestsByRedeemer<T>, _, _>(&redeem.redeemer, key);
+        index::remove::<RedeemRequestsByVault<T>, _, _>(&redeem.vault, key);
+    }
+
+    /// Returns the ids of the open redeem requests of the redeemer
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - account of the redeemer
+    pub fn get_redeem_requests_for_redeemer(account_id: T::AccountId) -> Vec<H256> {
+        <RedeemRequestsByRedeemer<T>>::get(account_id)
+    }
+
+    /// Returns the ids of the open redeem requests the vault has to fulfill
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - account of the vault
+    pub fn get_redeem_requests_for_vault(vault_id: T::AccountId) -> Vec<H256> {
+        <RedeemRequestsByVault<T>>::get(vault_id)
+    }
+
     /// Fetch a pre-existing redeem request or throw.
     ///
     /// # Arguments
@@ -315,20 +602,131 @@ impl<T: Trait> Module<T> {
         Ok(<RedeemRequests<T>>::get(*key))
     }
 
+    /// Checks the Bitcoin transaction of a redeem request against the current
+    /// state without executing the request. Returns the error `execute_redeem`
+    /// would fail with, except for the check of the vault.
+    ///
+    /// # Arguments
+    ///
+    /// * `redeem_id` - identifier of redeem request as output from request_redeem
+    /// * `tx_id` - transaction hash
+    /// * `tx_block_height` - block number of backing chain
+    /// * `merkle_proof` - raw bytes
+    /// * `raw_tx` - raw bytes
+    pub fn _verify_and_validate_transaction(
+        redeem_id: H256,
+        tx_id: H256Le,
+        tx_block_height: u32,
+        merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> UnitResult {
+        ext::security::ensure_parachain_status_running::<T>()?;
+
+        let redeem = Self::get_redeem_request_from_id(&redeem_id)?;
+        Self::verify_and_validate_transaction(
+            redeem_id,
+            &redeem,
+            tx_id,
+            tx_block_height,
+            merkle_proof,
+            raw_tx,
+        )
+    }
+
+    /// Checks that the redeem request has not expired and that the Bitcoin
+    /// transaction is included in the main chain and pays the redeemer.
+    fn verify_and_validate_transaction(
+        redeem_id: H256,
+        redeem: &Redeem<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>,
+        tx_id: H256Le,
+        tx_block_height: u32,
+        merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> UnitResult {
+        let amount =
+            Self::verify_transaction_inclusion(redeem, tx_id, tx_block_height, merkle_proof)?;
+        ext::btc_relay::validate_transaction::<T>(
+            raw_tx,
+            amount,
+            redeem.btc_address,
+            redeem_id.clone().as_bytes().to_vec(),
+        )
+    }
+
+    /// Checks that the redeem request has not expired and that the Bitcoin
+    /// transaction is included in the main chain. Returns the amount of BTC
+    /// the transaction must pay to the redeemer.
+    fn verify_transaction_inclusion(
+        redeem: &Redeem<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>,
+        tx_id: H256Le,
+        tx_block_height: u32,
+        merkle_proof: Vec<u8>,
+    ) -> Result<i64, Error> {
+        let height = <system::Module<T>>::block_number();
+        let period = Self::redeem_period();
+        ensure!(
+            height <= redeem.opentime + period,
+            Error::CommitPeriodExpired
+        );
+        let amount: usize = redeem
+            .amount_btc
+            .try_into()
+            .map_err(|_e| Error::RuntimeError)?;
+        ext::btc_relay::verify_transaction_inclusion::<T>(tx_id, tx_block_height, merkle_proof)?;
+        Ok(amount as i64)
+    }
+
+    /// Burns the redeemed PolkaBTC, releases the vault's tokens and removes
+    /// the redeem request once its Bitcoin payment has been verified.
+    fn complete_redeem(
+        redeem_id: H256,
+        redeem: Redeem<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>,
+    ) -> UnitResult {
+        ext::treasury::burn::<T>(redeem.redeemer.clone(), redeem.amount_polka_btc)?;
+        if redeem.premium_dot > 0.into() {
+            ext::vault_registry::redeem_tokens_premium::<T>(
+                &redeem.vault,
+                redeem.amount_polka_btc,
+                redeem.premium_dot,
+                &redeem.redeemer,
+            )?;
+        } else {
+            ext::vault_registry::redeem_tokens::<T>(&redeem.vault, redeem.amount_polka_btc)?;
+        }
+        Self::remove_redeem_request(redeem_id);
+        Self::deposit_event(<Event<T>>::ExecuteRedeem(
+            redeem_id,
+            redeem.redeemer,
+            redeem.vault,
+            redeem.premium_dot,
+        ));
+        Ok(())
+    }
+
     /// Ensure that the parachain is running or a vault is being liquidated.
     fn ensure_parachain_running_or_error_liquidated() -> UnitResult {
-        ext::security::ensure_parachain_status_has_only_specific_errors::<T>(
-            [ErrorCode::Liquidation].to_vec(),
-        )?;
-        ext::security::ensure_parachain_status_running::<T>()
+        if ext::security::is_parachain_error_liquidation::<T>() {
+            ext::security::ensure_parachain_status_has_only_specific_errors::<T>(
+                [ErrorCode::Liquidation].to_vec(),
+            )
+        } else {
+            ext::security::ensure_parachain_status_running::<T>()
+        }
     }
 
     /// Calculates the fraction of BTC to be redeemed in DOT when the
-    /// BTC Parachain state is in ERROR state due to a LIQUIDATION error.
+    /// BTC Parachain state is in ERROR state due to a LIQUIDATION error:
+    /// the share of the total supply of PolkaBTC issued by the
+    /// LiquidationVault, in units of 10^-5.
     fn get_partial_redeem_factor() -> Result<u128, Error> {
         let total_liquidation_value = ext::vault_registry::total_liquidation_value::<T>()?;
         let total_supply = Self::btc_to_u128(ext::treasury::get_total_supply::<T>())?;
-        Ok(total_liquidation_value / total_supply)
+        Ok(total_liquidation_value
+            .checked_mul(100_000)
+            .ok_or(Error::RuntimeError)?
+            .checked_div(total_supply)
+            .unwrap_or(0)
+            .min(100_000))
     }
 
     fn btc_to_u128(amount: PolkaBTC<T>) -> Result<u128, Error> {
@@ -360,3 +758,17 @@ impl<T: Trait> Module<T> {
             .ok_or(Error::RuntimeError)
     }
 }
+
+impl<T: Trait> vault_registry::OnVaultLiquidated<T::AccountId> for Module<T> {
+    /// Reassigns the open redeem requests of a liquidated vault to the
+    /// `LiquidationVault`, such that they can still be cancelled.
+    fn on_vault_liquidated(vault_id: &T::AccountId, liquidation_vault_id: &T::AccountId) {
+        for redeem_id in <RedeemRequestsByVault<T>>::take(vault_id) {
+            <RedeemRequests<T>>::mutate(redeem_id, |redeem| {
+                redeem.vault = liquidation_vault_id.clone()
+            });
+            // the LiquidationVault is not limited in its number of open requests
+            index::insert::<RedeemRequestsByVault<T>, _, _>(liquidation_vault_id, redeem_id);
+        }
+    }
+}
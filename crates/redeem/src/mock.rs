@@ -34,6 +34,7 @@ impl_outer_event! {
         collateral<T>,
         btc_relay,
         treasury<T>,
+        fee<T>,
         exchange_rate_oracle<T>,
         security,
     }
@@ -95,6 +96,7 @@ impl pallet_balances::Trait for Test {
 
 impl vault_registry::Trait for Test {
     type Event = TestEvent;
+    type OnVaultLiquidated = Redeem;
 }
 
 impl collateral::Trait for Test {
@@ -115,6 +117,15 @@ impl treasury::Trait for Test {
     type Event = TestEvent;
 }
 
+parameter_types! {
+    pub const EpochPeriod: u64 = 100;
+}
+
+impl fee::Trait for Test {
+    type Event = TestEvent;
+    type EpochPeriod = EpochPeriod;
+}
+
 parameter_types! {
     pub const MinimumPeriod: u64 = 5;
 }
@@ -128,8 +139,13 @@ impl exchange_rate_oracle::Trait for Test {
     type Event = TestEvent;
 }
 
+parameter_types! {
+    pub const MaxExpiredRedeemsPerBlock: u32 = 2;
+}
+
 impl Trait for Test {
     type Event = TestEvent;
+    type MaxExpiredRedeemsPerBlock = MaxExpiredRedeemsPerBlock;
 }
 
 pub type System = system::Module<Test>;
@@ -1,15 +1,18 @@
 use crate::ext;
 use crate::mock::*;
+use crate::{RedeemPeriod, RedeemRequestsByRedeemer, EXPIRE_REDEEM_WEIGHT};
 
 use crate::types::{PolkaBTC, Redeem as RedeemRequest, DOT};
+use bitcoin::address::BtcAddress;
 use bitcoin::types::H256Le;
-use frame_support::{assert_err, assert_noop, assert_ok};
+use frame_support::{
+    assert_err, assert_noop, assert_ok, traits::OnInitialize, StorageMap, StorageValue,
+};
 use mocktopus::mocking::*;
 use primitive_types::H256;
-use sp_core::H160;
 use sp_std::convert::TryInto;
-use vault_registry::Vault;
-use x_core::Error;
+use vault_registry::{Vault, Wallet};
+use x_core::{index, Error};
 
 type Event = crate::Event<Test>;
 
@@ -50,6 +53,38 @@ fn inject_redeem_request(
     Redeem::insert_redeem_request(key, value)
 }
 
+/// Inserts vault BOB with `tokens` issued tokens, which can be redeemed, and
+/// `collateral` locked DOT.
+fn init_redeem_vault(tokens: Balance, collateral: Balance) {
+    <vault_registry::Module<Test>>::_insert_vault(
+        &BOB,
+        vault_registry::Vault {
+            id: BOB,
+            to_be_issued_tokens: 0,
+            issued_tokens: tokens,
+            to_be_redeemed_tokens: 0,
+            wallet: Wallet::new(BtcAddress::default()),
+            banned_until: None,
+        },
+    );
+    assert_ok!(<collateral::Module<Test>>::lock_collateral(
+        &BOB, collateral
+    ));
+}
+
+/// ALICE requests to redeem `amount` PolkaBTC with vault BOB at `height`,
+/// the request id is `H256([0; 32])`.
+fn request_redeem_ok(amount: Balance, height: BlockNumber) {
+    System::set_block_number(height);
+    ext::security::get_secure_id::<Test>.mock_safe(|_| MockResult::Return(H256([0; 32])));
+    assert_ok!(Redeem::request_redeem(
+        Origin::signed(ALICE),
+        amount,
+        BtcAddress::default(),
+        BOB
+    ));
+}
+
 #[test]
 fn test_ensure_parachain_running_or_error_liquidated_fails() {
     run_test(|| {
@@ -95,19 +130,14 @@ fn test_request_redeem_fails_with_amount_exceeds_user_balance() {
                 to_be_issued_tokens: 0,
                 issued_tokens: 10,
                 to_be_redeemed_tokens: 0,
-                btc_address: H160([0; 20]),
+                wallet: Wallet::new(BtcAddress::default()),
                 banned_until: None,
             }))
         });
         <treasury::Module<Test>>::mint(ALICE, 2);
         let amount = 10_000_000;
         assert_err!(
-            Redeem::request_redeem(
-                Origin::signed(ALICE),
-                amount,
-                H160::from_slice(&[0; 20]),
-                BOB
-            ),
+            Redeem::request_redeem(Origin::signed(ALICE), amount, BtcAddress::default(), BOB),
             Error::AmountExceedsUserBalance
         );
     })
@@ -117,7 +147,7 @@ fn test_request_redeem_fails_with_amount_exceeds_user_balance() {
 fn test_request_redeem_fails_with_vault_not_found() {
     run_test(|| {
         assert_err!(
-            Redeem::request_redeem(Origin::signed(ALICE), 0, H160::from_slice(&[0; 20]), BOB),
+            Redeem::request_redeem(Origin::signed(ALICE), 0, BtcAddress::default(), BOB),
             Error::VaultNotFound
         );
     })
@@ -132,7 +162,7 @@ fn test_request_redeem_fails_with_vault_banned() {
                 to_be_issued_tokens: 0,
                 issued_tokens: 0,
                 to_be_redeemed_tokens: 0,
-                btc_address: H160([0; 20]),
+                wallet: Wallet::new(BtcAddress::default()),
                 banned_until: Some(1),
             }))
         });
@@ -140,7 +170,7 @@ fn test_request_redeem_fails_with_vault_banned() {
             .mock_safe(|_, _| MockResult::Return(Err(Error::VaultBanned)));
 
         assert_err!(
-            Redeem::request_redeem(Origin::signed(ALICE), 0, H160::from_slice(&[0; 20]), BOB),
+            Redeem::request_redeem(Origin::signed(ALICE), 0, BtcAddress::default(), BOB),
             Error::VaultBanned
         );
     })
@@ -156,7 +186,7 @@ fn test_request_redeem_fails_with_amount_exceeds_vault_balance() {
                 to_be_issued_tokens: 0,
                 issued_tokens: 10,
                 to_be_redeemed_tokens: 0,
-                btc_address: H160([0; 20]),
+                wallet: Wallet::new(BtcAddress::default()),
                 banned_until: None,
             }))
         });
@@ -165,17 +195,38 @@ fn test_request_redeem_fails_with_amount_exceeds_vault_balance() {
         ext::vault_registry::ensure_not_banned::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
         let amount = 11;
         assert_err!(
-            Redeem::request_redeem(
-                Origin::signed(ALICE),
-                amount,
-                H160::from_slice(&[0; 20]),
-                BOB
-            ),
+            Redeem::request_redeem(Origin::signed(ALICE), amount, BtcAddress::default(), BOB),
             Error::AmountExceedsVaultBalance
         );
     })
 }
 
+#[test]
+fn test_request_redeem_with_too_many_open_requests_fails() {
+    run_test(|| {
+        <vault_registry::Module<Test>>::_insert_vault(
+            &BOB,
+            vault_registry::Vault {
+                id: BOB,
+                to_be_issued_tokens: 0,
+                issued_tokens: 10,
+                to_be_redeemed_tokens: 0,
+                wallet: Wallet::new(BtcAddress::default()),
+                banned_until: None,
+            },
+        );
+        let open_ids = (0..index::MAX_OPEN_REQUESTS as u64)
+            .map(H256::from_low_u64_be)
+            .collect::<Vec<_>>();
+        <RedeemRequestsByRedeemer<Test>>::insert(ALICE, open_ids);
+
+        assert_noop!(
+            Redeem::request_redeem(Origin::signed(ALICE), 9, BtcAddress::default(), BOB),
+            Error::MaxOpenRequestsReached
+        );
+    })
+}
+
 #[test]
 fn test_request_redeem_succeeds_in_running_state() {
     run_test(|| {
@@ -187,7 +238,7 @@ fn test_request_redeem_succeeds_in_running_state() {
                 to_be_issued_tokens: 0,
                 issued_tokens: 10,
                 to_be_redeemed_tokens: 0,
-                btc_address: H160([0; 20]),
+                wallet: Wallet::new(BtcAddress::default()),
                 banned_until: None,
             },
         );
@@ -216,7 +267,7 @@ fn test_request_redeem_succeeds_in_running_state() {
         assert_ok!(Redeem::request_redeem(
             Origin::signed(redeemer.clone()),
             amount,
-            H160([0; 20]),
+            BtcAddress::default(),
             BOB
         ));
 
@@ -225,7 +276,7 @@ fn test_request_redeem_succeeds_in_running_state() {
             redeemer.clone(),
             amount,
             BOB,
-            H160([0; 20]),
+            BtcAddress::default(),
         ));
         assert_ok!(
             Redeem::get_redeem_request_from_id(&H256([0; 32])),
@@ -233,24 +284,107 @@ fn test_request_redeem_succeeds_in_running_state() {
                 vault: BOB,
                 opentime: 1,
                 amount_polka_btc: amount,
+                fee_polka_btc: 0,
                 amount_btc: amount,
                 amount_dot: 0,
                 premium_dot: 0,
                 redeemer: redeemer.clone(),
-                btc_address: H160([0; 20]),
+                btc_address: BtcAddress::default(),
             }
         );
     })
 }
 
+#[test]
+fn test_request_redeem_pays_fee_into_fee_pool() {
+    run_test(|| {
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
+        <vault_registry::Module<Test>>::_insert_vault(
+            &BOB,
+            vault_registry::Vault {
+                id: BOB,
+                to_be_issued_tokens: 0,
+                issued_tokens: 100,
+                to_be_redeemed_tokens: 0,
+                wallet: Wallet::new(BtcAddress::default()),
+                banned_until: None,
+            },
+        );
+
+        ext::fee::get_redeem_fee::<Test>.mock_safe(|amount| MockResult::Return(Ok(amount / 20)));
+        ext::vault_registry::increase_to_be_redeemed_tokens::<Test>.mock_safe(|_, amount_btc| {
+            assert_eq!(amount_btc, 95);
+            MockResult::Return(Ok(()))
+        });
+        ext::treasury::lock::<Test>.mock_safe(|_, amount_polka_btc| {
+            assert_eq!(amount_polka_btc, 95);
+            MockResult::Return(Ok(()))
+        });
+        ext::security::get_secure_id::<Test>.mock_safe(move |_| MockResult::Return(H256([0; 32])));
+
+        assert_ok!(Redeem::request_redeem(
+            Origin::signed(ALICE),
+            100,
+            BtcAddress::default(),
+            BOB
+        ));
+
+        let fee_pool = ext::fee::fee_pool_account_id::<Test>();
+        assert_eq!(
+            <treasury::Module<Test>>::get_balance_from_account(fee_pool),
+            5
+        );
+        assert_eq!(<fee::Module<Test>>::epoch_rewards(), 5);
+
+        let redeem = Redeem::get_redeem_request_from_id(&H256([0; 32])).unwrap();
+        assert_eq!(redeem.amount_polka_btc, 95);
+        assert_eq!(redeem.fee_polka_btc, 5);
+        assert_eq!(redeem.amount_btc, 95);
+    })
+}
+
+#[test]
+fn test_request_redeem_failing_premium_check_does_not_pay_fee() {
+    run_test(|| {
+        <vault_registry::Module<Test>>::_insert_vault(
+            &BOB,
+            vault_registry::Vault {
+                id: BOB,
+                to_be_issued_tokens: 0,
+                issued_tokens: 100,
+                to_be_redeemed_tokens: 0,
+                wallet: Wallet::new(BtcAddress::default()),
+                banned_until: None,
+            },
+        );
+
+        ext::fee::get_redeem_fee::<Test>.mock_safe(|amount| MockResult::Return(Ok(amount / 20)));
+        ext::vault_registry::is_vault_below_premium_threshold::<Test>
+            .mock_safe(|_| MockResult::Return(Err(Error::RuntimeError)));
+
+        assert_noop!(
+            Redeem::request_redeem(Origin::signed(ALICE), 100, BtcAddress::default(), BOB),
+            Error::RuntimeError
+        );
+
+        let fee_pool = ext::fee::fee_pool_account_id::<Test>();
+        assert_eq!(
+            <treasury::Module<Test>>::get_balance_from_account(fee_pool),
+            0
+        );
+        assert_eq!(<fee::Module<Test>>::epoch_rewards(), 0);
+    })
+}
+
 #[test]
 fn test_partial_redeem_factor() {
     run_test(|| {
         ext::vault_registry::total_liquidation_value::<Test>
-            .mock_safe(|| MockResult::Return(Ok(1000)));
-        ext::treasury::get_total_supply::<Test>.mock_safe(|| MockResult::Return(10));
+            .mock_safe(|| MockResult::Return(Ok(250)));
+        ext::treasury::get_total_supply::<Test>.mock_safe(|| MockResult::Return(1000));
 
-        assert_ok!(Redeem::get_partial_redeem_factor(), 100);
+        // a quarter of the PolkaBTC was issued by the LiquidationVault
+        assert_ok!(Redeem::get_partial_redeem_factor(), 25_000);
     })
 }
 
@@ -278,7 +412,7 @@ fn test_request_redeem_succeeds_in_error_state() {
                 to_be_issued_tokens: 0,
                 issued_tokens: amount,
                 to_be_redeemed_tokens: 0,
-                btc_address: H160([0; 20]),
+                wallet: Wallet::new(BtcAddress::default()),
                 banned_until: None,
             },
         );
@@ -292,48 +426,49 @@ fn test_request_redeem_succeeds_in_error_state() {
         );
 
         ext::vault_registry::redeem_tokens_liquidation::<Test>.mock_safe(
-            move |vault_id, amount_polka_btc| {
-                assert_eq!(vault_id, &BOB);
+            move |redeemer_id, amount_polka_btc| {
+                assert_eq!(redeemer_id, &redeemer);
                 assert_eq!(amount_polka_btc, amount / 2);
 
                 MockResult::Return(Ok(()))
             },
         );
 
-        ext::treasury::lock::<Test>.mock_safe(move |account, amount_polka_btc| {
-            assert_eq!(account, redeemer);
-            assert_eq!(amount_polka_btc, amount);
-
-            MockResult::Return(Ok(()))
-        });
-
         ext::security::get_secure_id::<Test>.mock_safe(move |_| MockResult::Return(H256([0; 32])));
 
         assert_ok!(Redeem::request_redeem(
             Origin::signed(redeemer.clone()),
             amount,
-            H160([0; 20]),
+            BtcAddress::default(),
             BOB
         ));
 
+        // half of the PolkaBTC is burned for DOT, the other half is locked
+        // until the vault pays the BTC
+        assert_emitted!(Event::RedeemLiquidation(redeemer.clone(), amount / 2));
         assert_emitted!(Event::RequestRedeem(
             H256([0; 32]),
             redeemer.clone(),
-            amount,
+            amount / 2,
             BOB,
-            H160([0; 20]),
+            BtcAddress::default(),
         ));
+        assert_eq!(
+            <treasury::Module<Test>>::get_locked_balance_from_account(redeemer),
+            amount / 2
+        );
         assert_ok!(
             Redeem::get_redeem_request_from_id(&H256([0; 32])),
             RedeemRequest {
                 vault: BOB,
                 opentime: 1,
-                amount_polka_btc: amount,
+                amount_polka_btc: amount / 2,
+                fee_polka_btc: 0,
                 amount_btc: amount / 2,
                 amount_dot: amount / 2,
                 premium_dot: 0,
                 redeemer: redeemer.clone(),
-                btc_address: H160([0; 20]),
+                btc_address: BtcAddress::default(),
             }
         );
     })
@@ -365,11 +500,12 @@ fn test_execute_redeem_fails_with_unauthorized_vault() {
                 vault: BOB,
                 opentime: 0,
                 amount_polka_btc: 0,
+                fee_polka_btc: 0,
                 amount_btc: 0,
                 amount_dot: 0,
                 premium_dot: 0,
                 redeemer: ALICE,
-                btc_address: H160([0; 20]),
+                btc_address: BtcAddress::default(),
             }))
         });
 
@@ -391,17 +527,19 @@ fn test_execute_redeem_fails_with_unauthorized_vault() {
 fn test_execute_redeem_fails_with_commit_period_expired() {
     run_test(|| {
         <system::Module<Test>>::set_block_number(20);
+        <RedeemPeriod<Test>>::set(10);
 
         Redeem::get_redeem_request_from_id.mock_safe(|_| {
             MockResult::Return(Ok(RedeemRequest {
                 vault: BOB,
-                opentime: 30,
+                opentime: 9,
                 amount_polka_btc: 0,
+                fee_polka_btc: 0,
                 amount_btc: 0,
                 amount_dot: 0,
                 premium_dot: 0,
                 redeemer: ALICE,
-                btc_address: H160([0; 20]),
+                btc_address: BtcAddress::default(),
             }))
         });
 
@@ -424,6 +562,7 @@ fn test_execute_redeem_succeeds() {
     run_test(|| {
         ext::oracle::btc_to_dots::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
         System::set_block_number(40);
+        <RedeemPeriod<Test>>::set(20);
         <vault_registry::Module<Test>>::_insert_vault(
             &BOB,
             vault_registry::Vault {
@@ -431,7 +570,7 @@ fn test_execute_redeem_succeeds() {
                 to_be_issued_tokens: 0,
                 issued_tokens: 200,
                 to_be_redeemed_tokens: 200,
-                btc_address: H160([0; 20]),
+                wallet: Wallet::new(BtcAddress::default()),
                 banned_until: None,
             },
         );
@@ -446,11 +585,12 @@ fn test_execute_redeem_succeeds() {
                 vault: BOB,
                 opentime: 20,
                 amount_polka_btc: 100,
+                fee_polka_btc: 0,
                 amount_btc: 0,
                 amount_dot: 0,
                 premium_dot: 0,
                 redeemer: ALICE,
-                btc_address: H160([0; 20]),
+                btc_address: BtcAddress::default(),
             },
         );
 
@@ -476,7 +616,7 @@ fn test_execute_redeem_succeeds() {
             Vec::default(),
             Vec::default()
         ));
-        assert_emitted!(Event::ExecuteRedeem(H256([0; 32]), ALICE, BOB));
+        assert_emitted!(Event::ExecuteRedeem(H256([0; 32]), ALICE, BOB, 0));
         assert_err!(
             Redeem::get_redeem_request_from_id(&H256([0u8; 32])),
             Error::RedeemIdNotFound,
@@ -484,6 +624,148 @@ fn test_execute_redeem_succeeds() {
     })
 }
 
+#[test]
+fn test_execute_redeem_pays_premium_to_redeemer() {
+    run_test(|| {
+        System::set_block_number(40);
+        <RedeemPeriod<Test>>::set(20);
+        ext::btc_relay::verify_transaction_inclusion::<Test>
+            .mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        ext::btc_relay::validate_transaction::<Test>
+            .mock_safe(|_, _, _, _| MockResult::Return(Ok(())));
+        ext::treasury::burn::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::redeem_tokens::<Test>.mock_safe(|_, _| {
+            panic!("the premium must be paid with the redeemed tokens");
+        });
+        ext::vault_registry::redeem_tokens_premium::<Test>.mock_safe(
+            |vault, amount_polka_btc, premium, redeemer| {
+                assert_eq!(vault, &BOB);
+                assert_eq!(amount_polka_btc, 100);
+                assert_eq!(premium, 5);
+                assert_eq!(redeemer, &ALICE);
+                MockResult::Return(Ok(()))
+            },
+        );
+
+        inject_redeem_request(
+            H256([0u8; 32]),
+            RedeemRequest {
+                vault: BOB,
+                opentime: 20,
+                amount_polka_btc: 100,
+                fee_polka_btc: 0,
+                amount_btc: 100,
+                amount_dot: 0,
+                premium_dot: 5,
+                redeemer: ALICE,
+                btc_address: BtcAddress::default(),
+            },
+        );
+
+        assert_ok!(Redeem::execute_redeem(
+            Origin::signed(BOB),
+            H256([0u8; 32]),
+            H256Le::zero(),
+            0,
+            Vec::default(),
+            Vec::default()
+        ));
+        assert_emitted!(Event::ExecuteRedeem(H256([0; 32]), ALICE, BOB, 5));
+    })
+}
+
+#[test]
+fn test_execute_batched_redeem_succeeds() {
+    run_test(|| {
+        System::set_block_number(40);
+        <RedeemPeriod<Test>>::set(20);
+        ext::btc_relay::verify_transaction_inclusion::<Test>
+            .mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        ext::btc_relay::validate_batch_transaction::<Test>.mock_safe(
+            |_, output_index, amount, _, redeem_id, commitment_path| {
+                assert_eq!(output_index, 1);
+                assert_eq!(amount, 100);
+                assert_eq!(redeem_id, vec![1u8; 32]);
+                assert_eq!(commitment_path, vec![H256Le::zero()]);
+                MockResult::Return(Ok(()))
+            },
+        );
+        ext::treasury::burn::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::redeem_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+
+        inject_redeem_request(
+            H256([1u8; 32]),
+            RedeemRequest {
+                vault: BOB,
+                opentime: 20,
+                amount_polka_btc: 100,
+                fee_polka_btc: 0,
+                amount_btc: 100,
+                amount_dot: 0,
+                premium_dot: 0,
+                redeemer: ALICE,
+                btc_address: BtcAddress::default(),
+            },
+        );
+
+        assert_ok!(Redeem::execute_batched_redeem(
+            Origin::signed(BOB),
+            H256([1u8; 32]),
+            H256Le::zero(),
+            0,
+            Vec::default(),
+            Vec::default(),
+            1,
+            vec![H256Le::zero()]
+        ));
+        assert_emitted!(Event::ExecuteRedeem(H256([1; 32]), ALICE, BOB, 0));
+        assert_err!(
+            Redeem::get_redeem_request_from_id(&H256([1u8; 32])),
+            Error::RedeemIdNotFound,
+        );
+    })
+}
+
+#[test]
+fn test_verify_and_validate_transaction_returns_validation_error() {
+    run_test(|| {
+        System::set_block_number(40);
+        <RedeemPeriod<Test>>::set(20);
+        ext::btc_relay::verify_transaction_inclusion::<Test>
+            .mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        ext::btc_relay::validate_transaction::<Test>
+            .mock_safe(|_, _, _, _| MockResult::Return(Err(Error::InvalidOpreturn)));
+
+        inject_redeem_request(
+            H256([0u8; 32]),
+            RedeemRequest {
+                vault: BOB,
+                opentime: 20,
+                amount_polka_btc: 100,
+                fee_polka_btc: 0,
+                amount_btc: 0,
+                amount_dot: 0,
+                premium_dot: 0,
+                redeemer: ALICE,
+                btc_address: BtcAddress::default(),
+            },
+        );
+
+        assert_err!(
+            Redeem::_verify_and_validate_transaction(
+                H256([0u8; 32]),
+                H256Le::zero(),
+                0,
+                Vec::default(),
+                Vec::default()
+            ),
+            Error::InvalidOpreturn
+        );
+        // the redeem request is left untouched
+        assert!(Redeem::get_redeem_request_from_id(&H256([0u8; 32])).is_ok());
+    })
+}
+
 #[test]
 fn test_cancel_redeem_fails_with_redeem_id_not_found() {
     run_test(|| {
@@ -498,17 +780,20 @@ fn test_cancel_redeem_fails_with_redeem_id_not_found() {
 fn test_cancel_redeem_fails_with_time_not_expired() {
     run_test(|| {
         System::set_block_number(20);
+        // the request can still be executed in the last block of the period
+        <RedeemPeriod<Test>>::set(20);
 
         Redeem::get_redeem_request_from_id.mock_safe(|_| {
             MockResult::Return(Ok(RedeemRequest {
                 vault: BOB,
                 opentime: 0,
                 amount_polka_btc: 0,
+                fee_polka_btc: 0,
                 amount_btc: 0,
                 amount_dot: 0,
                 premium_dot: 0,
                 redeemer: ALICE,
-                btc_address: H160([0; 20]),
+                btc_address: BtcAddress::default(),
             }))
         });
 
@@ -529,11 +814,12 @@ fn test_cancel_redeem_fails_with_unauthorized_caller() {
                 vault: BOB,
                 opentime: 0,
                 amount_polka_btc: 0,
+                fee_polka_btc: 0,
                 amount_btc: 0,
                 amount_dot: 0,
                 premium_dot: 0,
                 redeemer: ALICE,
-                btc_address: H160([0; 20]),
+                btc_address: BtcAddress::default(),
             }))
         });
 
@@ -547,23 +833,40 @@ fn test_cancel_redeem_fails_with_unauthorized_caller() {
 #[test]
 fn test_cancel_redeem_succeeds() {
     run_test(|| {
+        System::set_block_number(11);
+        init_redeem_vault(0, 0);
         inject_redeem_request(
             H256([0u8; 32]),
             RedeemRequest {
                 vault: BOB,
                 opentime: 10,
                 amount_polka_btc: 0,
+                fee_polka_btc: 0,
                 amount_btc: 0,
                 amount_dot: 0,
                 premium_dot: 0,
                 redeemer: ALICE,
-                btc_address: H160([0; 20]),
+                btc_address: BtcAddress::default(),
             },
         );
 
+        assert_eq!(
+            Redeem::get_redeem_requests_for_redeemer(ALICE),
+            vec![H256([0u8; 32])]
+        );
+        assert_eq!(
+            Redeem::get_redeem_requests_for_vault(BOB),
+            vec![H256([0u8; 32])]
+        );
+
         ext::vault_registry::ban_vault::<Test>.mock_safe(|vault, height| {
             assert_eq!(vault, BOB);
-            assert_eq!(height, 1);
+            assert_eq!(height, 11);
+            MockResult::Return(Ok(()))
+        });
+        ext::vault_registry::record_failed_redeem::<Test>.mock_safe(|vault, height| {
+            assert_eq!(vault, &BOB);
+            assert_eq!(height, 11);
             MockResult::Return(Ok(()))
         });
 
@@ -577,5 +880,377 @@ fn test_cancel_redeem_succeeds() {
             Error::RedeemIdNotFound,
         );
         assert_emitted!(Event::CancelRedeem(H256([0; 32]), ALICE));
+        assert!(Redeem::get_redeem_requests_for_redeemer(ALICE).is_empty());
+        assert!(Redeem::get_redeem_requests_for_vault(BOB).is_empty());
+    })
+}
+
+#[test]
+fn test_cancel_redeem_with_reimbursement_slashes_vault() {
+    run_test(|| {
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
+        // 10% punishment fee
+        ext::vault_registry::punishment_fee::<Test>.mock_safe(|| MockResult::Return(Ok(10_000)));
+        ext::vault_registry::ban_vault::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        init_redeem_vault(100, 1000);
+        request_redeem_ok(100, 10);
+        System::set_block_number(11);
+
+        assert_ok!(Redeem::cancel_redeem(
+            Origin::signed(ALICE),
+            H256([0u8; 32]),
+            true
+        ));
+
+        // the PolkaBTC is burned and the redeemer receives its value plus the fee
+        assert_eq!(
+            <treasury::Module<Test>>::get_locked_balance_from_account(ALICE),
+            0
+        );
+        assert_eq!(
+            <treasury::Module<Test>>::get_balance_from_account(ALICE),
+            ALICE_BALANCE - 100 + 110
+        );
+        assert_eq!(
+            <collateral::Module<Test>>::get_collateral_from_account(&BOB),
+            1000 - 110
+        );
+        let vault = <vault_registry::Module<Test>>::_get_vault_from_id(&BOB).unwrap();
+        assert_eq!(vault.issued_tokens, 0);
+        assert_eq!(vault.to_be_redeemed_tokens, 0);
+    })
+}
+
+#[test]
+fn test_cancel_redeem_fails_with_insufficient_tokens_committed() {
+    run_test(|| {
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
+        init_redeem_vault(100, 1000);
+        request_redeem_ok(100, 10);
+        System::set_block_number(11);
+        // the vault has fewer to-be-redeemed tokens than the request
+        <vault_registry::Module<Test>>::_insert_vault(
+            &BOB,
+            vault_registry::Vault {
+                id: BOB,
+                to_be_issued_tokens: 0,
+                issued_tokens: 100,
+                to_be_redeemed_tokens: 50,
+                wallet: Wallet::new(BtcAddress::default()),
+                banned_until: None,
+            },
+        );
+
+        assert_noop!(
+            Redeem::cancel_redeem(Origin::signed(ALICE), H256([0u8; 32]), false),
+            Error::InsufficientTokensCommitted
+        );
+    })
+}
+
+#[test]
+fn test_begin_block_cancels_expired_redeem_requests() {
+    run_test(|| {
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
+        <RedeemPeriod<Test>>::set(10);
+        init_redeem_vault(0, 0);
+        inject_redeem_request(
+            H256([0u8; 32]),
+            RedeemRequest {
+                vault: BOB,
+                opentime: 1,
+                amount_polka_btc: 0,
+                fee_polka_btc: 0,
+                amount_btc: 0,
+                amount_dot: 0,
+                premium_dot: 0,
+                redeemer: ALICE,
+                btc_address: BtcAddress::default(),
+            },
+        );
+
+        ext::vault_registry::ban_vault::<Test>.mock_safe(|vault, height| {
+            assert_eq!(vault, BOB);
+            assert_eq!(height, 12);
+            MockResult::Return(Ok(()))
+        });
+
+        // the request can still be executed at the end of its period
+        Redeem::begin_block(11);
+        assert_ok!(Redeem::get_redeem_request_from_id(&H256([0u8; 32])));
+
+        Redeem::begin_block(12);
+        assert_err!(
+            Redeem::get_redeem_request_from_id(&H256([0u8; 32])),
+            Error::RedeemIdNotFound,
+        );
+        assert_emitted!(Event::ExpireRedeem(H256([0; 32]), BOB));
+    })
+}
+
+#[test]
+fn test_begin_block_cancels_expired_redeem_requests_in_batches() {
+    run_test(|| {
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
+        <RedeemPeriod<Test>>::set(10);
+        init_redeem_vault(0, 0);
+        for i in 1..=3 {
+            inject_redeem_request(
+                H256([i; 32]),
+                RedeemRequest {
+                    vault: BOB,
+                    opentime: 1,
+                    amount_polka_btc: 0,
+                    fee_polka_btc: 0,
+                    amount_btc: 0,
+                    amount_dot: 0,
+                    premium_dot: 0,
+                    redeemer: ALICE,
+                    btc_address: BtcAddress::default(),
+                },
+            );
+        }
+        ext::vault_registry::ban_vault::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+
+        // at most two expired requests are cancelled per block
+        assert_eq!(Redeem::begin_block(12), 2);
+        assert!(Redeem::get_redeem_request_from_id(&H256([1; 32])).is_err());
+        assert!(Redeem::get_redeem_request_from_id(&H256([2; 32])).is_err());
+        assert_ok!(Redeem::get_redeem_request_from_id(&H256([3; 32])));
+
+        assert_eq!(Redeem::begin_block(13), 1);
+        assert!(Redeem::get_redeem_request_from_id(&H256([3; 32])).is_err());
+    })
+}
+
+#[test]
+fn test_begin_block_unlocks_polka_btc_of_expired_redeem_request() {
+    run_test(|| {
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
+        // 10% punishment fee
+        ext::vault_registry::punishment_fee::<Test>.mock_safe(|| MockResult::Return(Ok(10_000)));
+        ext::vault_registry::ban_vault::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        <RedeemPeriod<Test>>::set(10);
+        init_redeem_vault(100, 1000);
+        request_redeem_ok(100, 1);
+
+        assert_eq!(Redeem::begin_block(12), 1);
+        assert_emitted!(Event::ExpireRedeem(H256([0; 32]), BOB));
+
+        // the redeemer keeps the PolkaBTC and receives the punishment fee
+        assert_eq!(
+            <treasury::Module<Test>>::get_locked_balance_from_account(ALICE),
+            0
+        );
+        assert_eq!(
+            <treasury::Module<Test>>::get_balance_from_account(ALICE),
+            ALICE_BALANCE + 10
+        );
+        assert_eq!(
+            <collateral::Module<Test>>::get_collateral_from_account(&BOB),
+            1000 - 10
+        );
+        let vault = <vault_registry::Module<Test>>::_get_vault_from_id(&BOB).unwrap();
+        assert_eq!(vault.issued_tokens, 100);
+        assert_eq!(vault.to_be_redeemed_tokens, 0);
+    })
+}
+
+#[test]
+fn test_begin_block_keeps_redeem_request_failing_to_expire() {
+    run_test(|| {
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
+        <RedeemPeriod<Test>>::set(10);
+        init_redeem_vault(100, 1000);
+        request_redeem_ok(100, 1);
+
+        ext::vault_registry::punishment_fee::<Test>
+            .mock_safe(|| MockResult::Return(Err(Error::RuntimeError)));
+
+        assert_eq!(Redeem::begin_block(12), 1);
+        assert_ok!(Redeem::get_redeem_request_from_id(&H256([0u8; 32])));
+        assert_emitted!(Event::ExpireRedeemFailed(H256([0; 32])));
+    })
+}
+
+#[test]
+fn test_begin_block_does_not_expire_redeem_requests_unless_running() {
+    run_test(|| {
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
+        ext::vault_registry::ban_vault::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        <RedeemPeriod<Test>>::set(10);
+        init_redeem_vault(100, 1000);
+        request_redeem_ok(100, 1);
+
+        ext::security::ensure_parachain_status_running::<Test>
+            .mock_safe(|| MockResult::Return(Err(Error::ParachainNotRunning)));
+        assert_eq!(Redeem::begin_block(12), 0);
+        assert_ok!(Redeem::get_redeem_request_from_id(&H256([0u8; 32])));
+
+        ext::security::ensure_parachain_status_running::<Test>
+            .mock_safe(|| MockResult::Return(Ok(())));
+        assert_eq!(Redeem::begin_block(13), 1);
+        assert_emitted!(Event::ExpireRedeem(H256([0; 32]), BOB));
+    })
+}
+
+#[test]
+fn test_on_initialize_weighs_expired_redeem_requests() {
+    run_test(|| {
+        <RedeemPeriod<Test>>::set(10);
+        init_redeem_vault(0, 0);
+        for i in 1..=3 {
+            inject_redeem_request(
+                H256([i; 32]),
+                RedeemRequest {
+                    vault: BOB,
+                    opentime: 1,
+                    amount_polka_btc: 0,
+                    fee_polka_btc: 0,
+                    amount_btc: 0,
+                    amount_dot: 0,
+                    premium_dot: 0,
+                    redeemer: ALICE,
+                    btc_address: BtcAddress::default(),
+                },
+            );
+        }
+        ext::vault_registry::ban_vault::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+
+        assert_eq!(Redeem::on_initialize(11), 0);
+        assert_eq!(Redeem::on_initialize(12), 2 * EXPIRE_REDEEM_WEIGHT);
+        assert_eq!(Redeem::on_initialize(13), EXPIRE_REDEEM_WEIGHT);
+    })
+}
+
+#[test]
+fn test_on_initialize_keeps_redeem_request_executable_until_period_ends() {
+    run_test(|| {
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
+        ext::btc_relay::verify_transaction_inclusion::<Test>
+            .mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        ext::btc_relay::validate_transaction::<Test>
+            .mock_safe(|_, _, _, _| MockResult::Return(Ok(())));
+        <RedeemPeriod<Test>>::set(10);
+        init_redeem_vault(100, 1000);
+        request_redeem_ok(100, 1);
+
+        // the last block of the period starts before the vault executes
+        System::set_block_number(11);
+        Redeem::on_initialize(11);
+        assert_ok!(Redeem::execute_redeem(
+            Origin::signed(BOB),
+            H256([0u8; 32]),
+            H256Le::zero(),
+            0,
+            Vec::default(),
+            Vec::default()
+        ));
+        assert_emitted!(Event::ExecuteRedeem(H256([0; 32]), ALICE, BOB, 0));
+    })
+}
+
+#[test]
+fn test_liquidation_reassigns_open_redeem_requests_to_liquidation_vault() {
+    run_test(|| {
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
+        ext::vault_registry::ban_vault::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        <vault_registry::Module<Test>>::_insert_vault(
+            &CAROL,
+            vault_registry::Vault {
+                id: CAROL,
+                to_be_issued_tokens: 0,
+                issued_tokens: 0,
+                to_be_redeemed_tokens: 0,
+                wallet: Wallet::new(BtcAddress::default()),
+                banned_until: None,
+            },
+        );
+        <vault_registry::Module<Test>>::_set_liquidation_vault(&CAROL);
+        <vault_registry::Module<Test>>::_set_failed_redeem_threshold(1);
+        init_redeem_vault(200, 1000);
+
+        request_redeem_ok(100, 1);
+        let second_id = H256([1; 32]);
+        ext::security::get_secure_id::<Test>.mock_safe(move |_| MockResult::Return(second_id));
+        assert_ok!(Redeem::request_redeem(
+            Origin::signed(ALICE),
+            100,
+            BtcAddress::default(),
+            BOB
+        ));
+
+        // the failed redeem liquidates the vault
+        System::set_block_number(2);
+        assert_ok!(Redeem::cancel_redeem(
+            Origin::signed(ALICE),
+            H256([0; 32]),
+            false
+        ));
+        assert_err!(
+            <vault_registry::Module<Test>>::_get_vault_from_id(&BOB),
+            Error::VaultNotFound
+        );
+
+        // the open request of the vault is taken over by the LiquidationVault
+        assert_eq!(
+            Redeem::get_redeem_request_from_id(&second_id)
+                .unwrap()
+                .vault,
+            CAROL
+        );
+        assert!(Redeem::get_redeem_requests_for_vault(BOB).is_empty());
+        assert_eq!(
+            Redeem::get_redeem_requests_for_vault(CAROL),
+            vec![second_id]
+        );
+
+        assert_ok!(Redeem::cancel_redeem(
+            Origin::signed(ALICE),
+            second_id,
+            false
+        ));
+        assert_eq!(
+            <treasury::Module<Test>>::get_locked_balance_from_account(ALICE),
+            0
+        );
+        let liquidation_vault = <vault_registry::Module<Test>>::_get_vault_from_id(&CAROL).unwrap();
+        assert_eq!(liquidation_vault.to_be_redeemed_tokens, 0);
+        assert!(Redeem::get_redeem_requests_for_vault(CAROL).is_empty());
+    })
+}
+
+#[test]
+fn test_redeem_liquidation_succeeds() {
+    run_test(|| {
+        let balance = <treasury::Module<Test>>::get_balance_from_account(ALICE);
+        ext::vault_registry::redeem_tokens_liquidation::<Test>.mock_safe(|redeemer, amount| {
+            assert_eq!(redeemer, &ALICE);
+            assert_eq!(amount, 4);
+            MockResult::Return(Ok(()))
+        });
+
+        assert_ok!(Redeem::redeem_liquidation(Origin::signed(ALICE), 4));
+
+        assert_eq!(
+            <treasury::Module<Test>>::get_balance_from_account(ALICE),
+            balance - 4
+        );
+        assert_eq!(
+            <treasury::Module<Test>>::get_locked_balance_from_account(ALICE),
+            0
+        );
+        assert_emitted!(Event::RedeemLiquidation(ALICE, 4));
+    })
+}
+
+#[test]
+fn test_redeem_liquidation_fails_with_amount_exceeds_user_balance() {
+    run_test(|| {
+        let balance = <treasury::Module<Test>>::get_balance_from_account(ALICE);
+        assert_noop!(
+            Redeem::redeem_liquidation(Origin::signed(ALICE), balance + 1),
+            Error::AmountExceedsUserBalance
+        );
     })
 }
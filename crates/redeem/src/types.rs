@@ -1,6 +1,6 @@
+use bitcoin::address::BtcAddress;
 use codec::{Decode, Encode};
 use frame_support::traits::Currency;
-use sp_core::H160;
 
 pub(crate) type DOT<T> =
     <<T as collateral::Trait>::DOT as Currency<<T as system::Trait>::AccountId>>::Balance;
@@ -14,9 +14,10 @@ pub struct Redeem<AccountId, BlockNumber, PolkaBTC, DOT> {
     pub vault: AccountId,
     pub opentime: BlockNumber,
     pub amount_polka_btc: PolkaBTC,
+    pub fee_polka_btc: PolkaBTC,
     pub amount_btc: PolkaBTC,
     pub amount_dot: DOT,
     pub premium_dot: DOT,
     pub redeemer: AccountId,
-    pub btc_address: H160,
+    pub btc_address: BtcAddress,
 }
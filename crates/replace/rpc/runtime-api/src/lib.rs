@@ -0,0 +1,31 @@
+//! Runtime API definition for the Replace module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use bitcoin::types::H256Le;
+use codec::Codec;
+use sp_core::H256;
+use sp_std::prelude::*;
+use x_core::Error;
+
+sp_api::decl_runtime_apis! {
+    pub trait ReplaceApi<AccountId> where
+        AccountId: Codec,
+    {
+        /// Checks the Bitcoin transaction of a replace request against the current
+        /// state without executing the request
+        fn verify_and_validate_transaction(
+            replace_id: H256,
+            tx_id: H256Le,
+            tx_block_height: u32,
+            merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+        ) -> Result<(), Error>;
+
+        /// Returns the ids of the open replace requests of the old vault
+        fn get_old_vault_replace_requests(vault_id: AccountId) -> Vec<H256>;
+
+        /// Returns the ids of the open replace requests accepted by the new vault
+        fn get_new_vault_replace_requests(vault_id: AccountId) -> Vec<H256>;
+    }
+}
@@ -0,0 +1,143 @@
+//! RPC interface for the Replace module.
+
+use bitcoin::types::H256Le;
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+pub use replace_rpc_runtime_api::ReplaceApi as ReplaceRuntimeApi;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{Bytes, H256};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::sync::Arc;
+
+#[rpc]
+pub trait ReplaceApi<BlockHash, AccountId> {
+    /// Dry-runs the transaction checks of `execute_replace` and fails with
+    /// the error the extrinsic would fail with.
+    #[rpc(name = "replace_verifyAndValidateTransaction")]
+    fn verify_and_validate_transaction(
+        &self,
+        replace_id: H256,
+        tx_id: H256Le,
+        tx_block_height: u32,
+        merkle_proof: Bytes,
+        raw_tx: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<()>;
+
+    /// Returns the ids of the open replace requests of the old vault
+    #[rpc(name = "replace_getOldVaultReplaceRequests")]
+    fn get_old_vault_replace_requests(
+        &self,
+        vault_id: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<H256>>;
+
+    /// Returns the ids of the open replace requests accepted by the new vault
+    #[rpc(name = "replace_getNewVaultReplaceRequests")]
+    fn get_new_vault_replace_requests(
+        &self,
+        vault_id: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<H256>>;
+}
+
+/// A struct that implements the [`ReplaceApi`].
+pub struct Replace<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Replace<C, B> {
+    /// Create new `Replace` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Replace {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+/// Error code returned when the runtime API call fails.
+const RUNTIME_ERROR: i64 = 1;
+/// Error code returned when the checked transaction is rejected.
+const TRANSACTION_ERROR: i64 = 2;
+
+fn runtime_error<E: std::fmt::Debug>(message: &str, error: E) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(RUNTIME_ERROR),
+        message: message.into(),
+        data: Some(format!("{:?}", error).into()),
+    }
+}
+
+impl<C, Block> Replace<C, Block>
+where
+    Block: BlockT,
+    C: HeaderBackend<Block>,
+{
+    /// Returns the given block or, if not specified, the best block
+    fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId<Block> {
+        // if the block hash is not supplied assume the best block
+        BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash))
+    }
+}
+
+impl<C, Block, AccountId> ReplaceApi<<Block as BlockT>::Hash, AccountId> for Replace<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: ReplaceRuntimeApi<Block, AccountId>,
+    AccountId: Codec,
+{
+    fn verify_and_validate_transaction(
+        &self,
+        replace_id: H256,
+        tx_id: H256Le,
+        tx_block_height: u32,
+        merkle_proof: Bytes,
+        raw_tx: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<()> {
+        let api = self.client.runtime_api();
+        let at = self.block_id(at);
+
+        api.verify_and_validate_transaction(
+            &at,
+            replace_id,
+            tx_id,
+            tx_block_height,
+            merkle_proof.to_vec(),
+            raw_tx.to_vec(),
+        )
+        .map_err(|e| runtime_error("Unable to check the transaction.", e))?
+        .map_err(|e| RpcError {
+            code: ErrorCode::ServerError(TRANSACTION_ERROR),
+            message: e.message().into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn get_old_vault_replace_requests(
+        &self,
+        vault_id: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<H256>> {
+        self.client
+            .runtime_api()
+            .get_old_vault_replace_requests(&self.block_id(at), vault_id)
+            .map_err(|e| runtime_error("Unable to query the replace requests.", e))
+    }
+
+    fn get_new_vault_replace_requests(
+        &self,
+        vault_id: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<H256>> {
+        self.client
+            .runtime_api()
+            .get_new_vault_replace_requests(&self.block_id(at), vault_id)
+            .map_err(|e| runtime_error("Unable to query the replace requests.", e))
+    }
+}
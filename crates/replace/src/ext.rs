@@ -5,6 +5,7 @@ use mocktopus::macros::mockable;
 pub(crate) mod btc_relay {
     use sp_std::vec::Vec;
 
+    use bitcoin::address::BtcAddress;
     use bitcoin::types::H256Le;
     use x_core::UnitResult;
 
@@ -25,10 +26,11 @@ pub(crate) mod btc_relay {
     pub fn validate_transaction<T: btc_relay::Trait>(
         raw_tx: Vec<u8>,
         amount: i64,
-        btc_address: Vec<u8>,
+        btc_address: BtcAddress,
         issue_id: Vec<u8>,
     ) -> UnitResult {
-        <btc_relay::Module<T>>::_validate_transaction(raw_tx, amount, btc_address, issue_id)
+        <btc_relay::Module<T>>::_validate_transaction(raw_tx, amount, btc_address, issue_id)?;
+        Ok(())
     }
 }
 
@@ -130,6 +132,16 @@ pub(crate) mod collateral {
     }
 }
 
+#[cfg_attr(test, mockable)]
+pub(crate) mod oracle {
+    use crate::types::{PolkaBTC, DOT};
+    use x_core::Result;
+
+    pub fn btc_to_dots<T: exchange_rate_oracle::Trait>(amount: PolkaBTC<T>) -> Result<DOT<T>> {
+        <exchange_rate_oracle::Module<T>>::btc_to_dots(amount)
+    }
+}
+
 #[cfg_attr(test, mockable)]
 pub(crate) mod security {
     use primitive_types::H256;
@@ -16,7 +16,7 @@ use sp_std::vec::Vec;
 use system::ensure_signed;
 
 use bitcoin::types::H256Le;
-use x_core::{Error, UnitResult};
+use x_core::{index, Error, UnitResult};
 
 use crate::types::{PolkaBTC, Replace, DOT};
 
@@ -35,6 +35,9 @@ mod tests;
 /// The replace module id, used for deriving its sovereign account ID.
 const _MODULE_ID: ModuleId = ModuleId(*b"replacem");
 
+/// Granularity of `ReplaceGriefingCollateralRate`
+pub const GRANULARITY: u32 = 5;
+
 /// The pallet's configuration trait.
 pub trait Trait:
     system::Trait + vault_registry::Trait + collateral::Trait + btc_relay::Trait + treasury::Trait
@@ -46,9 +49,18 @@ pub trait Trait:
 // The pallet's storage items.
 decl_storage! {
     trait Store for Module<T: Trait> as Replace {
+        /// The minimum collateral (DOT) a vault needs to provide as griefing protection.
         ReplaceGriefingCollateral: DOT<T>;
+        /// Share of the amount to be replaced, converted to DOT, a vault needs to provide
+        /// as griefing protection, but at least `ReplaceGriefingCollateral`.
+        /// For example, if the `ReplaceGriefingCollateralRate` is set to 5000, it is equivalent to 5%.
+        ReplaceGriefingCollateralRate get(fn replace_griefing_collateral_rate) config(): u128;
         ReplacePeriod: T::BlockNumber;
         ReplaceRequests: map hasher(blake2_128_concat) H256 => Option<Replace<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>>;
+        /// Ids of the open replace requests of each old vault
+        ReplaceRequestsByOldVault: map hasher(blake2_128_concat) T::AccountId => Vec<H256>;
+        /// Ids of the open replace requests accepted by each new vault
+        ReplaceRequestsByNewVault: map hasher(blake2_128_concat) T::AccountId => Vec<H256>;
     }
 }
 
@@ -206,9 +218,9 @@ impl<T: Trait> Module<T> {
                 ext::vault_registry::is_over_minimum_collateral::<T>(vault_collateral);
             ensure!(over_threshold, Error::InsufficientCollateral);
         }
-        // step 6: Check that the griefingCollateral is greater or equal ReplaceGriefingCollateral
+        // step 6: Check that the griefingCollateral is greater or equal the required griefing collateral
         ensure!(
-            griefing_collateral >= <ReplaceGriefingCollateral<T>>::get(),
+            griefing_collateral >= Self::get_required_griefing_collateral(amount)?,
             Error::InsufficientCollateral
         );
         // step 7: Lock the oldVault’s griefing collateral
@@ -226,7 +238,7 @@ impl<T: Trait> Module<T> {
             new_vault: None,
             collateral: vault_collateral,
             accept_time: None,
-            btc_address: vault.btc_address,
+            btc_address: vault.wallet.get_btc_address(),
         };
         Self::insert_replace_request(replace_id, replace);
         // step 11: Emit RequestReplace event
@@ -291,7 +303,12 @@ impl<T: Trait> Module<T> {
         // step 5: Lock the newVault’s collateral by calling lockCollateral
         ext::collateral::lock_collateral::<T>(new_vault_id.clone(), collateral)?;
         // step 6: Update the ReplaceRequest entry
-        replace.add_new_vault(new_vault_id.clone(), height, collateral, vault.btc_address);
+        replace.add_new_vault(
+            new_vault_id.clone(),
+            height,
+            collateral,
+            vault.wallet.get_btc_address(),
+        );
         Self::insert_replace_request(replace_id, replace);
         // step 7: Emit a AcceptReplace(newVault, replaceId, collateral) event
         Self::deposit_event(<Event<T>>::AcceptReplace(
@@ -342,7 +359,7 @@ impl<T: Trait> Module<T> {
                 accept_time: Some(current_height),
                 amount: btc_amount,
                 griefing_collateral: 0.into(),
-                btc_address: new_vault.btc_address,
+                btc_address: new_vault.wallet.get_btc_address(),
                 collateral: collateral,
             },
         );
@@ -371,25 +388,17 @@ impl<T: Trait> Module<T> {
         // step 1: Retrieve the ReplaceRequest as per the replaceId parameter from Vaults in the VaultRegistry
         let replace = Self::get_replace_request(&replace_id)?;
         // step 2: Check that the current Parachain block height minus the ReplacePeriod is smaller than the opentime of the ReplaceRequest
-        let replace_period = Self::replace_period();
-        let current_height = Self::current_height();
-        ensure!(
-            current_height <= replace.open_time + replace_period,
-            Error::ReplacePeriodExpired
-        );
+        Self::ensure_replace_period_not_expired(&replace)?;
         // step 3: Retrieve the Vault as per the newVault parameter from Vaults in the VaultRegistry
         let _new_vault = ext::vault_registry::get_vault_from_id::<T>(&new_vault_id)?;
-        // step 4: Call verifyTransactionInclusion in BTC-Relay, providing txid, txBlockHeight, txIndex, and merkleProof as parameters
-        ext::btc_relay::verify_transaction_inclusion::<T>(tx_id, tx_block_height, merkle_proof)?;
-        // step 5: Call validateTransaction in BTC-Relay
-        let amount =
-            TryInto::<u64>::try_into(replace.amount).map_err(|_e| Error::RuntimeError)? as i64;
-
-        ext::btc_relay::validate_transaction::<T>(
+        // step 4 and 5: Verify the transaction inclusion and validate the transaction
+        Self::verify_and_validate_transaction(
+            replace_id,
+            &replace,
+            tx_id,
+            tx_block_height,
+            merkle_proof,
             raw_tx,
-            amount,
-            replace.btc_address.as_bytes().to_vec(),
-            replace_id.clone().as_bytes().to_vec(),
         )?;
         // step 6: Call the replaceTokens
         ext::vault_registry::replace_tokens::<T>(
@@ -414,6 +423,76 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Checks the Bitcoin transaction of a replace request against the current
+    /// state without executing the request. Returns the error `execute_replace`
+    /// would fail with, except for the checks of the new vault.
+    ///
+    /// # Arguments
+    ///
+    /// * `replace_id` - the ID of the replacement request
+    /// * `tx_id` - the backing chain transaction id
+    /// * `tx_block_height` - the blocked height of the backing transaction
+    /// * 'merkle_proof' - the merkle root of the block
+    /// * `raw_tx` - the transaction id in bytes
+    pub fn _verify_and_validate_transaction(
+        replace_id: H256,
+        tx_id: H256Le,
+        tx_block_height: u32,
+        merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> Result<(), Error> {
+        // Check that Parachain status is RUNNING
+        ext::security::ensure_parachain_status_running::<T>()?;
+        let replace = Self::get_replace_request(&replace_id)?;
+        Self::ensure_replace_period_not_expired(&replace)?;
+        Self::verify_and_validate_transaction(
+            replace_id,
+            &replace,
+            tx_id,
+            tx_block_height,
+            merkle_proof,
+            raw_tx,
+        )
+    }
+
+    /// Checks that the current Parachain block height minus the ReplacePeriod
+    /// is smaller than the opentime of the replace request.
+    fn ensure_replace_period_not_expired(
+        replace: &Replace<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>,
+    ) -> Result<(), Error> {
+        let replace_period = Self::replace_period();
+        let current_height = Self::current_height();
+        ensure!(
+            current_height <= replace.open_time + replace_period,
+            Error::ReplacePeriodExpired
+        );
+        Ok(())
+    }
+
+    /// Checks that the Bitcoin transaction is included in the main chain and
+    /// pays the old vault.
+    fn verify_and_validate_transaction(
+        replace_id: H256,
+        replace: &Replace<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>,
+        tx_id: H256Le,
+        tx_block_height: u32,
+        merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> Result<(), Error> {
+        // Call verifyTransactionInclusion in BTC-Relay, providing txid, txBlockHeight, txIndex, and merkleProof as parameters
+        ext::btc_relay::verify_transaction_inclusion::<T>(tx_id, tx_block_height, merkle_proof)?;
+        // Call validateTransaction in BTC-Relay
+        let amount =
+            TryInto::<u64>::try_into(replace.amount).map_err(|_e| Error::RuntimeError)? as i64;
+
+        ext::btc_relay::validate_transaction::<T>(
+            raw_tx,
+            amount,
+            replace.btc_address,
+            replace_id.clone().as_bytes().to_vec(),
+        )
+    }
+
     fn _cancel_replace(new_vault_id: T::AccountId, replace_id: H256) -> Result<(), Error> {
         // Check that Parachain status is RUNNING
         ext::security::ensure_parachain_status_running::<T>()?;
@@ -459,6 +538,11 @@ impl<T: Trait> Module<T> {
         key: H256,
         value: Replace<T::AccountId, T::BlockNumber, PolkaBTC<T>, DOT<T>>,
     ) {
+        // requests are inserted again when they are accepted by a new vault
+        index::insert::<ReplaceRequestsByOldVault<T>, _, _>(&value.old_vault, key);
+        if let Some(new_vault) = &value.new_vault {
+            index::insert::<ReplaceRequestsByNewVault<T>, _, _>(new_vault, key);
+        }
         <ReplaceRequests<T>>::insert(key, value)
     }
 
@@ -467,7 +551,52 @@ impl<T: Trait> Module<T> {
     }
 
     fn remove_replace_request(key: H256) {
-        <ReplaceRequests<T>>::remove(key)
+        if let Some(replace) = <ReplaceRequests<T>>::take(key) {
+            index::remove::<ReplaceRequestsByOldVault<T>, _, _>(&replace.old_vault, key);
+            if let Some(new_vault) = &replace.new_vault {
+                index::remove::<ReplaceRequestsByNewVault<T>, _, _>(new_vault, key);
+            }
+        }
+    }
+
+    /// Returns the ids of the open replace requests of the old vault
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - account of the old vault
+    pub fn get_replace_requests_for_old_vault(vault_id: T::AccountId) -> Vec<H256> {
+        <ReplaceRequestsByOldVault<T>>::get(vault_id)
+    }
+
+    /// Returns the ids of the open replace requests accepted by the new vault
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - account of the new vault
+    pub fn get_replace_requests_for_new_vault(vault_id: T::AccountId) -> Vec<H256> {
+        <ReplaceRequestsByNewVault<T>>::get(vault_id)
+    }
+
+    /// Calculates the griefing collateral a vault needs to provide to
+    /// replace `amount` of PolkaBTC
+    fn get_required_griefing_collateral(amount: PolkaBTC<T>) -> Result<DOT<T>, Error> {
+        let amount_in_dot = ext::oracle::btc_to_dots::<T>(amount)?;
+        let raw_amount_in_dot =
+            TryInto::<u128>::try_into(amount_in_dot).map_err(|_e| Error::RuntimeError)?;
+        let raw_griefing_collateral = raw_amount_in_dot
+            .checked_mul(<ReplaceGriefingCollateralRate>::get())
+            .ok_or(Error::RuntimeError)?
+            .checked_div(10u128.pow(GRANULARITY))
+            .ok_or(Error::RuntimeError)?;
+        let griefing_collateral = TryInto::<DOT<T>>::try_into(raw_griefing_collateral)
+            .map_err(|_e| Error::RuntimeError)?;
+
+        let minimum = <ReplaceGriefingCollateral<T>>::get();
+        if griefing_collateral > minimum {
+            Ok(griefing_collateral)
+        } else {
+            Ok(minimum)
+        }
     }
 
     #[allow(dead_code)]
@@ -475,6 +604,11 @@ impl<T: Trait> Module<T> {
         <ReplaceGriefingCollateral<T>>::set(amount);
     }
 
+    #[allow(dead_code)]
+    fn set_replace_griefing_collateral_rate(rate: u128) {
+        <ReplaceGriefingCollateralRate>::set(rate);
+    }
+
     #[allow(dead_code)]
     fn set_replace_period(value: T::BlockNumber) {
         <ReplacePeriod<T>>::set(value);
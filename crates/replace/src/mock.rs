@@ -95,6 +95,7 @@ impl pallet_balances::Trait for Test {
 
 impl vault_registry::Trait for Test {
     type Event = TestEvent;
+    type OnVaultLiquidated = ();
 }
 
 impl collateral::Trait for Test {
@@ -3,12 +3,12 @@ use crate::mock::*;
 use crate::PolkaBTC;
 use crate::Replace as R;
 use crate::DOT;
+use bitcoin::address::BtcAddress;
 use bitcoin::types::H256Le;
 use frame_support::{assert_noop, assert_ok};
 use mocktopus::mocking::*;
 use primitive_types::H256;
-use sp_core::H160;
-use vault_registry::Vault;
+use vault_registry::{Vault, Wallet};
 use x_core::{Error, UnitResult};
 
 type Event = crate::Event<Test>;
@@ -39,7 +39,7 @@ fn test_request() -> R<u64, u64, u64, u64> {
         accept_time: None,
         amount: 10,
         griefing_collateral: 0,
-        btc_address: H160([0; 20]),
+        btc_address: BtcAddress::default(),
         collateral: 20,
     }
 }
@@ -49,7 +49,7 @@ fn test_vault() -> Vault<u64, u64, u64> {
         id: BOB,
         banned_until: None,
         issued_tokens: 5,
-        btc_address: H160([0; 20]),
+        wallet: Wallet::new(BtcAddress::default()),
         to_be_issued_tokens: 0,
         to_be_redeemed_tokens: 0,
     }
@@ -131,7 +131,7 @@ fn test_request_replace_vault_banned_fails() {
                 to_be_issued_tokens: 0,
                 issued_tokens: 0,
                 to_be_redeemed_tokens: 0,
-                btc_address: H160([0; 20]),
+                wallet: Wallet::new(BtcAddress::default()),
                 banned_until: Some(1),
             }))
         });
@@ -156,7 +156,7 @@ fn test_request_replace_insufficient_griefing_collateral_fails() {
                 to_be_issued_tokens: 0,
                 issued_tokens: 10,
                 to_be_redeemed_tokens: 0,
-                btc_address: H160([0; 20]),
+                wallet: Wallet::new(BtcAddress::default()),
                 banned_until: None,
             }))
         });
@@ -172,6 +172,47 @@ fn test_request_replace_insufficient_griefing_collateral_fails() {
     })
 }
 
+#[test]
+fn test_request_replace_griefing_collateral_scales_with_amount() {
+    run_test(|| {
+        ext::vault_registry::ensure_not_banned::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::get_vault_from_id::<Test>.mock_safe(|_| {
+            MockResult::Return(Ok(Vault {
+                id: BOB,
+                to_be_issued_tokens: 0,
+                issued_tokens: 100,
+                to_be_redeemed_tokens: 0,
+                wallet: Wallet::new(BtcAddress::default()),
+                banned_until: None,
+            }))
+        });
+        ext::collateral::get_collateral_from_account::<Test>.mock_safe(|_| MockResult::Return(1));
+        ext::vault_registry::is_over_minimum_collateral::<Test>
+            .mock_safe(|_| MockResult::Return(true));
+        ext::collateral::lock_collateral::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::increase_to_be_redeemed_tokens::<Test>
+            .mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|amount| MockResult::Return(Ok(amount * 2)));
+
+        Replace::set_replace_griefing_collateral(2);
+        Replace::set_replace_griefing_collateral_rate(10_000); // 10%
+
+        // 100 PolkaBTC are worth 200 DOT, of which 10% are required
+        assert_noop!(
+            Replace::_request_replace(BOB, 100, 19),
+            Error::InsufficientCollateral
+        );
+        assert_ok!(Replace::_request_replace(BOB, 100, 20));
+
+        // small requests require at least the minimum griefing collateral
+        assert_noop!(
+            Replace::_request_replace(BOB, 5, 1),
+            Error::InsufficientCollateral
+        );
+        assert_ok!(Replace::_request_replace(BOB, 5, 2));
+    })
+}
+
 #[test]
 fn test_withdraw_replace_request_invalid_replace_id_fails() {
     run_test(|| {
@@ -490,6 +531,29 @@ fn test_execute_replace_replace_period_expired_fails() {
     })
 }
 
+#[test]
+fn test_execute_replace_unknown_new_vault_fails_before_transaction_checks() {
+    run_test(|| {
+        Replace::get_replace_request.mock_safe(|_| {
+            let mut req = test_request();
+            req.open_time = 5;
+            MockResult::Return(Ok(req))
+        });
+        Replace::current_height.mock_safe(|| MockResult::Return(10));
+        Replace::replace_period.mock_safe(|| MockResult::Return(20));
+
+        ext::vault_registry::get_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Err(Error::VaultNotFound)));
+        ext::btc_relay::verify_transaction_inclusion::<Test>
+            .mock_safe(|_, _, _| MockResult::Return(Err(Error::InvalidMerkleProof)));
+
+        assert_eq!(
+            execute_replace(BOB, H256::zero(), H256Le::zero(), 1, Vec::new(), Vec::new()),
+            Err(Error::VaultNotFound)
+        );
+    })
+}
+
 #[test]
 fn test_cancel_replace_invalid_replace_id_fails() {
     run_test(|| {
@@ -762,6 +826,89 @@ fn test_execute_replace_succeeds() {
     })
 }
 
+#[test]
+fn test_replace_requests_are_indexed_by_vault() {
+    run_test(|| {
+        let replace_id = H256::zero();
+        let mut replace = test_request();
+        Replace::insert_replace_request(replace_id, replace.clone());
+        assert_eq!(
+            Replace::get_replace_requests_for_old_vault(ALICE),
+            vec![replace_id]
+        );
+        assert!(Replace::get_replace_requests_for_new_vault(BOB).is_empty());
+
+        // accepting the request stores it again with the new vault
+        replace.new_vault = Some(BOB);
+        Replace::insert_replace_request(replace_id, replace);
+        assert_eq!(
+            Replace::get_replace_requests_for_old_vault(ALICE),
+            vec![replace_id]
+        );
+        assert_eq!(
+            Replace::get_replace_requests_for_new_vault(BOB),
+            vec![replace_id]
+        );
+
+        Replace::remove_replace_request(replace_id);
+        assert!(Replace::get_replace_requests_for_old_vault(ALICE).is_empty());
+        assert!(Replace::get_replace_requests_for_new_vault(BOB).is_empty());
+    })
+}
+
+#[test]
+fn test_verify_and_validate_transaction_succeeds() {
+    run_test(|| {
+        Replace::get_replace_request.mock_safe(|_| {
+            let mut replace = test_request();
+            replace.open_time = 5;
+            MockResult::Return(Ok(replace))
+        });
+        Replace::current_height.mock_safe(|| MockResult::Return(10));
+        Replace::replace_period.mock_safe(|| MockResult::Return(20));
+        ext::btc_relay::verify_transaction_inclusion::<Test>
+            .mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        ext::btc_relay::validate_transaction::<Test>
+            .mock_safe(|_, _, _, _| MockResult::Return(Ok(())));
+        ext::vault_registry::replace_tokens::<Test>
+            .mock_safe(|_, _, _, _| panic!("the replace request must not be executed"));
+
+        assert_ok!(Replace::_verify_and_validate_transaction(
+            H256::zero(),
+            H256Le::zero(),
+            1,
+            Vec::new(),
+            Vec::new()
+        ));
+    })
+}
+
+#[test]
+fn test_verify_and_validate_transaction_returns_inclusion_error() {
+    run_test(|| {
+        Replace::get_replace_request.mock_safe(|_| {
+            let mut replace = test_request();
+            replace.open_time = 5;
+            MockResult::Return(Ok(replace))
+        });
+        Replace::current_height.mock_safe(|| MockResult::Return(10));
+        Replace::replace_period.mock_safe(|| MockResult::Return(20));
+        ext::btc_relay::verify_transaction_inclusion::<Test>
+            .mock_safe(|_, _, _| MockResult::Return(Err(Error::Confirmations)));
+
+        assert_eq!(
+            Replace::_verify_and_validate_transaction(
+                H256::zero(),
+                H256Le::zero(),
+                1,
+                Vec::new(),
+                Vec::new()
+            ),
+            Err(Error::Confirmations)
+        );
+    })
+}
+
 #[test]
 fn test_cancel_replace_succeeds() {
     run_test(|| {
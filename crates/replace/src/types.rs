@@ -1,6 +1,6 @@
+use bitcoin::address::BtcAddress;
 use codec::{Decode, Encode};
 use frame_support::traits::Currency;
-use sp_core::H160;
 
 pub(crate) type DOT<T> =
     <<T as collateral::Trait>::DOT as Currency<<T as system::Trait>::AccountId>>::Balance;
@@ -17,7 +17,7 @@ pub struct Replace<AccountId, BlockNumber, PolkaBTC, DOT> {
     pub new_vault: Option<AccountId>,
     pub collateral: DOT,
     pub accept_time: Option<BlockNumber>,
-    pub btc_address: H160,
+    pub btc_address: BtcAddress,
 }
 
 impl<AccountId, BlockNumber, PolkaBTC, DOT> Replace<AccountId, BlockNumber, PolkaBTC, DOT> {
@@ -26,7 +26,7 @@ impl<AccountId, BlockNumber, PolkaBTC, DOT> Replace<AccountId, BlockNumber, Polk
         new_vault_id: AccountId,
         accept_time: BlockNumber,
         collateral: DOT,
-        btc_address: H160,
+        btc_address: BtcAddress,
     ) {
         self.new_vault = Some(new_vault_id);
         self.accept_time = Some(accept_time);
@@ -23,6 +23,7 @@ use crate::types::{
     ActiveStakedRelayer, InactiveStakedRelayer, PolkaBTC, ProposalStatus, StakedRelayerStatus,
     StatusUpdate, Tally, DOT,
 };
+use bitcoin::address::BtcAddress;
 use bitcoin::parser::parse_transaction;
 use bitcoin::types::*;
 /// # Staked Relayers module implementation
@@ -39,11 +40,12 @@ use frame_support::{
 };
 use primitive_types::H256;
 use security::types::{ErrorCode, StatusCode};
-use sp_core::{H160, U256};
+use sp_core::U256;
 use sp_std::collections::btree_set::BTreeSet;
 use sp_std::convert::TryInto;
 use sp_std::vec::Vec;
 use system::ensure_signed;
+use vault_registry::Wallet;
 
 /// ## Configuration
 /// The pallet's configuration trait.
@@ -875,14 +877,14 @@ impl<T: Trait> Module<T> {
 
     /// Checks if the vault is doing a valid merge transaction to move funds between
     /// addresses.
-    pub(crate) fn is_valid_merge_transaction(tx: &Transaction, vault_addr: H160) -> bool {
+    pub(crate) fn is_valid_merge_transaction(tx: &Transaction, wallet: &Wallet) -> bool {
         for out in &tx.outputs {
             // return if address not extractable (i.e. op_return)
             let out_addr = match out.extract_address() {
                 Ok(addr) => addr,
                 Err(_) => return false,
             };
-            if H160::from_slice(&out_addr) != vault_addr {
+            if !wallet.has_btc_address(&out_addr) {
                 return false;
             }
         }
@@ -894,9 +896,9 @@ impl<T: Trait> Module<T> {
         tx: &Transaction,
         out_val: i64,
         exp_val: PolkaBTC<T>,
-        out_addr: H160,
-        req_addr: H160,
-        vault_addr: H160,
+        out_addr: BtcAddress,
+        req_addr: BtcAddress,
+        wallet: &Wallet,
     ) -> Result<bool, DispatchError> {
         let value =
             TryInto::<u64>::try_into(exp_val).map_err(|_e| Error::<T>::RuntimeError)? as i64;
@@ -906,7 +908,7 @@ impl<T: Trait> Module<T> {
             if tx.outputs.len() == 3 {
                 let out = &tx.outputs[2];
                 if let Ok(vault_out_addr) = out.extract_address() {
-                    return Ok(H160::from_slice(&vault_out_addr) == vault_addr);
+                    return Ok(wallet.has_btc_address(&vault_out_addr));
                 }
                 return Ok(false);
             }
@@ -931,18 +933,18 @@ impl<T: Trait> Module<T> {
         let tx = parse_transaction(raw_tx.as_slice())?;
 
         // collect all addresses that feature in the inputs of the transaction
-        let input_addresses: Vec<Result<Vec<u8>, _>> = tx
+        let input_addresses: Vec<Result<BtcAddress, _>> = tx
             .clone()
             .inputs
             .into_iter()
             .map(|input| input.extract_address())
             .collect();
 
-        // check if vault's btc address features in an input of the transaction
+        // check if one of the vault's btc addresses features in an input of the transaction
         ensure!(
             input_addresses.into_iter().any(|address_result| {
                 match address_result {
-                    Ok(address) => H160::from_slice(&address) == vault.btc_address,
+                    Ok(address) => vault.wallet.has_btc_address(&address),
                     _ => false,
                 }
             }),
@@ -951,7 +953,7 @@ impl<T: Trait> Module<T> {
 
         // check if the transaction is a "migration"
         ensure!(
-            !Self::is_valid_merge_transaction(&tx, vault.btc_address),
+            !Self::is_valid_merge_transaction(&tx, &vault.wallet),
             Error::<T>::ValidMergeTransaction
         );
 
@@ -974,7 +976,6 @@ impl<T: Trait> Module<T> {
                 // check if redeem / replace
                 if let Ok(out_ret) = out.script.extract_op_return_data() {
                     let id = H256::from_slice(&out_ret);
-                    let addr = H160::from_slice(&out_addr);
                     match ext::redeem::get_redeem_request_from_id::<T>(&id) {
                         Ok(req) => {
                             ensure!(
@@ -982,9 +983,9 @@ impl<T: Trait> Module<T> {
                                     &tx,
                                     out_val,
                                     req.amount_btc,
-                                    addr,
+                                    out_addr,
                                     req.btc_address,
-                                    vault.btc_address,
+                                    &vault.wallet,
                                 )?,
                                 Error::<T>::ValidRedeemTransaction
                             );
@@ -998,9 +999,9 @@ impl<T: Trait> Module<T> {
                                     &tx,
                                     out_val,
                                     req.amount,
-                                    addr,
+                                    out_addr,
                                     req.btc_address,
-                                    vault.btc_address,
+                                    &vault.wallet,
                                 )?,
                                 Error::<T>::ValidReplaceTransaction
                             );
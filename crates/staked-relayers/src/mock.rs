@@ -36,6 +36,7 @@ impl_outer_event! {
         exchange_rate_oracle<T>,
         btc_relay,
         redeem<T>,
+        fee<T>,
         replace<T>,
         security,
     }
@@ -110,6 +111,7 @@ impl security::Trait for Test {
 
 impl vault_registry::Trait for Test {
     type Event = TestEvent;
+    type OnVaultLiquidated = ();
 }
 
 impl treasury::Trait for Test {
@@ -130,8 +132,22 @@ impl btc_relay::Trait for Test {
     type Event = TestEvent;
 }
 
+parameter_types! {
+    pub const EpochPeriod: u64 = 100;
+}
+
+impl fee::Trait for Test {
+    type Event = TestEvent;
+    type EpochPeriod = EpochPeriod;
+}
+
+parameter_types! {
+    pub const MaxExpiredRedeemsPerBlock: u32 = 2;
+}
+
 impl redeem::Trait for Test {
     type Event = TestEvent;
+    type MaxExpiredRedeemsPerBlock = MaxExpiredRedeemsPerBlock;
 }
 
 impl replace::Trait for Test {
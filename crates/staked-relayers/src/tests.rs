@@ -4,6 +4,7 @@ use crate::types::{
     Tally,
 };
 use crate::{ext, mock::*};
+use bitcoin::address::BtcAddress;
 use bitcoin::formatter::Formattable;
 use bitcoin::types::{
     Address, H256Le, TransactionBuilder, TransactionInputBuilder, TransactionOutput,
@@ -16,7 +17,7 @@ use security::types::{ErrorCode, StatusCode};
 use sp_core::{H160, H256, U256};
 use sp_std::collections::btree_set::BTreeSet;
 use std::convert::TryInto;
-use vault_registry::Vault;
+use vault_registry::{Vault, Wallet};
 
 type Event = crate::Event<Test>;
 
@@ -47,12 +48,12 @@ macro_rules! assert_not_emitted {
 /// Mocking functions
 fn init_zero_vault<Test>(
     id: AccountId,
-    btc_address: Option<H160>,
+    btc_address: Option<BtcAddress>,
 ) -> Vault<AccountId, BlockNumber, u64> {
     let mut vault = Vault::default();
     vault.id = id;
     match btc_address {
-        Some(btc_address) => vault.btc_address = btc_address,
+        Some(btc_address) => vault.wallet = Wallet::new(btc_address),
         None => {}
     }
     vault
@@ -715,10 +716,10 @@ fn test_report_vault_passes_with_vault_transaction() {
         inject_active_staked_relayer(&ALICE, amount);
         let vault = CAROL;
 
-        let btc_address = H160::from_slice(&[
+        let btc_address = BtcAddress::P2PKH(H160::from_slice(&[
             126, 125, 148, 208, 221, 194, 29, 131, 191, 188, 252, 119, 152, 228, 84, 126, 223, 8,
             50, 170,
-        ]);
+        ]));
         ext::vault_registry::get_vault_from_id::<Test>.mock_safe(move |_| {
             MockResult::Return(Ok(init_zero_vault::<Test>(
                 vault.clone(),
@@ -749,10 +750,10 @@ fn test_report_vault_fails_with_nonvault_transaction() {
         inject_active_staked_relayer(&ALICE, amount);
         let vault = CAROL;
 
-        let btc_address = H160::from_slice(&[
+        let btc_address = BtcAddress::P2PKH(H160::from_slice(&[
             125, 125, 148, 208, 221, 194, 29, 131, 191, 188, 252, 119, 152, 228, 84, 126, 223, 8,
             50, 170,
-        ]);
+        ]));
 
         ext::vault_registry::get_vault_from_id::<Test>.mock_safe(move |_| {
             MockResult::Return(Ok(init_zero_vault::<Test>(
@@ -787,10 +788,10 @@ fn test_report_vault_succeeds_with_segwit_transaction() {
         inject_active_staked_relayer(&ALICE, amount);
         let vault = CAROL;
 
-        let btc_address = H160::from_slice(&[
+        let btc_address = BtcAddress::P2PKH(H160::from_slice(&[
             164, 180, 202, 72, 222, 11, 63, 255, 193, 84, 4, 161, 172, 220, 141, 186, 174, 34, 105,
             85,
-        ]);
+        ]));
         ext::vault_registry::get_vault_from_id::<Test>.mock_safe(move |_| {
             MockResult::Return(Ok(init_zero_vault::<Test>(
                 vault.clone(),
@@ -967,7 +968,7 @@ fn test_is_valid_merge_transaction_fails() {
         assert_eq!(
             Staking::is_valid_merge_transaction(
                 &transaction,
-                H160::from_slice(address2.as_bytes())
+                &Wallet::new(BtcAddress::P2PKH(H160::from_slice(address2.as_bytes())))
             ),
             false
         );
@@ -999,7 +1000,44 @@ fn test_is_valid_merge_transaction_succeeds() {
             .build();
 
         assert_eq!(
-            Staking::is_valid_merge_transaction(&transaction, H160::from_slice(address.as_bytes())),
+            Staking::is_valid_merge_transaction(
+                &transaction,
+                &Wallet::new(BtcAddress::P2PKH(H160::from_slice(address.as_bytes())))
+            ),
+            true
+        );
+    })
+}
+
+#[test]
+fn test_is_valid_merge_transaction_between_vault_addresses_succeeds() {
+    run_test(|| {
+        let address1: Address = "66c7060feb882664ae62ffad0051fe843e318e85"
+            .try_into()
+            .unwrap();
+
+        let address2: Address = "5f69790b72c98041330644bbd50f2ebb5d073c36"
+            .try_into()
+            .unwrap();
+
+        let mut wallet = Wallet::new(BtcAddress::P2PKH(H160::from_slice(address1.as_bytes())));
+        assert_ok!(wallet.add_btc_address(BtcAddress::P2PKH(H160::from_slice(address2.as_bytes()))));
+
+        let transaction = TransactionBuilder::new()
+            .with_version(1)
+            .add_input(
+                TransactionInputBuilder::new()
+                    .with_coinbase(true)
+                    .with_height(&vec![0, 0, 0, 0])
+                    .with_previous_index(u32::max_value())
+                    .build(),
+            )
+            .add_output(TransactionOutput::p2pkh(50, &address1))
+            .add_output(TransactionOutput::p2pkh(50, &address2))
+            .build();
+
+        assert_eq!(
+            Staking::is_valid_merge_transaction(&transaction, &wallet),
             true
         );
     })
@@ -1037,9 +1075,9 @@ fn test_is_valid_request_transaction_fails() {
                 &transaction,
                 50,
                 100,
-                H160::from_slice(address1.as_bytes()),
-                H160::from_slice(address1.as_bytes()),
-                H160::from_slice(address2.as_bytes()),
+                BtcAddress::P2PKH(H160::from_slice(address1.as_bytes())),
+                BtcAddress::P2PKH(H160::from_slice(address1.as_bytes())),
+                &Wallet::new(BtcAddress::P2PKH(H160::from_slice(address2.as_bytes()))),
             ),
             Ok(false)
         );
@@ -1078,9 +1116,9 @@ fn test_is_valid_request_transaction_succeeds() {
                 &transaction,
                 100,
                 100,
-                H160::from_slice(address1.as_bytes()),
-                H160::from_slice(address1.as_bytes()),
-                H160::from_slice(address2.as_bytes()),
+                BtcAddress::P2PKH(H160::from_slice(address1.as_bytes())),
+                BtcAddress::P2PKH(H160::from_slice(address1.as_bytes())),
+                &Wallet::new(BtcAddress::P2PKH(H160::from_slice(address2.as_bytes()))),
             ),
             Ok(true)
         );
@@ -1101,7 +1139,7 @@ fn test_check_invalid_transaction_fails_with_valid_merge_transaction() {
                 to_be_issued_tokens: 0,
                 issued_tokens: 0,
                 to_be_redeemed_tokens: 0,
-                btc_address: H160::from_slice(address.as_bytes()),
+                wallet: Wallet::new(BtcAddress::P2PKH(H160::from_slice(address.as_bytes()))),
                 banned_until: None,
             }))
         });
@@ -1141,10 +1179,10 @@ fn test_check_invalid_transaction_fails_with_valid_merge_transaction() {
 #[test]
 fn test_check_invalid_transaction_fails_with_valid_request_or_redeem() {
     run_test(|| {
-        let address1 = Address::from([
-            164, 180, 202, 72, 222, 11, 63, 255, 193, 84, 4, 161, 172, 220, 141, 186, 174, 34, 105,
-            85,
-        ]);
+        // P2SH address of the nested P2WPKH witness program 0x0014a4b4ca48de0b3fffc15404a1acdc8dbaae226955
+        let address1 = BtcAddress::P2SH(H160::from([
+            41, 40, 244, 58, 241, 141, 45, 96, 232, 168, 67, 84, 13, 128, 134, 179, 5, 52, 19, 57,
+        ]));
 
         let address2: Address = "5f69790b72c98041330644bbd50f2ebb5d073c36"
             .try_into()
@@ -1156,7 +1194,7 @@ fn test_check_invalid_transaction_fails_with_valid_request_or_redeem() {
                 to_be_issued_tokens: 0,
                 issued_tokens: 0,
                 to_be_redeemed_tokens: 0,
-                btc_address: H160::from_slice(address1.as_bytes()),
+                wallet: Wallet::new(address1),
                 banned_until: None,
             }))
         });
@@ -1166,11 +1204,12 @@ fn test_check_invalid_transaction_fails_with_valid_request_or_redeem() {
                 vault: BOB,
                 opentime: 0,
                 amount_polka_btc: 0,
+                fee_polka_btc: 0,
                 amount_btc: 100,
                 amount_dot: 0,
                 premium_dot: 0,
                 redeemer: ALICE,
-                btc_address: H160::from_slice(address2.as_bytes()),
+                btc_address: BtcAddress::P2PKH(H160::from_slice(address2.as_bytes())),
             }))
         });
 
@@ -1225,7 +1264,7 @@ fn test_check_invalid_transaction_fails_with_valid_request_or_redeem() {
                 new_vault: None,
                 collateral: 0,
                 accept_time: None,
-                btc_address: H160::from_slice(address2.as_bytes()),
+                btc_address: BtcAddress::P2PKH(H160::from_slice(address2.as_bytes())),
             }))
         });
 
@@ -1251,7 +1290,9 @@ fn test_check_invalid_transaction_succeeds() {
         ext::vault_registry::get_vault_from_id::<Test>.mock_safe(move |_| {
             MockResult::Return(Ok(init_zero_vault::<Test>(
                 BOB,
-                Some(H160::from_slice(&vault_address.as_bytes())),
+                Some(BtcAddress::P2PKH(H160::from_slice(
+                    &vault_address.as_bytes(),
+                ))),
             )))
         });
 
@@ -13,7 +13,11 @@ extern crate mocktopus;
 // #[cfg(test)]
 // use mocktopus::macros::mockable;
 
-use frame_support::traits::{Currency, ExistenceRequirement::KeepAlive, ReservableCurrency};
+use frame_support::traits::{
+    Currency,
+    ExistenceRequirement::{AllowDeath, KeepAlive},
+    ReservableCurrency,
+};
 /// # PolkaBTC Treasury implementation
 /// The Treasury module according to the specification at
 /// https://interlay.gitlab.io/polkabtc-spec/spec/treasury.html
@@ -64,6 +68,7 @@ decl_event!(
         Transfer(AccountId, AccountId, Balance),
         Mint(AccountId, Balance),
         Lock(AccountId, Balance),
+        Unlock(AccountId, Balance),
         Burn(AccountId, Balance),
     }
 );
@@ -122,6 +127,26 @@ impl<T: Trait> Module<T> {
         let new_locked = <TotalLocked<T>>::get() - amount;
         <TotalLocked<T>>::put(new_locked);
     }
+    /// Transfer an amount of PolkaBTC between two accounts, e.g. to pay
+    /// out rewards from a pallet's account. Unlike the dispatchable
+    /// `transfer`, the sender's account may be emptied completely.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - sender of the PolkaBTC
+    /// * `receiver` - receiver of the PolkaBTC
+    /// * `amount` - amount of PolkaBTC
+    pub fn _transfer(
+        sender: T::AccountId,
+        receiver: T::AccountId,
+        amount: BalanceOf<T>,
+    ) -> Result<(), Error> {
+        T::PolkaBTC::transfer(&sender, &receiver, amount, AllowDeath)
+            .map_err(|_| Error::InsufficientFunds)?;
+
+        Self::deposit_event(RawEvent::Transfer(sender, receiver, amount));
+        Ok(())
+    }
     /// Mint new tokens
     ///
     /// # Arguments
@@ -152,6 +177,30 @@ impl<T: Trait> Module<T> {
         Self::deposit_event(RawEvent::Lock(redeemer, amount));
         Ok(())
     }
+    /// Unlock previously locked PolkaBTC tokens, e.g. if a redeem request
+    /// expired. Note: this moves them back to the free balance of PolkaBTC
+    /// and removes them from the locked supply of PolkaBTC.
+    ///
+    /// # Arguments
+    ///
+    /// * `redeemer` - the account that locked the tokens
+    /// * `amount` - the to be unlocked amount of PolkaBTC
+    pub fn unlock(redeemer: T::AccountId, amount: BalanceOf<T>) -> Result<(), Error> {
+        ensure!(
+            T::PolkaBTC::reserved_balance(&redeemer) >= amount,
+            Error::InsufficientLockedFunds
+        );
+
+        // update total locked balance
+        Self::decrease_total_locked(amount);
+
+        // remainder should always be 0 and is checked above
+        let _remainder = T::PolkaBTC::unreserve(&redeemer, amount);
+
+        Self::deposit_event(RawEvent::Unlock(redeemer, amount));
+
+        Ok(())
+    }
     /// Burn previously locked PolkaBTC tokens
     ///
     /// # Arguments
@@ -69,6 +69,21 @@ fn test_transfer_fails() {
     })
 }
 
+#[test]
+fn test_internal_transfer_empties_sender_succeeds() {
+    run_test(|| {
+        assert_ok!(Treasury::_transfer(ALICE, BOB, ALICE_BALANCE));
+        let transfer_event = TestEvent::test_events(RawEvent::Transfer(ALICE, BOB, ALICE_BALANCE));
+
+        assert!(System::events().iter().any(|a| a.event == transfer_event));
+        assert_eq!(Treasury::get_balance_from_account(ALICE), 0);
+        assert_eq!(
+            Treasury::get_balance_from_account(BOB),
+            BOB_BALANCE + ALICE_BALANCE
+        );
+    })
+}
+
 /// Mint
 #[test]
 fn test_mint_succeeds() {
@@ -140,6 +155,55 @@ fn test_lock_fails() {
     })
 }
 
+/// Unlock
+#[test]
+fn test_unlock_succeeds() {
+    run_test(|| {
+        let redeemer = ALICE;
+        let amount = ALICE_BALANCE;
+
+        let init_balance = Treasury::get_balance_from_account(ALICE);
+        let init_locked_balance = Treasury::get_locked_balance_from_account(ALICE);
+        let init_total_supply = Treasury::get_total_supply();
+
+        assert_ok!(Treasury::lock(redeemer, amount));
+        assert_ok!(Treasury::unlock(redeemer, amount));
+        let unlock_event = TestEvent::test_events(RawEvent::Unlock(ALICE, amount));
+
+        assert!(System::events().iter().any(|a| a.event == unlock_event));
+
+        let balance = Treasury::get_balance_from_account(ALICE);
+        let locked_balance = Treasury::get_locked_balance_from_account(ALICE);
+        let total_supply = Treasury::get_total_supply();
+
+        assert_eq!(balance, init_balance);
+        assert_eq!(locked_balance, init_locked_balance);
+        assert_eq!(total_supply, init_total_supply);
+    })
+}
+
+#[test]
+fn test_unlock_fails() {
+    run_test(|| {
+        let redeemer = ALICE;
+        let amount = ALICE_BALANCE;
+
+        let init_balance = Treasury::get_balance_from_account(ALICE);
+        let init_locked_balance = Treasury::get_locked_balance_from_account(ALICE);
+
+        assert_err!(
+            Treasury::unlock(redeemer, amount),
+            Error::InsufficientLockedFunds
+        );
+
+        let balance = Treasury::get_balance_from_account(ALICE);
+        let locked_balance = Treasury::get_locked_balance_from_account(ALICE);
+
+        assert_eq!(balance, init_balance);
+        assert_eq!(locked_balance, init_locked_balance);
+    })
+}
+
 /// Burn
 #[test]
 fn test_burn_succeeds() {
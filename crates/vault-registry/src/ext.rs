@@ -53,12 +53,20 @@ pub(crate) mod oracle {
 
 #[cfg_attr(test, mockable)]
 pub(crate) mod security {
-    use security::ErrorCode;
+    use security::{ErrorCode, StatusCode};
     use sp_std::vec::Vec;
-    use x_core::UnitResult;
+    use x_core::{Error, UnitResult};
+
+    pub fn set_parachain_status<T: security::Trait>(status_code: StatusCode) {
+        <security::Module<T>>::set_parachain_status(status_code)
+    }
+
+    pub fn insert_error<T: security::Trait>(error_code: ErrorCode) {
+        <security::Module<T>>::insert_error(error_code)
+    }
 
     pub fn recover_from_liquidation<T: security::Trait>() -> UnitResult {
-        Ok(())
+        <security::Module<T>>::recover_from_liquidation().map_err(|_e| Error::RuntimeError)
     }
 
     pub fn ensure_parachain_status_running<T: security::Trait>() -> UnitResult {
@@ -75,3 +83,14 @@ pub(crate) mod security {
         <security::Module<T>>::_ensure_parachain_status_has_not_specific_errors(error_codes)
     }
 }
+
+#[cfg_attr(test, mockable)]
+pub(crate) mod randomness {
+    use codec::Decode;
+    use frame_support::traits::Randomness;
+
+    pub fn random_u128<T: system::Trait>() -> u128 {
+        let seed = <randomness_collective_flip::Module<T>>::random(b"vault-selection");
+        u128::decode(&mut seed.as_ref()).unwrap_or(0)
+    }
+}
@@ -20,25 +20,30 @@ extern crate mocktopus;
 #[cfg(test)]
 use mocktopus::macros::mockable;
 
+use bitcoin::address::BtcAddress;
 use codec::{Decode, Encode};
 use frame_support::dispatch::DispatchResult;
-use frame_support::{decl_event, decl_module, decl_storage, ensure};
+use frame_support::{decl_event, decl_module, decl_storage, ensure, IterableStorageMap};
 use primitive_types::H256;
-use sp_core::H160;
 use sp_std::convert::TryInto;
 use sp_std::vec::Vec;
 use system::ensure_signed;
 
-use security::ErrorCode;
+use security::{ErrorCode, StatusCode};
 use x_core::{Error, Result, UnitResult};
 
-pub use crate::types::Vault;
 use crate::types::{DefaultVault, PolkaBTC, RichVault, DOT};
+pub use crate::types::{Vault, Wallet};
 
 /// Granularity of `SecureCollateralThreshold`, `AuctionCollateralThreshold`,
 /// `LiquidationCollateralThreshold`, and `PunishmentFee`
 pub const GRANULARITY: u32 = 5;
 
+/// Maximum number of vaults examined when selecting the vaults of an issue
+/// request, which bounds the storage reads of `request_issue_auto` and
+/// `request_issue_split`
+pub const MAX_VAULTS_EXAMINED: u32 = 100;
+
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct RegisterRequest<AccountId, DateTime> {
@@ -54,6 +59,34 @@ pub trait Trait:
 {
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+    /// Handler for the open requests of a liquidated vault.
+    type OnVaultLiquidated: OnVaultLiquidated<Self::AccountId>;
+}
+
+/// Notifies pallets which hold requests assigned to vaults that a vault has
+/// been liquidated, such that they can reassign its open requests to the
+/// `LiquidationVault`, which took over the vault's tokens and collateral.
+pub trait OnVaultLiquidated<AccountId> {
+    /// # Arguments
+    /// * `vault_id` - the id of the liquidated vault
+    /// * `liquidation_vault_id` - the id of the `LiquidationVault`
+    fn on_vault_liquidated(vault_id: &AccountId, liquidation_vault_id: &AccountId);
+}
+
+impl<AccountId> OnVaultLiquidated<AccountId> for () {
+    fn on_vault_liquidated(_: &AccountId, _: &AccountId) {}
+}
+
+impl<AccountId, A, B> OnVaultLiquidated<AccountId> for (A, B)
+where
+    A: OnVaultLiquidated<AccountId>,
+    B: OnVaultLiquidated<AccountId>,
+{
+    fn on_vault_liquidated(vault_id: &AccountId, liquidation_vault_id: &AccountId) {
+        A::on_vault_liquidated(vault_id, liquidation_vault_id);
+        B::on_vault_liquidated(vault_id, liquidation_vault_id);
+    }
 }
 
 // This pallet's storage items.
@@ -83,7 +116,7 @@ decl_storage! {
         /// `PremiumRedeemThreshold`, users are allocated a premium in DOT
         /// when redeeming with the Vault - as defined by this parameter.
         /// For example, if the RedeemPremiumFee is set to 5000, it is equivalent to 5%.
-        RedeemPremiumFee: u128;
+        RedeemPremiumFee get(fn redeem_premium_fee) config(): u128;
 
         /// Determines the over-collateralization rate for DOT collateral locked
         /// by Vaults, necessary for issuing PolkaBTC. Must to be strictly
@@ -111,8 +144,31 @@ decl_storage! {
         /// LiquidationVault and claims are later handled via the LiquidationVault.
         LiquidationVault: T::AccountId;
 
+        /// Premium in DOT paid out of the collateral of the `LiquidationVault` to
+        /// users redeeming PolkaBTC against it, as a share of the redeemed amount.
+        /// For example, if the `LiquidationRedeemPremium` is set to 5000, it is equivalent to 5%.
+        LiquidationRedeemPremium get(fn liquidation_redeem_premium) config(): u128;
+
+        /// Number of failed redeems within `FailedRedeemWindow` blocks after
+        /// which a Vault is liquidated automatically. If set to 0, Vaults are
+        /// never liquidated for failing redeems.
+        FailedRedeemThreshold get(fn failed_redeem_threshold) config(): u32;
+
+        /// Number of blocks within which the failed redeems of a Vault count
+        /// towards the `FailedRedeemThreshold`.
+        FailedRedeemWindow get(fn failed_redeem_window) config(): T::BlockNumber;
+
+        /// Block heights of the failed redeems of each Vault within the
+        /// `FailedRedeemWindow`.
+        FailedRedeems: map hasher(blake2_128_concat) T::AccountId => Vec<T::BlockNumber>;
+
         /// Mapping of Vaults, using the respective Vault account identifier as key.
         Vaults: map hasher(blake2_128_concat) T::AccountId => Vault<T::AccountId, T::BlockNumber, PolkaBTC<T>>;
+
+        /// Mapping of the BTC addresses registered by Vaults to the respective
+        /// Vault account identifier. An address can only be registered once,
+        /// and is released again when its Vault is liquidated.
+        ReservedBtcAddresses: map hasher(blake2_128_concat) BtcAddress => T::AccountId;
     }
 }
 
@@ -133,9 +189,10 @@ decl_module! {
         /// # Errors
         /// * `InsuficientVaultCollateralAmount` - if the collateral is below the minimum threshold
         /// * `VaultAlreadyRegistered` - if a vault is already registered for the origin account
+        /// * `BtcAddressAlreadyRegistered` - if the address is already registered by a vault
         /// * `InsufficientCollateralAvailable` - if the vault does not own enough collateral
         #[weight = 1000]
-        fn register_vault(origin, collateral: DOT<T>, btc_address: H160) -> DispatchResult {
+        fn register_vault(origin, collateral: DOT<T>, btc_address: BtcAddress) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ext::security::ensure_parachain_status_running::<T>()?;
             ext::security::ensure_parachain_status_running::<T>()?;
@@ -143,16 +200,42 @@ decl_module! {
             ensure!(collateral >= Self::get_minimum_collateral_vault(),
                     Error::InsuficientVaultCollateralAmount);
             ensure!(!Self::vault_exists(&sender), Error::VaultAlreadyRegistered);
+            ensure!(!Self::btc_address_reserved(&btc_address), Error::BtcAddressAlreadyRegistered);
 
             ext::collateral::lock::<T>(&sender, collateral)?;
             let vault = RichVault::<T>::new(sender.clone(), btc_address);
             Self::_insert_vault(&sender, &vault);
+            Self::reserve_btc_address(&btc_address, &sender);
 
             Self::deposit_event(Event::<T>::RegisterVault(vault.id(), collateral));
 
             Ok(())
         }
 
+        /// Registers an additional BTC address for the Vault of the origin account.
+        /// Issue requests are assigned the Vault's addresses in a round-robin fashion.
+        ///
+        /// # Arguments
+        /// * `btc_address` - the BTC address to add to the Vault's wallet
+        ///
+        /// # Errors
+        /// * `VaultNotFound` - if no vault exists for the origin account
+        /// * `BtcAddressAlreadyRegistered` - if the address is already registered by a vault
+        /// * `MaxBtcAddressesReached` - if the vault cannot register any more addresses
+        #[weight = 1000]
+        fn register_address(origin, btc_address: BtcAddress) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            ext::security::ensure_parachain_status_running::<T>()?;
+
+            let mut vault = Self::rich_vault_from_id(&sender)?;
+            ensure!(!Self::btc_address_reserved(&btc_address), Error::BtcAddressAlreadyRegistered);
+            vault.add_btc_address(btc_address)?;
+            Self::reserve_btc_address(&btc_address, &sender);
+
+            Self::deposit_event(Event::<T>::RegisterAddress(vault.id(), btc_address));
+            Ok(())
+        }
+
         /// Locks additional collateral as a security against stealing the
         /// Bitcoin locked with it.
         ///
@@ -216,8 +299,20 @@ impl<T: Trait> Module<T> {
         Self::u128_to_dot(PunishmentFee::get())
     }
 
-    pub fn _get_redeem_premium_fee() -> Result<DOT<T>> {
-        Self::u128_to_dot(RedeemPremiumFee::get())
+    /// Returns the premium in DOT paid to a user redeeming `tokens` with a
+    /// Vault below the `PremiumRedeemThreshold`: the `RedeemPremiumFee` share
+    /// of the value of the tokens at the current exchange rate
+    ///
+    /// # Arguments
+    /// * `tokens` - the amount of PolkaBTC redeemed
+    pub fn _get_redeem_premium(tokens: PolkaBTC<T>) -> Result<DOT<T>> {
+        let value = Self::dot_to_u128(ext::oracle::btc_to_dots::<T>(tokens)?)?;
+        let premium = value
+            .checked_mul(<RedeemPremiumFee>::get())
+            .ok_or(Error::RuntimeError)?
+            .checked_div(10u128.pow(GRANULARITY))
+            .ok_or(Error::RuntimeError)?;
+        Self::u128_to_dot(premium)
     }
 
     pub fn _get_vault_from_id(vault_id: &T::AccountId) -> Result<DefaultVault<T>> {
@@ -231,18 +326,34 @@ impl<T: Trait> Module<T> {
     /// * `vault_id` - the id of the vault from which to increase to-be-issued tokens
     /// * `tokens` - the amount of tokens to be reserved
     ///
+    /// Returns the BTC address of the vault assigned to this issue request,
+    /// rotating through the addresses registered by the vault
+    ///
     /// # Errors
     /// * `VaultNotFound` - if no vault exists for the given `vault_id`
     /// * `ExceedingVaultLimit` - if the amount of tokens to be issued is higher than the issuable amount by the vault
     pub fn _increase_to_be_issued_tokens(
         vault_id: &T::AccountId,
         tokens: PolkaBTC<T>,
-    ) -> Result<H160> {
+    ) -> Result<BtcAddress> {
         ext::security::ensure_parachain_status_running::<T>()?;
         let mut vault = Self::rich_vault_from_id(&vault_id)?;
         vault.increase_to_be_issued(tokens)?;
         Self::deposit_event(Event::<T>::IncreaseToBeIssuedTokens(vault.id(), tokens));
-        Ok(vault.data.btc_address)
+        Ok(vault.rotate_btc_address())
+    }
+
+    /// Returns the amount of tokens the vault can still issue with its
+    /// free collateral
+    ///
+    /// # Arguments
+    /// * `vault_id` - the id of the vault
+    ///
+    /// # Errors
+    /// * `VaultNotFound` - if no vault exists for the given `vault_id`
+    pub fn _get_issuable_tokens_from_vault(vault_id: &T::AccountId) -> Result<PolkaBTC<T>> {
+        let vault = Self::rich_vault_from_id(&vault_id)?;
+        vault.issuable_tokens()
     }
 
     /// Decreases the amount of tokens to be issued in the next issue request
@@ -453,7 +564,9 @@ impl<T: Trait> Module<T> {
         let mut vault = Self::rich_vault_from_id(&vault_id)?;
         vault.redeem_tokens(tokens)?;
         if premium > 0.into() {
+            // the premium is paid out as free DOT rather than as collateral
             ext::collateral::slash::<T>(vault_id, redeemer_id, premium)?;
+            ext::collateral::release::<T>(redeemer_id, premium)?;
         }
 
         Self::deposit_event(Event::<T>::RedeemTokensPremium(
@@ -467,7 +580,8 @@ impl<T: Trait> Module<T> {
 
     /// Handles redeem requests which are executed during a LIQUIDATION recover.
     /// Reduces the issued token of the LiquidationVault and slashes the
-    /// corresponding amount of DOT collateral.
+    /// corresponding amount of DOT collateral, plus the `LiquidationRedeemPremium`
+    /// as far as the collateral of the LiquidationVault suffices.
     /// Once LiquidationVault has not more issuedToken left,
     /// removes the LIQUIDATION error from the BTC Parachain status.
     ///
@@ -489,8 +603,14 @@ impl<T: Trait> Module<T> {
         let vault_id = <LiquidationVault<T>>::get();
         let mut vault = Self::rich_vault_from_id(&vault_id)?;
         vault.decrease_issued(tokens)?;
-        let to_slash = ext::oracle::btc_to_dots::<T>(tokens)?;
+        let to_slash = Self::get_liquidation_redeem_value(
+            tokens,
+            vault.data.issued_tokens + vault.data.to_be_issued_tokens,
+            vault.get_collateral(),
+        )?;
         ext::collateral::slash::<T>(&vault_id, &redeemer_id, to_slash)?;
+        // the redeemer receives free DOT rather than locked collateral
+        ext::collateral::release::<T>(&redeemer_id, to_slash)?;
 
         Self::deposit_event(Event::<T>::RedeemTokensLiquidation(
             redeemer_id.clone(),
@@ -547,7 +667,8 @@ impl<T: Trait> Module<T> {
     }
 
     /// Liquidates a vault, transferring all of its token balances to the
-    /// `LiquidationVault`, as well as the DOT collateral
+    /// `LiquidationVault`, as well as the DOT collateral. The open requests
+    /// of the vault are reassigned to the `LiquidationVault`.
     ///
     /// # Arguments
     /// * `vault_id` - the id of the vault to liquidate
@@ -563,11 +684,49 @@ impl<T: Trait> Module<T> {
         let mut liquidation_vault: RichVault<T> = Self::rich_vault_from_id(&liquidation_vault_id)?;
 
         vault.liquidate(&mut liquidation_vault)?;
+        <FailedRedeems<T>>::remove(vault_id);
+        T::OnVaultLiquidated::on_vault_liquidated(vault_id, &liquidation_vault_id);
 
         Self::deposit_event(Event::<T>::LiquidateVault(vault_id.clone()));
         Ok(())
     }
 
+    /// Records a redeem which the vault failed to execute in time. Once the
+    /// vault failed `FailedRedeemThreshold` redeems within `FailedRedeemWindow`
+    /// blocks, it is liquidated and the BTC Parachain enters the `LIQUIDATION`
+    /// error state, such that users can redeem against the `LiquidationVault`.
+    ///
+    /// # Arguments
+    /// * `vault_id` - the id of the vault which failed the redeem
+    /// * `height` - the current block height
+    pub fn _record_failed_redeem(vault_id: &T::AccountId, height: T::BlockNumber) -> UnitResult {
+        let threshold = <FailedRedeemThreshold>::get();
+        // vaults which were already liquidated cannot fail any further, and
+        // the LiquidationVault, which took over their requests, is never
+        // liquidated itself
+        if threshold == 0
+            || !Self::vault_exists(vault_id)
+            || *vault_id == <LiquidationVault<T>>::get()
+        {
+            return Ok(());
+        }
+
+        let window = <FailedRedeemWindow<T>>::get();
+        let mut failures = <FailedRedeems<T>>::get(vault_id);
+        failures.retain(|failed_at| *failed_at + window > height);
+        failures.push(height);
+
+        if failures.len() < threshold as usize {
+            <FailedRedeems<T>>::insert(vault_id, failures);
+            return Ok(());
+        }
+
+        Self::_liquidate_vault(vault_id)?;
+        ext::security::set_parachain_status::<T>(StatusCode::Error);
+        ext::security::insert_error::<T>(ErrorCode::Liquidation);
+        Ok(())
+    }
+
     pub fn _insert_vault<V: Into<DefaultVault<T>>>(id: &T::AccountId, rich_vault: V) {
         let vault: DefaultVault<T> = rich_vault.into();
         <Vaults<T>>::insert(id, vault)
@@ -641,25 +800,127 @@ impl<T: Trait> Module<T> {
         <LiquidationCollateralThreshold>::set(threshold);
     }
 
+    pub fn _set_redeem_premium_fee(fee: u128) {
+        <RedeemPremiumFee>::set(fee);
+    }
+
+    pub fn _set_liquidation_vault(vault_id: &T::AccountId) {
+        <LiquidationVault<T>>::put(vault_id);
+    }
+
+    pub fn _set_liquidation_redeem_premium(premium: u128) {
+        <LiquidationRedeemPremium>::set(premium);
+    }
+
+    pub fn _set_failed_redeem_threshold(threshold: u32) {
+        <FailedRedeemThreshold>::set(threshold);
+    }
+
+    pub fn _set_failed_redeem_window(window: T::BlockNumber) {
+        <FailedRedeemWindow<T>>::set(window);
+    }
+
     pub fn _is_over_minimum_collateral(amount: DOT<T>) -> bool {
         amount > Self::get_minimum_collateral_vault()
     }
 
-    pub fn _get_total_liquidation_value() -> Result<u128> {
+    /// Returns the id and issued tokens of all vaults with a non-zero amount
+    /// of issued tokens, excluding the `LiquidationVault`
+    pub fn _get_vaults_with_issued_tokens() -> Vec<(T::AccountId, PolkaBTC<T>)> {
         let liquidation_vault_id = <LiquidationVault<T>>::get();
+        <Vaults<T>>::iter()
+            .filter(|(id, vault)| *id != liquidation_vault_id && vault.issued_tokens > 0.into())
+            .map(|(id, vault)| (id, vault.issued_tokens))
+            .collect()
+    }
 
-        let liquidation_vault = Self::rich_vault_from_id(&liquidation_vault_id)?;
-
-        let liquidated_polka_btc_in_dot =
-            ext::oracle::btc_to_dots::<T>(liquidation_vault.data.issued_tokens)?;
+    /// Selects a vault that has locked enough collateral to issue `amount`
+    /// of PolkaBTC. The vault is chosen at random, weighted by its free
+    /// collateral, so that issue requests are spread across vaults. Only the
+    /// first `MAX_VAULTS_EXAMINED` vaults in storage order are examined.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - amount of PolkaBTC to be issued
+    pub fn _get_vault_with_sufficient_collateral(amount: PolkaBTC<T>) -> Result<T::AccountId> {
+        let mut candidates = Vec::new();
+        let mut total_weight: u128 = 0;
+        for vault in Self::get_issuable_vaults() {
+            if vault.issuable_tokens()? < amount {
+                continue;
+            }
+            let weight = Self::dot_to_u128(vault.get_free_collateral()?)?;
+            total_weight = total_weight
+                .checked_add(weight)
+                .ok_or(Error::RuntimeError)?;
+            candidates.push((vault.id(), weight));
+        }
+        ensure!(
+            !candidates.is_empty(),
+            Error::NoVaultWithSufficientCollateral
+        );
 
-        let raw_collateral =
-            Self::dot_to_u128(ext::collateral::for_account::<T>(&liquidation_vault_id))?;
+        let mut target = ext::randomness::random_u128::<T>()
+            .checked_rem(total_weight)
+            .unwrap_or(0);
+        for (id, weight) in candidates.iter() {
+            if target < *weight {
+                return Ok(id.clone());
+            }
+            target -= weight;
+        }
+        // only reached if none of the candidates has free collateral
+        Ok(candidates[0].0.clone())
+    }
 
-        let raw_liquidated_polka_btc_in_dot = Self::dot_to_u128(liquidated_polka_btc_in_dot)?;
+    /// Splits `amount` of PolkaBTC across vaults which together have locked
+    /// enough collateral to issue it. Vaults with the most issuable tokens
+    /// are used first, to keep the number of parts small. Only the first
+    /// `MAX_VAULTS_EXAMINED` vaults in storage order are examined.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - amount of PolkaBTC to be issued
+    pub fn _get_vaults_for_split_issue(
+        amount: PolkaBTC<T>,
+    ) -> Result<Vec<(T::AccountId, PolkaBTC<T>)>> {
+        let mut vaults = Vec::new();
+        for vault in Self::get_issuable_vaults() {
+            let issuable_tokens = vault.issuable_tokens()?;
+            if issuable_tokens > 0.into() {
+                vaults.push((vault.id(), issuable_tokens));
+            }
+        }
+        vaults.sort_by(|a, b| b.1.cmp(&a.1));
+
+        let mut parts = Vec::new();
+        let mut remaining = amount;
+        for (id, issuable_tokens) in vaults {
+            if remaining == 0.into() {
+                break;
+            }
+            let part = if issuable_tokens < remaining {
+                issuable_tokens
+            } else {
+                remaining
+            };
+            remaining -= part;
+            parts.push((id, part));
+        }
+        ensure!(
+            remaining == 0.into(),
+            Error::NoVaultWithSufficientCollateral
+        );
+        Ok(parts)
+    }
 
-        let total_liquidation_value = raw_liquidated_polka_btc_in_dot - raw_collateral;
-        Ok(total_liquidation_value)
+    /// Returns the amount of PolkaBTC issued by the LiquidationVault, i.e. the
+    /// PolkaBTC which is no longer backed by BTC but by the DOT collateral of
+    /// liquidated vaults
+    pub fn _get_total_liquidation_value() -> Result<u128> {
+        let liquidation_vault_id = <LiquidationVault<T>>::get();
+        let liquidation_vault = Self::rich_vault_from_id(&liquidation_vault_id)?;
+        Self::polkabtc_to_u128(liquidation_vault.data.issued_tokens)
     }
 
     /// Private getters and setters
@@ -669,6 +930,42 @@ impl<T: Trait> Module<T> {
         Ok(vault.into())
     }
 
+    /// Returns all vaults that are currently allowed to accept issue
+    /// requests, excluding the `LiquidationVault`
+    fn get_issuable_vaults() -> Vec<RichVault<T>> {
+        let height = <system::Module<T>>::block_number();
+        let liquidation_vault_id = <LiquidationVault<T>>::get();
+        <Vaults<T>>::iter()
+            .take(MAX_VAULTS_EXAMINED as usize)
+            .filter(|(id, _)| *id != liquidation_vault_id)
+            .map(|(_, vault)| RichVault::<T>::from(vault))
+            .filter(|vault| vault.ensure_not_banned(height).is_ok())
+            .collect()
+    }
+
+    /// Returns the DOT paid for redeeming `tokens` against the LiquidationVault:
+    /// their value at the current exchange rate plus the premium. The premium
+    /// is only paid from the `collateral` not backing the `remaining_tokens`
+    /// of the LiquidationVault, and the total never exceeds the `collateral`
+    fn get_liquidation_redeem_value(
+        tokens: PolkaBTC<T>,
+        remaining_tokens: PolkaBTC<T>,
+        collateral: DOT<T>,
+    ) -> Result<DOT<T>> {
+        let value = Self::dot_to_u128(ext::oracle::btc_to_dots::<T>(tokens)?)?;
+        let premium = value
+            .checked_mul(<LiquidationRedeemPremium>::get())
+            .ok_or(Error::RuntimeError)?
+            .checked_div(10u128.pow(GRANULARITY))
+            .ok_or(Error::RuntimeError)?;
+        let remaining_value = Self::dot_to_u128(ext::oracle::btc_to_dots::<T>(remaining_tokens)?)?;
+        let raw_collateral = Self::dot_to_u128(collateral)?;
+        let available_premium = raw_collateral
+            .saturating_sub(value)
+            .saturating_sub(remaining_value);
+        Self::u128_to_dot((value + premium.min(available_premium)).min(raw_collateral))
+    }
+
     fn vault_exists(id: &T::AccountId) -> bool {
         <Vaults<T>>::contains_key(id)
     }
@@ -677,6 +974,14 @@ impl<T: Trait> Module<T> {
         <MinimumCollateralVault<T>>::get()
     }
 
+    fn btc_address_reserved(btc_address: &BtcAddress) -> bool {
+        <ReservedBtcAddresses<T>>::contains_key(btc_address)
+    }
+
+    fn reserve_btc_address(btc_address: &BtcAddress, vault_id: &T::AccountId) {
+        <ReservedBtcAddresses<T>>::insert(btc_address, vault_id);
+    }
+
     // Other helpers
     /// Ensure that the parachain is NOT shutdown and DOES NOT have the given errors
     ///
@@ -758,6 +1063,7 @@ decl_event! {
             DOT = DOT<T>,
             BTCBalance = PolkaBTC<T> {
         RegisterVault(AccountId, DOT),
+        RegisterAddress(AccountId, BtcAddress),
         /// id, new collateral, total collateral, free collateral
         LockAdditionalCollateral(AccountId, DOT, DOT, DOT),
         /// id, withdrawn collateral, total collateral
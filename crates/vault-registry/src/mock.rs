@@ -118,6 +118,7 @@ impl exchange_rate_oracle::Trait for Test {
 
 impl Trait for Test {
     type Event = TestEvent;
+    type OnVaultLiquidated = ();
 }
 
 impl security::Trait for Test {
@@ -156,7 +157,11 @@ impl ExtBuilder {
         .unwrap();
 
         GenesisConfig {
+            redeem_premium_fee: 0,
             secure_collateral_threshold: 100000,
+            failed_redeem_threshold: 0,
+            failed_redeem_window: 0,
+            liquidation_redeem_premium: 0,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
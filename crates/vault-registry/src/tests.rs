@@ -1,4 +1,5 @@
-use frame_support::{assert_err, assert_noop, assert_ok, StorageValue};
+use bitcoin::address::BtcAddress;
+use frame_support::{assert_err, assert_noop, assert_ok, StorageMap, StorageValue};
 use sp_core::H160;
 
 use mocktopus::mocking::*;
@@ -6,7 +7,7 @@ use mocktopus::mocking::*;
 use crate::ext;
 use crate::mock::{
     run_test, Origin, System, Test, TestEvent, VaultRegistry, DEFAULT_COLLATERAL, DEFAULT_ID,
-    RICH_COLLATERAL, RICH_ID,
+    OTHER_ID, RICH_COLLATERAL, RICH_ID,
 };
 use x_core::{Error, UnitResult};
 
@@ -54,11 +55,15 @@ fn create_vault(id: u64) -> <Test as system::Trait>::AccountId {
         .mock_safe(|| MockResult::Return(DEFAULT_COLLATERAL));
     let collateral = DEFAULT_COLLATERAL;
     let origin = Origin::signed(id);
-    let result = VaultRegistry::register_vault(origin, collateral, H160::zero());
+    let result = VaultRegistry::register_vault(origin, collateral, vault_btc_address(id));
     assert_ok!(result);
     id
 }
 
+fn vault_btc_address(id: u64) -> BtcAddress {
+    BtcAddress::P2PKH(H160::from_low_u64_be(id))
+}
+
 fn create_sample_vault() -> <Test as system::Trait>::AccountId {
     create_vault(DEFAULT_ID)
 }
@@ -77,7 +82,8 @@ fn register_vault_fails_when_given_collateral_too_low() {
         VaultRegistry::get_minimum_collateral_vault.mock_safe(|| MockResult::Return(200));
         let id = 3;
         let collateral = 100;
-        let result = VaultRegistry::register_vault(Origin::signed(id), collateral, H160::zero());
+        let result =
+            VaultRegistry::register_vault(Origin::signed(id), collateral, BtcAddress::default());
         assert_err!(result, Error::InsuficientVaultCollateralAmount);
         assert_not_emitted!(Event::RegisterVault(id, collateral));
     });
@@ -87,8 +93,11 @@ fn register_vault_fails_when_given_collateral_too_low() {
 fn register_vault_fails_when_account_funds_too_low() {
     run_test(|| {
         let collateral = DEFAULT_COLLATERAL + 1;
-        let result =
-            VaultRegistry::register_vault(Origin::signed(DEFAULT_ID), collateral, H160::zero());
+        let result = VaultRegistry::register_vault(
+            Origin::signed(DEFAULT_ID),
+            collateral,
+            BtcAddress::default(),
+        );
         assert_err!(result, Error::InsufficientFunds);
         assert_not_emitted!(Event::RegisterVault(DEFAULT_ID, collateral));
     });
@@ -98,13 +107,79 @@ fn register_vault_fails_when_account_funds_too_low() {
 fn register_vault_fails_when_already_registered() {
     run_test(|| {
         let id = create_sample_vault();
-        let result =
-            VaultRegistry::register_vault(Origin::signed(id), DEFAULT_COLLATERAL, H160::zero());
+        let result = VaultRegistry::register_vault(
+            Origin::signed(id),
+            DEFAULT_COLLATERAL,
+            BtcAddress::default(),
+        );
         assert_err!(result, Error::VaultAlreadyRegistered);
         assert_emitted!(Event::RegisterVault(id, DEFAULT_COLLATERAL), 1);
     });
 }
 
+#[test]
+fn register_address_succeeds() -> UnitResult {
+    run_test(|| {
+        let id = create_sample_vault();
+        let btc_address = BtcAddress::P2WPKHv0(H160::from([1; 20]));
+        assert_ok!(VaultRegistry::register_address(
+            Origin::signed(id),
+            btc_address
+        ));
+        let vault = VaultRegistry::_get_vault_from_id(&id)?;
+        assert!(vault.wallet.has_btc_address(&vault_btc_address(id)));
+        assert!(vault.wallet.has_btc_address(&btc_address));
+        assert_emitted!(Event::RegisterAddress(id, btc_address));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn register_address_fails_when_already_registered() {
+    run_test(|| {
+        let id = create_sample_vault();
+        let res = VaultRegistry::register_address(Origin::signed(id), vault_btc_address(id));
+        assert_err!(res, Error::BtcAddressAlreadyRegistered);
+    })
+}
+
+#[test]
+fn register_address_fails_when_registered_by_other_vault() {
+    run_test(|| {
+        let id = create_sample_vault();
+        let other_id = create_vault(OTHER_ID);
+        let res = VaultRegistry::register_address(Origin::signed(id), vault_btc_address(other_id));
+        assert_err!(res, Error::BtcAddressAlreadyRegistered);
+        assert!(!VaultRegistry::_get_vault_from_id(&id)
+            .unwrap()
+            .wallet
+            .has_btc_address(&vault_btc_address(other_id)));
+    })
+}
+
+#[test]
+fn register_vault_fails_when_address_registered_by_other_vault() {
+    run_test(|| {
+        let id = create_sample_vault();
+        let result = VaultRegistry::register_vault(
+            Origin::signed(OTHER_ID),
+            DEFAULT_COLLATERAL,
+            vault_btc_address(id),
+        );
+        assert_err!(result, Error::BtcAddressAlreadyRegistered);
+        assert_not_emitted!(Event::RegisterVault(OTHER_ID, DEFAULT_COLLATERAL));
+    })
+}
+
+#[test]
+fn register_address_fails_when_vault_not_found() {
+    run_test(|| {
+        let res = VaultRegistry::register_address(Origin::signed(3), BtcAddress::default());
+        assert_err!(res, Error::VaultNotFound);
+    })
+}
+
 #[test]
 fn lock_additional_collateral_succeeds() -> UnitResult {
     run_test(|| {
@@ -171,7 +246,7 @@ fn increase_to_be_issued_tokens_succeeds() -> UnitResult {
         set_default_thresholds();
         let res = VaultRegistry::_increase_to_be_issued_tokens(&id, 50);
         let vault = VaultRegistry::_get_vault_from_id(&id)?;
-        assert_ok!(res, vault.btc_address);
+        assert_ok!(res, vault.wallet.get_btc_address());
         assert_eq!(vault.to_be_issued_tokens, 50);
         assert_emitted!(Event::IncreaseToBeIssuedTokens(id, 50));
 
@@ -179,6 +254,31 @@ fn increase_to_be_issued_tokens_succeeds() -> UnitResult {
     })
 }
 
+#[test]
+fn increase_to_be_issued_tokens_rotates_btc_addresses() {
+    run_test(|| {
+        let id = create_sample_vault();
+        set_default_thresholds();
+        let btc_address = BtcAddress::P2WPKHv0(H160::from([1; 20]));
+        assert_ok!(VaultRegistry::register_address(
+            Origin::signed(id),
+            btc_address
+        ));
+        assert_ok!(
+            VaultRegistry::_increase_to_be_issued_tokens(&id, 10),
+            vault_btc_address(id)
+        );
+        assert_ok!(
+            VaultRegistry::_increase_to_be_issued_tokens(&id, 10),
+            btc_address
+        );
+        assert_ok!(
+            VaultRegistry::_increase_to_be_issued_tokens(&id, 10),
+            vault_btc_address(id)
+        );
+    })
+}
+
 #[test]
 fn increase_to_be_issued_tokens_fails_with_insufficient_collateral() -> UnitResult {
     run_test(|| {
@@ -199,7 +299,7 @@ fn decrease_to_be_issued_tokens_succeeds() -> UnitResult {
         set_default_thresholds();
         assert_ok!(
             VaultRegistry::_increase_to_be_issued_tokens(&id, 50),
-            vault.btc_address
+            vault.wallet.get_btc_address()
         );
         let res = VaultRegistry::_decrease_to_be_issued_tokens(&id, 50);
         assert_ok!(res);
@@ -231,7 +331,7 @@ fn issue_tokens_succeeds() -> UnitResult {
         set_default_thresholds();
         assert_ok!(
             VaultRegistry::_increase_to_be_issued_tokens(&id, 50),
-            vault.btc_address
+            vault.wallet.get_btc_address()
         );
         let res = VaultRegistry::_issue_tokens(&id, 50);
         assert_ok!(res);
@@ -266,7 +366,7 @@ fn increase_to_be_redeemed_tokens_succeeds() -> UnitResult {
 
         assert_ok!(
             VaultRegistry::_increase_to_be_issued_tokens(&id, 50),
-            vault.btc_address
+            vault.wallet.get_btc_address()
         );
         assert_ok!(VaultRegistry::_issue_tokens(&id, 50));
         let res = VaultRegistry::_increase_to_be_redeemed_tokens(&id, 50);
@@ -301,7 +401,7 @@ fn decrease_to_be_redeemed_tokens_succeeds() -> UnitResult {
 
         assert_ok!(
             VaultRegistry::_increase_to_be_issued_tokens(&id, 50),
-            vault.btc_address
+            vault.wallet.get_btc_address()
         );
         assert_ok!(VaultRegistry::_issue_tokens(&id, 50));
         assert_ok!(VaultRegistry::_increase_to_be_redeemed_tokens(&id, 50));
@@ -407,6 +507,11 @@ fn redeem_tokens_premium_succeeds() -> UnitResult {
             assert_eq!(sender, &id);
             MockResult::Return(Ok(()))
         });
+        ext::collateral::release::<Test>.mock_safe(move |sender, amount| {
+            assert_eq!(sender, &user_id);
+            assert_eq!(amount, 30);
+            MockResult::Return(Ok(()))
+        });
         VaultRegistry::_increase_to_be_issued_tokens(&id, 50)?;
         assert_ok!(VaultRegistry::_issue_tokens(&id, 50));
         assert_ok!(VaultRegistry::_increase_to_be_redeemed_tokens(&id, 50));
@@ -421,6 +526,16 @@ fn redeem_tokens_premium_succeeds() -> UnitResult {
     })
 }
 
+#[test]
+fn get_redeem_premium_is_share_of_redeemed_value() {
+    run_test(|| {
+        VaultRegistry::_set_redeem_premium_fee(5000); // 5%
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|tokens| MockResult::Return(Ok(tokens * 2)));
+
+        assert_eq!(VaultRegistry::_get_redeem_premium(100), Ok(10));
+    })
+}
+
 #[test]
 fn redeem_tokens_premium_fails_with_insufficient_tokens() -> UnitResult {
     run_test(|| {
@@ -449,6 +564,7 @@ fn redeem_tokens_liquidation_succeeds() -> UnitResult {
             assert_eq!(sender, &id);
             MockResult::Return(Ok(()))
         });
+        ext::collateral::release::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
         ext::security::recover_from_liquidation::<Test>.mock_safe(|| MockResult::Return(Ok(())));
         VaultRegistry::_increase_to_be_issued_tokens(&id, 50)?;
         assert_ok!(VaultRegistry::_issue_tokens(&id, 50));
@@ -473,6 +589,7 @@ fn redeem_tokens_liquidation_does_not_call_recover_when_unnecessary() -> UnitRes
             assert_eq!(sender, &id);
             MockResult::Return(Ok(()))
         });
+        ext::collateral::release::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
 
         ext::security::recover_from_liquidation::<Test>.mock_safe(|| {
             panic!("this should not be called");
@@ -489,6 +606,116 @@ fn redeem_tokens_liquidation_does_not_call_recover_when_unnecessary() -> UnitRes
     })
 }
 
+#[test]
+fn redeem_tokens_liquidation_pays_premium() -> UnitResult {
+    run_test(|| {
+        let id = create_sample_vault();
+        <crate::LiquidationVault<Test>>::put(id);
+        let user_id = 5;
+        set_default_thresholds();
+        VaultRegistry::_set_liquidation_redeem_premium(10_000); // 10%
+        ext::collateral::slash::<Test>.mock_safe(move |sender, receiver, amount| {
+            assert_eq!(sender, &id);
+            assert_eq!(receiver, &user_id);
+            assert_eq!(amount, 22);
+            MockResult::Return(Ok(()))
+        });
+        ext::collateral::release::<Test>.mock_safe(move |account, amount| {
+            assert_eq!(account, &user_id);
+            assert_eq!(amount, 22);
+            MockResult::Return(Ok(()))
+        });
+        VaultRegistry::_increase_to_be_issued_tokens(&id, 50)?;
+        assert_ok!(VaultRegistry::_issue_tokens(&id, 50));
+
+        assert_ok!(VaultRegistry::_redeem_tokens_liquidation(&user_id, 20));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn redeem_tokens_liquidation_caps_premium_at_collateral() -> UnitResult {
+    run_test(|| {
+        let id = create_sample_vault();
+        <crate::LiquidationVault<Test>>::put(id);
+        let user_id = 5;
+        set_default_thresholds();
+        VaultRegistry::_set_liquidation_redeem_premium(10_000); // 10%
+        VaultRegistry::_increase_to_be_issued_tokens(&id, 50)?;
+        assert_ok!(VaultRegistry::_issue_tokens(&id, 50));
+        // 30 DOT back the remaining 30 tokens, leaving 1 DOT for the premium
+        ext::collateral::for_account::<Test>.mock_safe(|_| MockResult::Return(51));
+        ext::collateral::slash::<Test>.mock_safe(|_, _, amount| {
+            assert_eq!(amount, 21);
+            MockResult::Return(Ok(()))
+        });
+        ext::collateral::release::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+
+        assert_ok!(VaultRegistry::_redeem_tokens_liquidation(&user_id, 20));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn redeem_tokens_liquidation_does_not_pay_premium_from_backing_collateral() -> UnitResult {
+    run_test(|| {
+        let id = create_sample_vault();
+        <crate::LiquidationVault<Test>>::put(id);
+        let user_id = RICH_ID;
+        set_default_thresholds();
+        VaultRegistry::_set_liquidation_redeem_premium(10_000); // 10%
+        VaultRegistry::_increase_to_be_issued_tokens(&id, 50)?;
+        assert_ok!(VaultRegistry::_issue_tokens(&id, 50));
+        // the 50 tokens are worth all of the 100 DOT collateral
+        ext::oracle::btc_to_dots::<Test>.mock_safe(|tokens| MockResult::Return(Ok(tokens * 2)));
+        ext::security::recover_from_liquidation::<Test>.mock_safe(|| MockResult::Return(Ok(())));
+
+        assert_ok!(VaultRegistry::_redeem_tokens_liquidation(&user_id, 20));
+        assert_ok!(VaultRegistry::_redeem_tokens_liquidation(&user_id, 20));
+        // the last redeemer is still paid the full value
+        assert_ok!(VaultRegistry::_redeem_tokens_liquidation(&user_id, 10));
+
+        assert_eq!(ext::collateral::for_account::<Test>(&id), 0);
+        assert_eq!(
+            Balances::free_balance(user_id),
+            RICH_COLLATERAL + DEFAULT_COLLATERAL
+        );
+        assert_eq!(VaultRegistry::_get_vault_from_id(&id)?.issued_tokens, 0);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn redeem_tokens_liquidation_recovers_from_liquidation() -> UnitResult {
+    run_test(|| {
+        let id = create_sample_vault();
+        <crate::LiquidationVault<Test>>::put(id);
+        let user_id = 5;
+        set_default_thresholds();
+        ext::collateral::slash::<Test>.mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        ext::collateral::release::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        VaultRegistry::_increase_to_be_issued_tokens(&id, 50)?;
+        assert_ok!(VaultRegistry::_issue_tokens(&id, 50));
+        <security::Module<Test>>::set_parachain_status(security::StatusCode::Error);
+        <security::Module<Test>>::insert_error(security::ErrorCode::Liquidation);
+
+        assert_ok!(VaultRegistry::_redeem_tokens_liquidation(&user_id, 20));
+        assert!(<security::Module<Test>>::_is_parachain_error_liquidation());
+
+        assert_ok!(VaultRegistry::_redeem_tokens_liquidation(&user_id, 30));
+        assert!(!<security::Module<Test>>::_is_parachain_error_liquidation());
+        assert_eq!(
+            <security::Module<Test>>::get_parachain_status(),
+            security::StatusCode::Running
+        );
+
+        Ok(())
+    })
+}
+
 #[test]
 fn redeem_tokens_liquidation_fails_with_insufficient_tokens() -> UnitResult {
     run_test(|| {
@@ -593,6 +820,200 @@ fn liquidate_succeeds() -> UnitResult {
     })
 }
 
+#[test]
+fn liquidate_releases_btc_addresses() {
+    run_test(|| {
+        let id = create_sample_vault();
+        let liquidation_id = create_vault(DEFAULT_ID + 1);
+        <crate::LiquidationVault<Test>>::put(liquidation_id);
+        ext::collateral::slash::<Test>.mock_safe(|_, _, _| MockResult::Return(Ok(())));
+
+        let btc_address = BtcAddress::P2WPKHv0(H160::from([1; 20]));
+        assert_ok!(VaultRegistry::register_address(
+            Origin::signed(id),
+            btc_address
+        ));
+        assert_ok!(VaultRegistry::_liquidate_vault(&id));
+
+        assert!(!VaultRegistry::btc_address_reserved(&vault_btc_address(id)));
+        assert!(!VaultRegistry::btc_address_reserved(&btc_address));
+        assert_ok!(VaultRegistry::register_vault(
+            Origin::signed(OTHER_ID),
+            DEFAULT_COLLATERAL,
+            vault_btc_address(id),
+        ));
+        assert_ok!(VaultRegistry::register_address(
+            Origin::signed(OTHER_ID),
+            btc_address
+        ));
+    })
+}
+
+#[test]
+fn record_failed_redeem_liquidates_vault_after_threshold() -> UnitResult {
+    run_test(|| {
+        let id = create_sample_vault();
+        let liquidation_id = create_vault(DEFAULT_ID + 1);
+        <crate::LiquidationVault<Test>>::put(liquidation_id);
+        VaultRegistry::_set_failed_redeem_threshold(2);
+        VaultRegistry::_set_failed_redeem_window(10);
+        ext::collateral::slash::<Test>.mock_safe(|_, _, _| MockResult::Return(Ok(())));
+
+        assert_ok!(VaultRegistry::_record_failed_redeem(&id, 1));
+        assert_ok!(VaultRegistry::_get_vault_from_id(&id));
+        assert!(!<security::Module<Test>>::_is_parachain_error_liquidation());
+
+        assert_ok!(VaultRegistry::_record_failed_redeem(&id, 10));
+        assert_err!(VaultRegistry::_get_vault_from_id(&id), Error::VaultNotFound);
+        assert_emitted!(Event::LiquidateVault(id));
+        assert!(<security::Module<Test>>::_is_parachain_error_liquidation());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn record_failed_redeem_ignores_failures_outside_window() -> UnitResult {
+    run_test(|| {
+        let id = create_sample_vault();
+        VaultRegistry::_set_failed_redeem_threshold(2);
+        VaultRegistry::_set_failed_redeem_window(10);
+
+        assert_ok!(VaultRegistry::_record_failed_redeem(&id, 1));
+        assert_ok!(VaultRegistry::_record_failed_redeem(&id, 11));
+        assert_ok!(VaultRegistry::_get_vault_from_id(&id));
+        assert_eq!(<crate::FailedRedeems<Test>>::get(id), vec![11]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn record_failed_redeem_never_liquidates_liquidation_vault() -> UnitResult {
+    run_test(|| {
+        let liquidation_id = create_vault(DEFAULT_ID + 1);
+        <crate::LiquidationVault<Test>>::put(liquidation_id);
+        VaultRegistry::_set_failed_redeem_threshold(1);
+
+        assert_ok!(VaultRegistry::_record_failed_redeem(&liquidation_id, 1));
+        assert_ok!(VaultRegistry::_get_vault_from_id(&liquidation_id));
+        assert!(!<security::Module<Test>>::_is_parachain_error_liquidation());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn get_vaults_with_issued_tokens_succeeds() -> UnitResult {
+    run_test(|| {
+        let id = create_sample_vault();
+        let liquidation_id = create_vault(DEFAULT_ID + 1);
+        create_vault(DEFAULT_ID + 2);
+        <crate::LiquidationVault<Test>>::put(liquidation_id);
+        set_default_thresholds();
+
+        VaultRegistry::_increase_to_be_issued_tokens(&id, 50)?;
+        assert_ok!(VaultRegistry::_issue_tokens(&id, 25));
+        VaultRegistry::_increase_to_be_issued_tokens(&liquidation_id, 50)?;
+        assert_ok!(VaultRegistry::_issue_tokens(&liquidation_id, 50));
+
+        assert_eq!(
+            VaultRegistry::_get_vaults_with_issued_tokens(),
+            vec![(id, 25)]
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn get_vault_with_sufficient_collateral_succeeds() {
+    run_test(|| {
+        create_sample_vault();
+        let rich_id = create_vault(RICH_ID);
+        let additional = RICH_COLLATERAL - DEFAULT_COLLATERAL;
+        assert_ok!(VaultRegistry::lock_additional_collateral(
+            Origin::signed(rich_id),
+            additional
+        ));
+        set_default_thresholds();
+        ext::randomness::random_u128::<Test>.mock_safe(|| MockResult::Return(42));
+
+        // only the rich vault can issue more than 50 tokens at 200%
+        assert_eq!(
+            VaultRegistry::_get_vault_with_sufficient_collateral(60),
+            Ok(rich_id)
+        );
+    })
+}
+
+#[test]
+fn get_vault_with_sufficient_collateral_skips_banned_vaults() {
+    run_test(|| {
+        let id = create_sample_vault();
+        let banned_id = create_vault(RICH_ID);
+        set_default_thresholds();
+        assert_ok!(VaultRegistry::_ban_vault(banned_id, 100));
+
+        assert_eq!(
+            VaultRegistry::_get_vault_with_sufficient_collateral(10),
+            Ok(id)
+        );
+    })
+}
+
+#[test]
+fn get_issuable_vaults_examines_at_most_max_vaults() {
+    run_test(|| {
+        ext::collateral::lock::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        for id in 0..(crate::MAX_VAULTS_EXAMINED as u64 + 10) {
+            create_vault(100 + id);
+        }
+
+        assert_eq!(
+            VaultRegistry::get_issuable_vaults().len(),
+            crate::MAX_VAULTS_EXAMINED as usize
+        );
+    })
+}
+
+#[test]
+fn get_vault_with_sufficient_collateral_fails_when_no_vault_qualifies() {
+    run_test(|| {
+        create_sample_vault();
+        set_default_thresholds();
+
+        assert_err!(
+            VaultRegistry::_get_vault_with_sufficient_collateral(51),
+            Error::NoVaultWithSufficientCollateral
+        );
+    })
+}
+
+#[test]
+fn get_vaults_for_split_issue_succeeds() {
+    run_test(|| {
+        let id = create_sample_vault();
+        let rich_id = create_vault(RICH_ID);
+        let additional = RICH_COLLATERAL - DEFAULT_COLLATERAL;
+        assert_ok!(VaultRegistry::lock_additional_collateral(
+            Origin::signed(rich_id),
+            additional
+        ));
+        set_default_thresholds();
+
+        // the rich vault can issue 75 tokens and the default vault 50
+        assert_eq!(
+            VaultRegistry::_get_vaults_for_split_issue(100),
+            Ok(vec![(rich_id, 75), (id, 25)])
+        );
+        assert_err!(
+            VaultRegistry::_get_vaults_for_split_issue(126),
+            Error::NoVaultWithSufficientCollateral
+        );
+    })
+}
+
 #[test]
 fn is_collateral_below_threshold_true_succeeds() {
     run_test(|| {
@@ -638,7 +1059,7 @@ fn _is_vault_below_auction_threshold_false_succeeds() {
         let vault = VaultRegistry::_get_vault_from_id(&id).unwrap();
         assert_ok!(
             VaultRegistry::_increase_to_be_issued_tokens(&id, 50),
-            vault.btc_address
+            vault.wallet.get_btc_address()
         );
         let res = VaultRegistry::_issue_tokens(&id, 50);
         assert_ok!(res);
@@ -664,7 +1085,7 @@ fn register_vault_parachain_not_running_fails() {
             VaultRegistry::register_vault(
                 Origin::signed(DEFAULT_ID),
                 DEFAULT_COLLATERAL,
-                H160::zero()
+                BtcAddress::default()
             ),
             Error::ParachainNotRunning
         );
@@ -698,7 +1119,7 @@ fn _is_vault_below_liquidation_threshold_true_succeeds() {
         println!("{:?}", vault);
         assert_ok!(
             VaultRegistry::_increase_to_be_issued_tokens(&id, 50),
-            vault.btc_address
+            vault.wallet.get_btc_address()
         );
         let res = VaultRegistry::_issue_tokens(&id, 50);
         assert_ok!(res);
@@ -1,8 +1,9 @@
 use frame_support::traits::Currency;
 
+use bitcoin::address::BtcAddress;
 use codec::{Decode, Encode, HasCompact};
 use frame_support::{ensure, StorageMap};
-use sp_core::H160;
+use sp_std::prelude::*;
 
 #[cfg(test)]
 use mocktopus::macros::mockable;
@@ -17,6 +18,68 @@ pub(crate) type DOT<T> =
 pub(crate) type PolkaBTC<T> =
     <<T as treasury::Trait>::PolkaBTC as Currency<<T as system::Trait>::AccountId>>::Balance;
 
+/// Maximum number of Bitcoin addresses a single Vault can register
+pub const MAX_WALLET_ADDRESSES: usize = 32;
+
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Wallet {
+    // Bitcoin addresses of the Vault (P2PKH, P2SH, P2PKH, P2WSH), in the
+    // order in which they were registered
+    pub addresses: Vec<BtcAddress>,
+    // Index of the address assigned to the next issue request
+    pub next_address: u32,
+}
+
+impl Wallet {
+    pub fn new(btc_address: BtcAddress) -> Wallet {
+        Wallet {
+            addresses: vec![btc_address],
+            next_address: 0,
+        }
+    }
+
+    /// Returns true if the address is owned by the Vault
+    pub fn has_btc_address(&self, btc_address: &BtcAddress) -> bool {
+        self.addresses.contains(btc_address)
+    }
+
+    /// Adds a new address to the wallet
+    ///
+    /// # Errors
+    /// * `BtcAddressAlreadyRegistered` - if the address is already part of the wallet
+    /// * `MaxBtcAddressesReached` - if the wallet holds `MAX_WALLET_ADDRESSES` addresses
+    pub fn add_btc_address(&mut self, btc_address: BtcAddress) -> UnitResult {
+        ensure!(
+            !self.has_btc_address(&btc_address),
+            Error::BtcAddressAlreadyRegistered
+        );
+        ensure!(
+            self.addresses.len() < MAX_WALLET_ADDRESSES,
+            Error::MaxBtcAddressesReached
+        );
+        self.addresses.push(btc_address);
+        Ok(())
+    }
+
+    /// Returns the address the Vault registered with
+    pub fn get_btc_address(&self) -> BtcAddress {
+        self.addresses.first().cloned().unwrap_or_default()
+    }
+
+    /// Returns the address assigned to the next issue request and
+    /// advances to the following address in a round-robin fashion
+    pub fn rotate_btc_address(&mut self) -> BtcAddress {
+        let len = self.addresses.len() as u32;
+        if len == 0 {
+            return BtcAddress::default();
+        }
+        let index = self.next_address % len;
+        self.next_address = (index + 1) % len;
+        self.addresses[index as usize]
+    }
+}
+
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct Vault<AccountId, BlockNumber, PolkaBTC: HasCompact> {
@@ -30,8 +93,8 @@ pub struct Vault<AccountId, BlockNumber, PolkaBTC: HasCompact> {
     pub to_be_redeemed_tokens: PolkaBTC,
     // DOT collateral locked by this Vault
     // collateral: DOT,
-    // Bitcoin address of this Vault (P2PKH, P2SH, P2PKH, P2WSH)
-    pub btc_address: H160,
+    // Bitcoin addresses of this Vault
+    pub wallet: Wallet,
     // Block height until which this Vault is banned from being
     // used for Issue, Redeem (except during automatic liquidation) and Replace .
     pub banned_until: Option<BlockNumber>,
@@ -40,10 +103,13 @@ pub struct Vault<AccountId, BlockNumber, PolkaBTC: HasCompact> {
 impl<AccountId, BlockNumber, PolkaBTC: HasCompact + Default>
     Vault<AccountId, BlockNumber, PolkaBTC>
 {
-    pub(crate) fn new(id: AccountId, btc_address: H160) -> Vault<AccountId, BlockNumber, PolkaBTC> {
+    pub(crate) fn new(
+        id: AccountId,
+        btc_address: BtcAddress,
+    ) -> Vault<AccountId, BlockNumber, PolkaBTC> {
         Vault {
             id,
-            btc_address,
+            wallet: Wallet::new(btc_address),
             to_be_issued_tokens: Default::default(),
             issued_tokens: Default::default(),
             to_be_redeemed_tokens: Default::default(),
@@ -61,7 +127,7 @@ pub(crate) struct RichVault<T: Trait> {
 
 #[cfg_attr(test, mockable)]
 impl<T: Trait> RichVault<T> {
-    pub fn new(id: T::AccountId, btc_address: H160) -> RichVault<T> {
+    pub fn new(id: T::AccountId, btc_address: BtcAddress) -> RichVault<T> {
         let vault = Vault::new(id, btc_address);
         RichVault { data: vault }
     }
@@ -205,6 +271,10 @@ impl<T: Trait> RichVault<T> {
         liquidation_vault.force_increase_to_be_issued(self.data.to_be_issued_tokens);
         liquidation_vault.force_increase_to_be_redeemed(self.data.to_be_redeemed_tokens);
         <crate::Vaults<T>>::remove(&self.id());
+        // the addresses of a removed vault can be registered again
+        for btc_address in self.data.wallet.addresses.iter() {
+            <crate::ReservedBtcAddresses<T>>::remove(btc_address);
+        }
         Ok(())
     }
 
@@ -221,6 +291,19 @@ impl<T: Trait> RichVault<T> {
         }
     }
 
+    pub fn add_btc_address(&mut self, btc_address: BtcAddress) -> UnitResult {
+        let mut wallet = self.data.wallet.clone();
+        wallet.add_btc_address(btc_address)?;
+        Ok(self.update(|v| v.wallet = wallet.clone()))
+    }
+
+    pub fn rotate_btc_address(&mut self) -> BtcAddress {
+        let mut wallet = self.data.wallet.clone();
+        let btc_address = wallet.rotate_btc_address();
+        self.update(|v| v.wallet = wallet.clone());
+        btc_address
+    }
+
     pub fn ban_until(&mut self, height: T::BlockNumber) {
         self.update(|v| v.banned_until = Some(height));
     }
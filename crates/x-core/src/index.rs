@@ -0,0 +1,61 @@
+//! Storage maps indexing the ids of the open requests of each account, as
+//! used by the issue, redeem and replace pallets.
+
+use crate::{Error, UnitResult};
+use codec::{FullCodec, FullEncode};
+use frame_support::{ensure, storage::StorageMap};
+use sp_std::prelude::*;
+
+/// The maximum number of open requests of a single requester. The requests
+/// of vaults are not capped, since any account can open requests with them.
+pub const MAX_OPEN_REQUESTS: usize = 100;
+
+/// Checks that `count` more requests can be indexed for the account
+///
+/// # Errors
+///
+/// * `MaxOpenRequestsReached` - if the account would exceed `MAX_OPEN_REQUESTS`
+pub fn ensure_capacity<S, K, V>(account_id: &K, count: usize) -> UnitResult
+where
+    K: FullEncode,
+    V: FullCodec,
+    S: StorageMap<K, Vec<V>, Query = Vec<V>>,
+{
+    ensure!(
+        S::get(account_id).len() + count <= MAX_OPEN_REQUESTS,
+        Error::MaxOpenRequestsReached
+    );
+    Ok(())
+}
+
+/// Adds the request id to the index of the account, unless it is already
+/// indexed. The capacity of a requester must have been checked with
+/// `ensure_capacity`.
+pub fn insert<S, K, V>(account_id: &K, id: V)
+where
+    K: FullEncode,
+    V: FullCodec + PartialEq,
+    S: StorageMap<K, Vec<V>, Query = Vec<V>>,
+{
+    S::mutate(account_id, |ids| {
+        if !ids.contains(&id) {
+            ids.push(id)
+        }
+    });
+}
+
+/// Removes the request id from the index of the account
+pub fn remove<S, K, V>(account_id: &K, id: V)
+where
+    K: FullEncode,
+    V: FullCodec + PartialEq,
+    S: StorageMap<K, Vec<V>, Query = Vec<V>>,
+{
+    let mut ids = S::get(account_id);
+    ids.retain(|other| *other != id);
+    if ids.is_empty() {
+        S::remove(account_id);
+    } else {
+        S::insert(account_id, ids);
+    }
+}
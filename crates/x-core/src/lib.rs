@@ -2,10 +2,13 @@
 #![deny(warnings)]
 
 use codec::alloc::string::{String, ToString};
+use codec::{Decode, Encode};
 use frame_support::dispatch::DispatchError;
 use sp_std::prelude::*;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+pub mod index;
+
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq)]
 pub enum Error {
     // ----------
     // BTC Errors
@@ -63,6 +66,32 @@ pub enum Error {
     UnsupportedInputFormat,
     /// There are no NO_DATA blocks in this BlockChain
     NoDataEmpty, // not in spec
+    /// Batch of block headers is empty, too long or not contiguous
+    InvalidHeaderBatch, // not in spec
+    /// Block timestamp is not greater than the median time of the previous blocks
+    TimestampBelowMedianTimePast, // not in spec
+    /// Block timestamp is too far ahead of the parachain time
+    TimestampTooFarInFuture, // not in spec
+    /// Block version is below the minimum required at the block height
+    ObsoleteBlockVersion, // not in spec
+    /// Address string contains a character outside of the Base58 alphabet
+    InvalidBase58Character, // not in spec
+    /// Checksum of the Base58Check encoded address does not match
+    InvalidBase58Checksum, // not in spec
+    /// Address string contains a character outside of the Bech32 alphabet
+    InvalidBech32Character, // not in spec
+    /// Checksum of the Bech32 or Bech32m encoded address does not match
+    InvalidBech32Checksum, // not in spec
+    /// Address string has an invalid length, mixed case or a missing separator
+    MalformedAddress, // not in spec
+    /// Witness version or program of a SegWit address is invalid
+    InvalidWitnessProgram, // not in spec
+    /// Address prefix or human-readable part does not belong to the network
+    AddressNetworkMismatch, // not in spec
+    /// Output index or path of a batch payment commitment is invalid
+    InvalidBatchCommitment, // not in spec
+    /// Several outputs of a transaction match the payment or the OP_RETURN
+    AmbiguousTransactionOutputs, // not in spec
     // -------------
     // XClaim Errors
     // -------------
@@ -102,6 +131,17 @@ pub enum Error {
     RedeemIdNotFound,
     RedeemPeriodExpired,
     RedeemPeriodNotExpired,
+    BtcAddressAlreadyRegistered,
+    MaxBtcAddressesReached,
+    InsufficientFeeRewards,
+    InvalidFee,
+    NoVaultWithSufficientCollateral,
+    RefundIdNotFound,
+    RefundAddressNotSet,
+    RefundAddressAlreadySet,
+    RefundPeriodExpired,
+    RefundPeriodNotExpired,
+    MaxOpenRequestsReached,
 
     /// Parachain Status Errors (Security module)
     ParachainNotRunning,
@@ -159,6 +199,23 @@ impl Error {
             Error::UnsupportedOutputFormat => "Unsupported output format. Currently supported: Witness, P2PKH, P2SH,",
             Error::UnsupportedInputFormat => "Unsupported input format. Currently supported: Witness, P2PKH, P2SH",
             Error::NoDataEmpty => "There are no NO_DATA blocks in this BlockChain.",
+            Error::InvalidHeaderBatch => "Block header batch is empty, too long or not contiguous",
+            Error::TimestampBelowMedianTimePast => "Block timestamp is not greater than the median time of the previous 11 blocks",
+            Error::TimestampTooFarInFuture => "Block timestamp is too far ahead of the parachain time",
+            Error::ObsoleteBlockVersion => "Block version is below the minimum required at this height",
+            Error::InvalidBase58Character => "Invalid character in Base58 string",
+            Error::InvalidBase58Checksum => "Invalid Base58Check checksum",
+            Error::InvalidBech32Character => "Invalid character in Bech32 string",
+            Error::InvalidBech32Checksum => "Invalid Bech32 or Bech32m checksum",
+            Error::MalformedAddress => "Format of the Bitcoin address is invalid",
+            Error::InvalidWitnessProgram => "Invalid witness version or program",
+            Error::AddressNetworkMismatch => "Bitcoin address does not belong to the network",
+            Error::InvalidBatchCommitment => {
+                "Output index or path of the batch payment commitment is invalid"
+            }
+            Error::AmbiguousTransactionOutputs => {
+                "Several transaction outputs match the payment or the OP_RETURN"
+            }
 
             Error::ReplacePeriodExpired => "Replace period expired",
             Error::ReplacePeriodNotExpired => "Replace period not expired",
@@ -199,6 +256,17 @@ impl Error {
             Error::RedeemPeriodExpired => "The redeem period expired.",
             Error::UnauthorizedVault => "Unauthorized: Caller must be associated vault.",
             Error::RedeemPeriodNotExpired => "The period to complete the redeem request is not yet expired.",
+            Error::BtcAddressAlreadyRegistered => "The BTC address is already registered by a vault",
+            Error::MaxBtcAddressesReached => "The vault has registered the maximum number of BTC addresses",
+            Error::InsufficientFeeRewards => "The withdrawn amount exceeds the fee rewards of this vault",
+            Error::InvalidFee => "The fee must not exceed 100%",
+            Error::NoVaultWithSufficientCollateral => "No vault has locked enough collateral to issue the requested amount",
+            Error::RefundIdNotFound => "Requested refund id not found",
+            Error::RefundAddressNotSet => "The requester has not yet set a BTC address for the refund",
+            Error::RefundAddressAlreadySet => "The requester has already set a BTC address for the refund",
+            Error::RefundPeriodExpired => "The refund period expired.",
+            Error::RefundPeriodNotExpired => "The period to complete the refund request is not yet expired.",
+            Error::MaxOpenRequestsReached => "The account has reached the maximum number of open requests",
 
 
             Error::ParachainNotRunning => "Function disabled. Reason: the Parachain status is not 'RUNNING'.",
@@ -30,6 +30,7 @@ use sp_version::RuntimeVersion;
 // A few exports that help ease life for downstream crates.
 pub use balances::Call as BalancesCall;
 pub use btc_relay::bitcoin;
+use btc_relay::bitcoin::types::{BlockChain, H256Le, RichBlockHeader};
 pub use btc_relay::Call as RelayCall;
 pub use frame_support::{
     construct_runtime, parameter_types,
@@ -100,7 +101,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("btc-parachain"),
     impl_name: create_runtime_str!("btc-parachain"),
     authoring_version: 1,
-    spec_version: 1,
+    spec_version: 3,
     impl_version: 1,
     transaction_version: 1,
     apis: RUNTIME_API_VERSIONS,
@@ -293,23 +294,39 @@ impl staked_relayers::Trait for Runtime {
 
 impl vault_registry::Trait for Runtime {
     type Event = Event;
+    type OnVaultLiquidated = (Issue, Redeem);
 }
 
 impl exchange_rate_oracle::Trait for Runtime {
     type Event = Event;
 }
 
+parameter_types! {
+    pub const EpochPeriod: BlockNumber = 100;
+}
+
+impl fee::Trait for Runtime {
+    type Event = Event;
+    type EpochPeriod = EpochPeriod;
+}
+
 parameter_types! {
     pub const IssuePeriod: BlockNumber = 10;
+    pub const RefundPeriod: BlockNumber = 10;
+    pub const MaxExpiredIssuesPerBlock: u32 = 50;
+    pub const MaxExpiredRedeemsPerBlock: u32 = 50;
 }
 
 impl issue::Trait for Runtime {
     type Event = Event;
     type IssuePeriod = IssuePeriod;
+    type RefundPeriod = RefundPeriod;
+    type MaxExpiredIssuesPerBlock = MaxExpiredIssuesPerBlock;
 }
 
 impl redeem::Trait for Runtime {
     type Event = Event;
+    type MaxExpiredRedeemsPerBlock = MaxExpiredRedeemsPerBlock;
 }
 
 impl replace::Trait for Runtime {
@@ -339,9 +356,10 @@ construct_runtime!(
         StakedRelayers: staked_relayers::{Module, Call, Config<T>, Storage, Event<T>},
         VaultRegistry: vault_registry::{Module, Call, Config, Storage, Event<T>},
         ExchangeRateOracle: exchange_rate_oracle::{Module, Call, Config<T>, Storage, Event<T>},
-        Issue: issue::{Module, Call, Storage, Event<T>},
-        Redeem: redeem::{Module, Call, Storage, Event<T>},
-        Replace: replace::{Module, Call, Storage, Event<T>},
+        Fee: fee::{Module, Call, Config, Storage, Event<T>},
+        Issue: issue::{Module, Call, Config, Storage, Event<T>},
+        Redeem: redeem::{Module, Call, Config<T>, Storage, Event<T>},
+        Replace: replace::{Module, Call, Config, Storage, Event<T>},
     }
 );
 
@@ -460,4 +478,90 @@ impl_runtime_apis! {
             Grandpa::grandpa_authorities()
         }
     }
+
+    impl btc_relay_rpc_runtime_api::BtcRelayApi<Block> for Runtime {
+        fn get_best_block() -> H256Le {
+            BTCRelay::get_best_block()
+        }
+
+        fn get_best_block_height() -> u32 {
+            BTCRelay::get_best_block_height()
+        }
+
+        fn get_block_header(block_hash: H256Le) -> Option<RichBlockHeader> {
+            BTCRelay::get_block_header_from_hash(block_hash).ok()
+        }
+
+        fn get_block_header_at_height(block_height: u32) -> Option<RichBlockHeader> {
+            BTCRelay::get_main_chain_block_header(block_height).ok()
+        }
+
+        fn get_forks() -> Result<Vec<BlockChain>, x_core::Error> {
+            BTCRelay::get_chains()
+        }
+
+        fn get_confirmations(block_height: u32) -> Option<u32> {
+            BTCRelay::get_block_confirmations(block_height).ok()
+        }
+    }
+
+    impl issue_rpc_runtime_api::IssueApi<Block, AccountId> for Runtime {
+        fn verify_and_validate_transaction(
+            issue_id: Hash,
+            tx_id: H256Le,
+            tx_block_height: u32,
+            merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+        ) -> Result<(), x_core::Error> {
+            Issue::_verify_and_validate_transaction(issue_id, tx_id, tx_block_height, merkle_proof, raw_tx)
+        }
+
+        fn get_issue_requests(account_id: AccountId) -> Vec<Hash> {
+            Issue::get_issue_requests_for_requester(account_id)
+        }
+
+        fn get_vault_issue_requests(vault_id: AccountId) -> Vec<Hash> {
+            Issue::get_issue_requests_for_vault(vault_id)
+        }
+    }
+
+    impl redeem_rpc_runtime_api::RedeemApi<Block, AccountId> for Runtime {
+        fn verify_and_validate_transaction(
+            redeem_id: Hash,
+            tx_id: H256Le,
+            tx_block_height: u32,
+            merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+        ) -> Result<(), x_core::Error> {
+            Redeem::_verify_and_validate_transaction(redeem_id, tx_id, tx_block_height, merkle_proof, raw_tx)
+        }
+
+        fn get_redeem_requests(account_id: AccountId) -> Vec<Hash> {
+            Redeem::get_redeem_requests_for_redeemer(account_id)
+        }
+
+        fn get_vault_redeem_requests(vault_id: AccountId) -> Vec<Hash> {
+            Redeem::get_redeem_requests_for_vault(vault_id)
+        }
+    }
+
+    impl replace_rpc_runtime_api::ReplaceApi<Block, AccountId> for Runtime {
+        fn verify_and_validate_transaction(
+            replace_id: Hash,
+            tx_id: H256Le,
+            tx_block_height: u32,
+            merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+        ) -> Result<(), x_core::Error> {
+            Replace::_verify_and_validate_transaction(replace_id, tx_id, tx_block_height, merkle_proof, raw_tx)
+        }
+
+        fn get_old_vault_replace_requests(vault_id: AccountId) -> Vec<Hash> {
+            Replace::get_replace_requests_for_old_vault(vault_id)
+        }
+
+        fn get_new_vault_replace_requests(vault_id: AccountId) -> Vec<Hash> {
+            Replace::get_replace_requests_for_new_vault(vault_id)
+        }
+    }
 }
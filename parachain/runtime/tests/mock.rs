@@ -1,6 +1,8 @@
 extern crate hex;
 
+pub use bitcoin::address::BtcAddress;
 pub use bitcoin::formatter::Formattable;
+pub use bitcoin::network::Network;
 pub use bitcoin::types::*;
 pub use btc_parachain_runtime::{AccountId, Event, Runtime};
 pub use frame_support::{assert_err, assert_ok};
@@ -15,7 +17,11 @@ pub use x_core::Error;
 pub const ALICE: [u8; 32] = [0u8; 32];
 pub const BOB: [u8; 32] = [1u8; 32];
 pub const CLAIRE: [u8; 32] = [2u8; 32];
+#[allow(dead_code)]
+pub const DAVE: [u8; 32] = [3u8; 32];
 pub const CONFIRMATIONS: u32 = 6;
+/// Parachain time in milliseconds (2020-09-13)
+pub const CURRENT_TIME: u64 = 1_600_000_000_000;
 
 pub type BTCRelayCall = btc_relay::Call<Runtime>;
 pub type BTCRelayEvent = btc_relay::Event;
@@ -47,7 +53,7 @@ pub fn force_issue_tokens(
     vault: [u8; 32],
     collateral: u128,
     tokens: u128,
-    btc_address: H160,
+    btc_address: BtcAddress,
 ) {
     // register the vault
     assert_ok!(VaultRegistryCall::register_vault(collateral, btc_address)
@@ -77,11 +83,12 @@ pub fn assert_store_main_chain_header_event(height: u32, hash: H256Le) {
 
 #[allow(dead_code)]
 pub fn generate_transaction_and_mine(
-    dest_address: H160,
+    dest_address: BtcAddress,
     amount: u128,
     return_data: H256,
 ) -> (H256Le, u32, Vec<u8>, Vec<u8>) {
-    let address = Address::from(*dest_address.as_fixed_bytes());
+    // coinbase rewards are paid to an arbitrary address
+    let address = Address::from([0; 20]);
 
     let mut height = 1;
     let confirmations = 6;
@@ -114,7 +121,7 @@ pub fn generate_transaction_and_mine(
                 .with_previous_hash(init_block.transactions[0].hash())
                 .build(),
         )
-        .add_output(TransactionOutput::p2pkh(value.into(), &address))
+        .add_output(TransactionOutput::payment(value.into(), &dest_address))
         .add_output(TransactionOutput::op_return(0, return_data.as_bytes()))
         .build();
 
@@ -173,6 +180,8 @@ pub fn generate_transaction_and_mine(
 pub type SecurityModule = security::Module<Runtime>;
 #[allow(dead_code)]
 pub type SystemModule = system::Module<Runtime>;
+#[allow(dead_code)]
+pub type TimestampModule = timestamp::Module<Runtime>;
 
 #[allow(dead_code)]
 pub type VaultRegistryCall = vault_registry::Call<Runtime>;
@@ -192,6 +201,7 @@ impl ExtBuilder {
                 (account_of(ALICE), 1_000_000),
                 (account_of(BOB), 1_000_000),
                 (account_of(CLAIRE), 1_000_000),
+                (account_of(DAVE), 1_000_000),
             ],
         }
         .assimilate_storage(&mut storage)
@@ -209,16 +219,30 @@ impl ExtBuilder {
 
         btc_relay::GenesisConfig {
             confirmations: CONFIRMATIONS,
+            bitcoin_network: Network::Mainnet,
+            pruning_depth: 0,
+            max_pruned_per_block: 0,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
 
         vault_registry::GenesisConfig {
+            redeem_premium_fee: 0,
             secure_collateral_threshold: 100000,
+            failed_redeem_threshold: 0,
+            failed_redeem_window: 0,
+            liquidation_redeem_premium: 0,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
 
-        sp_io::TestExternalities::from(storage)
+        redeem::GenesisConfig::<Runtime> { redeem_period: 10 }
+            .assimilate_storage(&mut storage)
+            .unwrap();
+
+        let mut ext = sp_io::TestExternalities::from(storage);
+        // relayed block headers must not be ahead of the parachain time
+        ext.execute_with(|| TimestampModule::set_timestamp(CURRENT_TIME));
+        ext
     }
 }
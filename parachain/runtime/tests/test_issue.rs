@@ -49,11 +49,11 @@ fn integration_test_issue_polka_btc() {
     ExtBuilder::build().execute_with(|| {
         SystemModule::set_block_number(1);
 
-        let address = H160::from_slice(
+        let address = BtcAddress::P2PKH(H160::from_slice(
             hex::decode("66c7060feb882664ae62ffad0051fe843e318e85")
                 .unwrap()
                 .as_slice(),
-        );
+        ));
         let amount = 100000;
         let collateral = 100;
 
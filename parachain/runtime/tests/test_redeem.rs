@@ -2,10 +2,13 @@ mod mock;
 
 use mock::*;
 
+use frame_support::traits::OnInitialize;
 use primitive_types::H256;
+use security::ErrorCode;
 
 type RedeemCall = redeem::Call<Runtime>;
 type RedeemEvent = redeem::Event<Runtime>;
+type RedeemModule = redeem::Module<Runtime>;
 
 // asserts redeem event happen and extracts its id for further testing
 fn assert_redeem_request_event() -> H256 {
@@ -27,7 +30,7 @@ fn integration_test_redeem_should_fail_if_not_running() {
         SecurityModule::set_parachain_status(StatusCode::Shutdown);
 
         assert_err!(
-            RedeemCall::request_redeem(1000, H160([0; 20]), account_of(BOB))
+            RedeemCall::request_redeem(1000, BtcAddress::default(), account_of(BOB))
                 .dispatch(origin_of(account_of(ALICE))),
             Error::ParachainNotRunning,
         );
@@ -41,8 +44,9 @@ fn integration_test_redeem_polka_btc() {
         let vault = BOB;
         let collateral_vault = 10_000;
         let polkabtc = 1_000;
-        let vault_btc_address = H160([0u8; 20]);
-        let user_btc_address = H160([0u8; 20]);
+        let vault_btc_address = BtcAddress::default();
+        // redeem to a native SegWit v1 (taproot) address
+        let user_btc_address = BtcAddress::P2TR(H256::from([1u8; 32]));
 
         SystemModule::set_block_number(1);
 
@@ -78,3 +82,311 @@ fn integration_test_redeem_polka_btc() {
         .dispatch(origin_of(account_of(vault))));
     });
 }
+
+#[test]
+fn integration_test_redeem_polka_btc_in_last_block_of_redeem_period() {
+    ExtBuilder::build().execute_with(|| {
+        let user = ALICE;
+        let vault = BOB;
+        let collateral_vault = 10_000;
+        let polkabtc = 1_000;
+        let user_btc_address = BtcAddress::P2TR(H256::from([1u8; 32]));
+
+        SystemModule::set_block_number(1);
+
+        assert_ok!(OracleCall::set_exchange_rate(1).dispatch(origin_of(account_of(BOB))));
+
+        set_default_thresholds();
+
+        force_issue_tokens(
+            user,
+            vault,
+            collateral_vault,
+            polkabtc,
+            BtcAddress::default(),
+        );
+
+        assert_ok!(
+            RedeemCall::request_redeem(polkabtc, user_btc_address, account_of(vault))
+                .dispatch(origin_of(account_of(user)))
+        );
+        let redeem_id = assert_redeem_request_event();
+
+        // the blocks up to the end of the redeem period of 10 blocks are
+        // initialized before the vault executes the request
+        for height in 2..=11 {
+            SystemModule::set_block_number(height);
+            RedeemModule::on_initialize(height);
+        }
+
+        let (tx_id, tx_block_height, merkle_proof, raw_tx) =
+            generate_transaction_and_mine(user_btc_address, polkabtc, redeem_id);
+
+        assert_ok!(RedeemCall::execute_redeem(
+            redeem_id,
+            tx_id,
+            tx_block_height,
+            merkle_proof,
+            raw_tx
+        )
+        .dispatch(origin_of(account_of(vault))));
+        assert_eq!(
+            treasury::Module::<Runtime>::get_balance_from_account(account_of(user)),
+            0
+        );
+    });
+}
+
+#[test]
+fn integration_test_redeem_polka_btc_pays_premium() {
+    ExtBuilder::build().execute_with(|| {
+        let user = ALICE;
+        let vault = BOB;
+        let collateral_vault = 10_000;
+        let polkabtc = 1_000;
+        let user_btc_address = BtcAddress::P2TR(H256::from([1u8; 32]));
+
+        SystemModule::set_block_number(1);
+
+        assert_ok!(OracleCall::set_exchange_rate(1).dispatch(origin_of(account_of(BOB))));
+
+        set_default_thresholds();
+        vault_registry::Module::<Runtime>::_set_redeem_premium_fee(5000); // 5%
+
+        force_issue_tokens(
+            user,
+            vault,
+            collateral_vault,
+            polkabtc,
+            BtcAddress::default(),
+        );
+
+        // the vault's collateral rate drops to 111%, below the premium
+        // threshold of 120% but above the liquidation threshold of 110%
+        assert_ok!(OracleCall::set_exchange_rate(9).dispatch(origin_of(account_of(BOB))));
+
+        let initial_dot_balance =
+            collateral::Module::<Runtime>::get_balance_from_account(&account_of(user));
+
+        assert_ok!(
+            RedeemCall::request_redeem(polkabtc, user_btc_address, account_of(vault))
+                .dispatch(origin_of(account_of(user)))
+        );
+        let redeem_id = assert_redeem_request_event();
+
+        SystemModule::set_block_number(5);
+
+        let (tx_id, tx_block_height, merkle_proof, raw_tx) =
+            generate_transaction_and_mine(user_btc_address, polkabtc, redeem_id);
+
+        assert_ok!(RedeemCall::execute_redeem(
+            redeem_id,
+            tx_id,
+            tx_block_height,
+            merkle_proof,
+            raw_tx
+        )
+        .dispatch(origin_of(account_of(vault))));
+
+        // 5% of the 9_000 DOT the redeemed PolkaBTC are worth
+        let premium = 450;
+        let execute_event = Event::redeem(RedeemEvent::ExecuteRedeem(
+            redeem_id,
+            account_of(user),
+            account_of(vault),
+            premium,
+        ));
+        assert!(SystemModule::events()
+            .iter()
+            .any(|r| r.event == execute_event));
+
+        assert_eq!(
+            collateral::Module::<Runtime>::get_balance_from_account(&account_of(user)),
+            initial_dot_balance + premium
+        );
+        assert_eq!(
+            collateral::Module::<Runtime>::get_collateral_from_account(&account_of(vault)),
+            collateral_vault - premium
+        );
+        assert_eq!(
+            treasury::Module::<Runtime>::get_balance_from_account(account_of(user)),
+            0
+        );
+    });
+}
+
+#[test]
+fn integration_test_redeem_polka_btc_liquidation() {
+    ExtBuilder::build().execute_with(|| {
+        let user = ALICE;
+        let vault = BOB;
+        let liquidation_vault = CLAIRE;
+        let collateral_vault = 10_000;
+        let polkabtc = 1_000;
+
+        SystemModule::set_block_number(1);
+
+        assert_ok!(OracleCall::set_exchange_rate(1).dispatch(origin_of(account_of(BOB))));
+
+        set_default_thresholds();
+
+        assert_ok!(
+            VaultRegistryCall::register_vault(0, BtcAddress::P2PKH(H160::from([1u8; 20])))
+                .dispatch(origin_of(account_of(liquidation_vault)))
+        );
+        vault_registry::Module::<Runtime>::_set_liquidation_vault(&account_of(liquidation_vault));
+
+        force_issue_tokens(
+            user,
+            vault,
+            collateral_vault,
+            polkabtc,
+            BtcAddress::default(),
+        );
+
+        // the vault is liquidated and its collateral moves to the liquidation vault
+        assert_ok!(vault_registry::Module::<Runtime>::_liquidate_vault(
+            &account_of(vault)
+        ));
+        SecurityModule::set_parachain_status(StatusCode::Error);
+        SecurityModule::insert_error(ErrorCode::Liquidation);
+
+        let initial_dot_balance =
+            collateral::Module::<Runtime>::get_balance_from_account(&account_of(user));
+        let initial_collateral =
+            collateral::Module::<Runtime>::get_collateral_from_account(&account_of(user));
+
+        assert_ok!(RedeemCall::redeem_liquidation(polkabtc).dispatch(origin_of(account_of(user))));
+
+        // the user receives the value of the PolkaBTC as free DOT at an
+        // exchange rate of 1
+        assert_eq!(
+            collateral::Module::<Runtime>::get_balance_from_account(&account_of(user)),
+            initial_dot_balance + polkabtc
+        );
+        assert_eq!(
+            collateral::Module::<Runtime>::get_collateral_from_account(&account_of(user)),
+            initial_collateral
+        );
+        assert_eq!(
+            treasury::Module::<Runtime>::get_balance_from_account(account_of(user)),
+            0
+        );
+
+        // all tokens of the liquidation vault are redeemed, so the parachain recovers
+        assert!(!SecurityModule::_is_parachain_error_liquidation());
+        assert_eq!(SecurityModule::get_parachain_status(), StatusCode::Running);
+    });
+}
+
+#[test]
+fn integration_test_request_redeem_polka_btc_liquidation() {
+    ExtBuilder::build().execute_with(|| {
+        let user = ALICE;
+        let liquidated_vault = BOB;
+        let liquidation_vault = CLAIRE;
+        let vault = DAVE;
+        let collateral_vault = 10_000;
+        let polkabtc = 1_000;
+        let user_btc_address = BtcAddress::P2PKH(H160::from([2u8; 20]));
+
+        SystemModule::set_block_number(1);
+
+        assert_ok!(OracleCall::set_exchange_rate(1).dispatch(origin_of(account_of(BOB))));
+
+        set_default_thresholds();
+
+        assert_ok!(
+            VaultRegistryCall::register_vault(0, BtcAddress::P2PKH(H160::from([1u8; 20])))
+                .dispatch(origin_of(account_of(liquidation_vault)))
+        );
+        vault_registry::Module::<Runtime>::_set_liquidation_vault(&account_of(liquidation_vault));
+
+        force_issue_tokens(
+            user,
+            liquidated_vault,
+            collateral_vault,
+            polkabtc,
+            BtcAddress::default(),
+        );
+        force_issue_tokens(
+            user,
+            vault,
+            collateral_vault,
+            polkabtc,
+            BtcAddress::P2PKH(H160::from([3u8; 20])),
+        );
+
+        // half of the PolkaBTC is now issued by the liquidation vault
+        assert_ok!(vault_registry::Module::<Runtime>::_liquidate_vault(
+            &account_of(liquidated_vault)
+        ));
+        SecurityModule::set_parachain_status(StatusCode::Error);
+        SecurityModule::insert_error(ErrorCode::Liquidation);
+
+        let initial_dot_balance =
+            collateral::Module::<Runtime>::get_balance_from_account(&account_of(user));
+
+        assert_ok!(
+            RedeemCall::request_redeem(polkabtc, user_btc_address, account_of(vault))
+                .dispatch(origin_of(account_of(user)))
+        );
+        let redeem_id = assert_redeem_request_event();
+
+        // half of the redeemed PolkaBTC is paid out right away as free DOT at
+        // an exchange rate of 1, the other half is paid in BTC by the vault
+        let amount_dot = polkabtc / 2;
+        let amount_btc = polkabtc - amount_dot;
+        assert_eq!(
+            collateral::Module::<Runtime>::get_balance_from_account(&account_of(user)),
+            initial_dot_balance + amount_dot
+        );
+        assert_eq!(
+            treasury::Module::<Runtime>::get_locked_balance_from_account(account_of(user)),
+            amount_btc
+        );
+        assert_eq!(
+            RedeemModule::get_redeem_request_from_id(&redeem_id)
+                .unwrap()
+                .amount_btc,
+            amount_btc
+        );
+
+        // the remaining tokens of the liquidation vault are redeemed, so the
+        // parachain recovers and the vault can execute the redeem
+        assert_ok!(RedeemCall::redeem_liquidation(polkabtc - amount_dot)
+            .dispatch(origin_of(account_of(user))));
+        assert_eq!(SecurityModule::get_parachain_status(), StatusCode::Running);
+
+        SystemModule::set_block_number(5);
+
+        let (tx_id, tx_block_height, merkle_proof, raw_tx) =
+            generate_transaction_and_mine(user_btc_address, amount_btc, redeem_id);
+
+        assert_ok!(RedeemCall::execute_redeem(
+            redeem_id,
+            tx_id,
+            tx_block_height,
+            merkle_proof,
+            raw_tx
+        )
+        .dispatch(origin_of(account_of(vault))));
+
+        assert_eq!(
+            collateral::Module::<Runtime>::get_balance_from_account(&account_of(user)),
+            initial_dot_balance + polkabtc
+        );
+        assert_eq!(
+            treasury::Module::<Runtime>::get_balance_from_account(account_of(user)),
+            polkabtc - amount_btc
+        );
+        assert_eq!(
+            treasury::Module::<Runtime>::get_locked_balance_from_account(account_of(user)),
+            0
+        );
+        let vault =
+            vault_registry::Module::<Runtime>::_get_vault_from_id(&account_of(vault)).unwrap();
+        assert_eq!(vault.issued_tokens, polkabtc - amount_btc);
+        assert_eq!(vault.to_be_redeemed_tokens, 0);
+    });
+}
@@ -43,8 +43,10 @@ fn integration_test_replace_request_replace() {
         // peg spot rate
         assert_ok!(OracleCall::set_exchange_rate(1).dispatch(origin_of(account_of(BOB))));
         // bob creates a vault
-        assert_ok!(VaultRegistryCall::register_vault(amount, H160([0; 20]))
-            .dispatch(origin_of(account_of(BOB))));
+        assert_ok!(
+            VaultRegistryCall::register_vault(amount, BtcAddress::default())
+                .dispatch(origin_of(account_of(BOB)))
+        );
         // bob requests a replace
         assert_ok!(ReplaceCall::request_replace(amount, griefing_collateral)
             .dispatch(origin_of(account_of(BOB))));
@@ -64,8 +66,10 @@ fn integration_test_replace_withdraw_replace() {
         // peg spot rate
         assert_ok!(OracleCall::set_exchange_rate(1).dispatch(origin_of(account_of(BOB))));
         // bob creates a vault
-        assert_ok!(VaultRegistryCall::register_vault(collateral, H160([0; 20]))
-            .dispatch(origin_of(account_of(BOB))));
+        assert_ok!(
+            VaultRegistryCall::register_vault(collateral, BtcAddress::default())
+                .dispatch(origin_of(account_of(BOB)))
+        );
         // bob requests a replace
         assert_ok!(ReplaceCall::request_replace(5000, griefing_collateral)
             .dispatch(origin_of(account_of(BOB))));
@@ -86,11 +90,16 @@ fn integration_test_replace_accept_replace() {
         // peg spot rate
         assert_ok!(OracleCall::set_exchange_rate(1).dispatch(origin_of(account_of(BOB))));
         // bob creates a vault
-        assert_ok!(VaultRegistryCall::register_vault(amount, H160([0; 20]))
-            .dispatch(origin_of(account_of(ALICE))));
+        assert_ok!(
+            VaultRegistryCall::register_vault(amount, BtcAddress::default())
+                .dispatch(origin_of(account_of(ALICE)))
+        );
         // alice creates a vault
-        assert_ok!(VaultRegistryCall::register_vault(amount, H160([0; 20]))
-            .dispatch(origin_of(account_of(BOB))));
+        assert_ok!(VaultRegistryCall::register_vault(
+            amount,
+            BtcAddress::P2PKH(H160::from([1; 20]))
+        )
+        .dispatch(origin_of(account_of(BOB))));
         // bob requests a replace
         assert_ok!(ReplaceCall::request_replace(amount, griefing_collateral)
             .dispatch(origin_of(account_of(BOB))));
@@ -110,18 +119,19 @@ fn integration_test_replace_auction_replace() {
         let new_vault = BOB;
         let collateral = 4_000;
         let polkabtc = 1_000;
-        let vault_btc_address = H160([0u8; 20]);
+        let old_vault_btc_address = BtcAddress::default();
+        let new_vault_btc_address = BtcAddress::P2PKH(H160::from([1; 20]));
 
         set_default_thresholds();
         // peg spot rate
         assert_ok!(OracleCall::set_exchange_rate(1).dispatch(origin_of(account_of(BOB))));
 
         // old vault has issued some tokens with the user
-        force_issue_tokens(user, old_vault, collateral, polkabtc, vault_btc_address);
+        force_issue_tokens(user, old_vault, collateral, polkabtc, old_vault_btc_address);
 
         // new vault joins
         assert_ok!(
-            VaultRegistryCall::register_vault(collateral, vault_btc_address)
+            VaultRegistryCall::register_vault(collateral, new_vault_btc_address)
                 .dispatch(origin_of(account_of(new_vault)))
         );
         // exchange rate drops and vault is not collateralized any more
@@ -143,7 +153,8 @@ fn integration_test_replace_execute_replace() {
         let griefing_collateral = 50;
         let collateral = 4_000;
         let polkabtc = 1_000;
-        let vault_btc_address = H160([0u8; 20]);
+        let old_vault_btc_address = BtcAddress::default();
+        let new_vault_btc_address = BtcAddress::P2PKH(H160::from([1; 20]));
 
         set_default_thresholds();
         SystemModule::set_block_number(1);
@@ -152,11 +163,11 @@ fn integration_test_replace_execute_replace() {
         assert_ok!(OracleCall::set_exchange_rate(1).dispatch(origin_of(account_of(BOB))));
 
         // old vault has issued some tokens with the user
-        force_issue_tokens(user, old_vault, collateral, polkabtc, vault_btc_address);
+        force_issue_tokens(user, old_vault, collateral, polkabtc, old_vault_btc_address);
 
         // new vault joins
         assert_ok!(
-            VaultRegistryCall::register_vault(collateral, vault_btc_address)
+            VaultRegistryCall::register_vault(collateral, new_vault_btc_address)
                 .dispatch(origin_of(account_of(new_vault)))
         );
 
@@ -171,7 +182,7 @@ fn integration_test_replace_execute_replace() {
 
         // send the btc from the old_vault to the new_vault
         let (tx_id, tx_block_height, merkle_proof, raw_tx) =
-            generate_transaction_and_mine(vault_btc_address, polkabtc, replace_id);
+            generate_transaction_and_mine(new_vault_btc_address, polkabtc, replace_id);
 
         let r =
             ReplaceCall::execute_replace(replace_id, tx_id, tx_block_height, merkle_proof, raw_tx)
@@ -191,11 +202,15 @@ fn integration_test_replace_cancel_replace() {
         // peg spot rate
         assert_ok!(OracleCall::set_exchange_rate(1).dispatch(origin_of(account_of(BOB))));
         // bob creates a vault
-        assert_ok!(VaultRegistryCall::register_vault(amount, H160([0; 20]))
-            .dispatch(origin_of(account_of(ALICE))));
+        assert_ok!(
+            VaultRegistryCall::register_vault(amount, BtcAddress::default())
+                .dispatch(origin_of(account_of(ALICE)))
+        );
         // alice creates a vault
-        assert_ok!(VaultRegistryCall::register_vault(10, H160([0; 20]))
-            .dispatch(origin_of(account_of(BOB))));
+        assert_ok!(
+            VaultRegistryCall::register_vault(10, BtcAddress::P2PKH(H160::from([1; 20])))
+                .dispatch(origin_of(account_of(BOB)))
+        );
         // bob requests a replace
         assert_ok!(ReplaceCall::request_replace(amount, griefing_collateral)
             .dispatch(origin_of(account_of(BOB))));
@@ -1,7 +1,8 @@
+use btc_parachain_runtime::bitcoin::network::Network;
 use btc_parachain_runtime::{
-    AccountId, AuraConfig, BTCRelayConfig, DOTConfig, ExchangeRateOracleConfig, GenesisConfig,
-    GrandpaConfig, PolkaBTCConfig, Signature, StakedRelayersConfig, SudoConfig, SystemConfig,
-    VaultRegistryConfig, WASM_BINARY,
+    AccountId, AuraConfig, BTCRelayConfig, DOTConfig, ExchangeRateOracleConfig, FeeConfig,
+    GenesisConfig, GrandpaConfig, IssueConfig, PolkaBTCConfig, RedeemConfig, ReplaceConfig,
+    Signature, StakedRelayersConfig, SudoConfig, SystemConfig, VaultRegistryConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -52,6 +53,7 @@ pub fn development_config() -> ChainSpec {
                     get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
                     get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
                 ],
+                Network::Regtest,
                 true,
             )
         },
@@ -83,6 +85,7 @@ pub fn local_testnet_config() -> ChainSpec {
                     get_account_id_from_seed::<sr25519::Public>("Eve"),
                     get_account_id_from_seed::<sr25519::Public>("Ferdie"),
                 ],
+                Network::Regtest,
                 true,
             )
         },
@@ -98,6 +101,7 @@ fn testnet_genesis(
     initial_authorities: Vec<(AuraId, GrandpaId)>,
     root_key: AccountId,
     endowed_accounts: Vec<AccountId>,
+    bitcoin_network: Network,
     _enable_println: bool,
 ) -> GenesisConfig {
     GenesisConfig {
@@ -129,9 +133,30 @@ fn testnet_genesis(
         exchange_rate_oracle: Some(ExchangeRateOracleConfig {
             admin: get_account_id_from_seed::<sr25519::Public>("BOB"),
         }),
-        btc_relay: Some(BTCRelayConfig { confirmations: 6 }),
+        btc_relay: Some(BTCRelayConfig {
+            confirmations: 6,
+            bitcoin_network,
+            pruning_depth: 4032,
+            max_pruned_per_block: 100,
+        }),
         vault_registry: Some(VaultRegistryConfig {
+            redeem_premium_fee: 5000, // 5%
             secure_collateral_threshold: 100000,
+            failed_redeem_threshold: 3,
+            failed_redeem_window: 14400,      // one day
+            liquidation_redeem_premium: 5000, // 5%
+        }),
+        fee: Some(FeeConfig {
+            issue_fee: 500,  // 0.5%
+            redeem_fee: 500, // 0.5%
+        }),
+        issue: Some(IssueConfig {
+            issue_griefing_collateral_rate: 5000, // 5%
+            griefing_collateral_fee_share: 10000, // 10%
+        }),
+        redeem: Some(RedeemConfig { redeem_period: 10 }),
+        replace: Some(ReplaceConfig {
+            replace_griefing_collateral_rate: 5000, // 5%
         }),
     }
 }
@@ -46,6 +46,22 @@ macro_rules! new_full_start {
                 prometheus_registry,
             ))
         })?
+        .with_rpc_extensions(
+            |builder| -> Result<jsonrpc_core::IoHandler<sc_rpc::Metadata>, _> {
+                use btc_relay_rpc::{BtcRelay, BtcRelayApi};
+                use issue_rpc::{Issue, IssueApi};
+                use redeem_rpc::{Redeem, RedeemApi};
+                use replace_rpc::{Replace, ReplaceApi};
+
+                let client = builder.client().clone();
+                let mut io = jsonrpc_core::IoHandler::default();
+                io.extend_with(BtcRelayApi::to_delegate(BtcRelay::new(client.clone())));
+                io.extend_with(IssueApi::to_delegate(Issue::new(client.clone())));
+                io.extend_with(RedeemApi::to_delegate(Redeem::new(client.clone())));
+                io.extend_with(ReplaceApi::to_delegate(Replace::new(client)));
+                Ok(io)
+            },
+        )?
         .with_import_queue(
             |_config, client, mut select_chain, _transaction_pool, spawn_task_handle| {
                 let select_chain = select_chain